@@ -0,0 +1,48 @@
+//! Timing of the vectorized distance against the two-row distance of the generic closure path.
+//!
+//! Run with `cargo bench --features simd --bench lanes`.
+
+use std::{hint::black_box, time::Instant};
+
+use dtw_rs::{DynamicTimeWarping, LaneCost, Parameters};
+
+fn series(len: usize, seed: u64) -> Vec<f64> {
+    (0..len)
+        .map(|k| ((k as u64 * 2654435761 + seed) % 1000) as f64 / 100.0)
+        .collect()
+}
+
+fn time<R>(label: &str, runs: u32, f: impl Fn() -> R) -> f64 {
+    let start = Instant::now();
+    for _ in 0..runs {
+        black_box(f());
+    }
+    let seconds = start.elapsed().as_secs_f64() / runs as f64;
+    println!("{label:<24} {:>10.3} ms", seconds * 1e3);
+    seconds
+}
+
+fn main() {
+    for len in [1_000, 4_000] {
+        let (a, b) = (series(len, 3), series(len, 11));
+        let (a32, b32) = (
+            a.iter().map(|x| *x as f32).collect::<Vec<f32>>(),
+            b.iter().map(|x| *x as f32).collect::<Vec<f32>>(),
+        );
+        println!("{len} x {len}");
+        let scalar = time("closure f64", 5, || {
+            DynamicTimeWarping::distance_only(&a, &b, |x, y| f64::abs(x - y), Parameters::new())
+        });
+        let f64_lanes = time("vectorized f64", 5, || {
+            DynamicTimeWarping::vectorized_distance(&a, &b, LaneCost::Absolute)
+        });
+        let f32_lanes = time("vectorized f32", 5, || {
+            DynamicTimeWarping::vectorized_distance(&a32, &b32, LaneCost::Absolute)
+        });
+        println!(
+            "speedup                  {:>10.1}x f64, {:.1}x f32",
+            scalar / f64_lanes,
+            scalar / f32_lanes
+        );
+    }
+}
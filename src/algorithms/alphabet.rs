@@ -0,0 +1,120 @@
+use std::ops::Add;
+
+use super::{dynamic_programming::Element, restriction::Restriction, utils::Matrix};
+
+/// Costs of aligning sequences over a finite alphabet of `k` symbols `0..k`.
+///
+/// A diagonal step costs the substitution of `a[i]` by `b[j]`, a vertical step the deletion of
+/// `a[i]` and a horizontal step the insertion of `b[j]`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CostTable<D> {
+    substitution: Matrix<D>,
+    insertion: Vec<D>,
+    deletion: Vec<D>,
+}
+
+impl<D: Clone> CostTable<D> {
+    /// Cost table from the `k × k` substitution costs and the per-symbol insertion and deletion
+    /// costs.
+    pub fn new(substitution: Vec<Vec<D>>, insertion: Vec<D>, deletion: Vec<D>) -> Self {
+        let k = substitution.len();
+        assert!(
+            substitution.iter().all(|row| row.len() == k),
+            "Substitution costs should be a square table"
+        );
+        assert!(
+            insertion.len() == k && deletion.len() == k,
+            "Insertion and deletion costs should have one cost per symbol"
+        );
+        Self {
+            substitution: Matrix::from_iter(substitution.into_iter().flatten(), k, k),
+            insertion,
+            deletion,
+        }
+    }
+
+    /// Cost table with the substitution costs `substitution`, where insertions and deletions
+    /// cost as much as substituting a symbol by itself.
+    pub fn substitution(substitution: Vec<Vec<D>>) -> Self {
+        let diagonal = (0..substitution.len())
+            .map(|s| substitution[s][s].clone())
+            .collect::<Vec<D>>();
+        Self::new(substitution, diagonal.clone(), diagonal)
+    }
+
+    /// Number of symbols of the alphabet.
+    pub fn symbols(&self) -> usize {
+        self.insertion.len()
+    }
+
+    /// Cost of substituting the symbol `x` by the symbol `y`.
+    pub fn substitution_cost(&self, x: usize, y: usize) -> &D {
+        &self.substitution[(x, y)]
+    }
+}
+
+/// Accumulate the costs of the symbols `a` and `b` using `table`, recording the chosen step at
+/// each cell in `trace` in the order of the symmetric step pattern.
+pub(crate) fn optimize_table<D: Clone + PartialOrd + Add<D, Output = D>>(
+    matrix: &mut Matrix<Element<D>>,
+    trace: &mut Matrix<Option<u8>>,
+    restriction: &Restriction,
+    a: &[usize],
+    b: &[usize],
+    table: &CostTable<D>,
+) {
+    restriction.iter(matrix.shape()).for_each(|(i, j)| {
+        if (i, j) == (0, 0) {
+            matrix[(0, 0)] = Element::Value(table.substitution_cost(a[0], b[0]).clone());
+            return;
+        }
+        let candidates = [
+            (i != 0 && j != 0).then(|| {
+                matrix[(i - 1, j - 1)].clone()
+                    + Element::Value(table.substitution_cost(a[i], b[j]).clone())
+            }),
+            (i != 0)
+                .then(|| matrix[(i - 1, j)].clone() + Element::Value(table.deletion[a[i]].clone())),
+            (j != 0).then(|| {
+                matrix[(i, j - 1)].clone() + Element::Value(table.insertion[b[j]].clone())
+            }),
+        ];
+        let best = candidates
+            .into_iter()
+            .enumerate()
+            .filter_map(|(m, cost)| cost.map(|cost| (m, cost)))
+            .reduce(|best, candidate| {
+                if candidate.1 < best.1 {
+                    candidate
+                } else {
+                    best
+                }
+            });
+        if let Some((m, cost)) = best.filter(|(_, cost)| *cost != Element::Inf) {
+            matrix[(i, j)] = cost;
+            trace[(i, j)] = Some(m as u8);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CostTable;
+
+    #[test]
+    fn cost_table() {
+        let table = CostTable::new(vec![vec![0, 3], vec![2, 0]], vec![1, 5], vec![4, 1]);
+        assert_eq!(table.symbols(), 2);
+        assert_eq!(*table.substitution_cost(0, 1), 3);
+        assert_eq!(*table.substitution_cost(1, 0), 2);
+        let table = CostTable::substitution(vec![vec![1, 3], vec![2, 0]]);
+        assert_eq!(table.insertion, [1, 0]);
+        assert_eq!(table.deletion, [1, 0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn cost_table_not_square() {
+        CostTable::new(vec![vec![0, 1], vec![1]], vec![1, 1], vec![1, 1]);
+    }
+}
@@ -0,0 +1,171 @@
+use std::ops::{Add, Sub};
+
+use super::{
+    dynamic_programming::{DynamicTimeWarping, Parameters},
+    normalization::Normalization,
+};
+use crate::{Algorithm, Cost};
+
+/// Alignment of two sequences forced through known corresponding cells, decomposed into
+/// independent alignments of the segments between consecutive anchors.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Anchored<D> {
+    segments: Vec<DynamicTimeWarping<D>>,
+    offsets: Vec<(usize, usize)>,
+    distance: D,
+}
+
+impl<D> Anchored<D>
+where
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Sub<D, Output = D> + Cost,
+{
+    /// Dynamic time warping between `a` and `b` through the cells `anchors`, using the distance
+    /// closure `distance`.
+    pub fn with_closure<T>(
+        a: &[T],
+        b: &[T],
+        anchors: &[(usize, usize)],
+        distance: impl Fn(&T, &T) -> D,
+    ) -> Self {
+        Self::with_closure_and_param(a, b, anchors, distance, Parameters::default())
+    }
+
+    /// Dynamic time warping between `a` and `b` through the cells `anchors`, using the distance
+    /// closure `distance` and the hyper-parameters `hyper_parameters` within every segment.
+    ///
+    /// The segments accumulate their costs without normalization nor square root, which are
+    /// applied once to the accumulated cost of the whole path.
+    pub fn with_closure_and_param<T>(
+        a: &[T],
+        b: &[T],
+        anchors: &[(usize, usize)],
+        distance: impl Fn(&T, &T) -> D,
+        hyper_parameters: impl Into<Parameters>,
+    ) -> Self {
+        assert!(
+            !a.is_empty() && !b.is_empty(),
+            "Sequences should not be empty"
+        );
+        let hyper_parameters = hyper_parameters.into();
+        let additive = hyper_parameters
+            .clone()
+            .normalization(Normalization::None)
+            .square_root(false);
+        let mut bounds = vec![(0, 0)];
+        bounds.extend_from_slice(anchors);
+        bounds.push((a.len() - 1, b.len() - 1));
+        bounds.dedup();
+        assert!(
+            bounds
+                .windows(2)
+                .all(|w| w[0].0 <= w[1].0 && w[0].1 <= w[1].1),
+            "Anchors should be sorted in both sequences and lie within them"
+        );
+        if bounds.len() == 1 {
+            bounds.push(bounds[0]);
+        }
+        let segments = bounds
+            .windows(2)
+            .map(|w| {
+                DynamicTimeWarping::with_closure_and_param(
+                    &a[w[0].0..=w[1].0],
+                    &b[w[0].1..=w[1].1],
+                    &distance,
+                    additive.clone(),
+                )
+            })
+            .collect::<Vec<DynamicTimeWarping<D>>>();
+        // Every anchor is the last cell of a segment and the first cell of the next one, whose
+        // local cost is removed as that segment computed it.
+        let accumulated = bounds.windows(2).skip(1).fold(
+            segments
+                .iter()
+                .fold(D::default(), |acc, segment| acc + segment.distance()),
+            |acc, w| {
+                let shape = (w[1].0 - w[0].0 + 1, w[1].1 - w[0].1 + 1);
+                let local =
+                    additive.local_cost(shape, |i, j| distance(&a[w[0].0 + i], &b[w[0].1 + j]));
+                acc - local(0, 0)
+            },
+        );
+        let path_length = segments
+            .iter()
+            .map(|segment| segment.path().len() - 1)
+            .sum::<usize>()
+            + 1;
+        let distance = hyper_parameters.normalize(&accumulated, (a.len(), b.len()), path_length);
+        bounds.pop();
+        Self {
+            segments,
+            offsets: bounds,
+            distance,
+        }
+    }
+
+    /// Alignments of the segments between consecutive anchors, whose distances are their
+    /// accumulated costs before normalization.
+    pub fn segments(&self) -> &[DynamicTimeWarping<D>] {
+        &self.segments
+    }
+
+    /// Cells the path is forced through, without the first and the last cells of the matrix.
+    pub fn anchors(&self) -> &[(usize, usize)] {
+        &self.offsets[1..]
+    }
+
+    /// Warped distance between the sequences, counting the cost of every anchor once.
+    pub fn distance(&self) -> D {
+        self.distance.clone()
+    }
+
+    /// Warped path between the sequences, passing through every anchor.
+    pub fn path(&self) -> Vec<(usize, usize)> {
+        let mut path = vec![(0, 0)];
+        for (segment, (oi, oj)) in self.segments.iter().zip(&self.offsets) {
+            path.extend(
+                segment
+                    .path()
+                    .into_iter()
+                    .skip(1)
+                    .map(|(i, j)| (i + oi, j + oj)),
+            );
+        }
+        path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Anchored;
+    use crate::{Algorithm, DynamicTimeWarping, Parameters};
+
+    #[test]
+    fn anchored_path_passes_through_anchors() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let free = DynamicTimeWarping::between(&a, &b);
+        let same = Anchored::with_closure(&a, &b, &[(2, 3)], |x, y| f64::abs(x - y));
+        assert_eq!(same.distance(), free.distance());
+        assert_eq!(same.path(), free.path());
+        let anchored = Anchored::with_closure(&a, &b, &[(1, 4)], |x, y| f64::abs(x - y));
+        assert_eq!(anchored.segments().len(), 2);
+        assert_eq!(anchored.anchors(), &[(1, 4)]);
+        assert!(anchored.path().contains(&(1, 4)));
+        assert!(anchored.distance() > free.distance());
+        let cost = anchored
+            .path()
+            .iter()
+            .map(|(i, j)| f64::abs(a[*i] - b[*j]))
+            .sum::<f64>();
+        assert_eq!(anchored.distance(), cost);
+        let euclidean = DynamicTimeWarping::with_param(&a, &b, Parameters::euclidean());
+        let on_path = Anchored::with_closure_and_param(
+            &a,
+            &b,
+            &[(2, 3)],
+            |x, y| f64::abs(x - y),
+            Parameters::euclidean(),
+        );
+        assert!((on_path.distance() - euclidean.distance()).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,170 @@
+use std::{
+    ops::Add,
+    time::{Duration, Instant},
+};
+
+use super::{
+    dynamic_programming::{DynamicTimeWarping, Element},
+    restriction::{Restriction, Window},
+};
+use crate::{Algorithm, Cost};
+
+/// Budget after which an [`Anytime`] computation stops.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Budget {
+    /// Maximum number of cells of the matrix to compute.
+    Cells(usize),
+    /// Maximum duration of the computation.
+    Time(Duration),
+}
+
+/// Bounds on the warped distance between two sequences, computed within a budget.
+///
+/// An upper bound is first computed exactly within a narrow corridor around the diagonal. The
+/// rows of the full matrix are then computed one by one until the budget is exhausted, every
+/// completed row giving a lower bound since any path crosses it. Both bounds equal the distance
+/// once every row is completed.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Anytime<D> {
+    lower: D,
+    upper: D,
+    rows: usize,
+    exact: bool,
+    corridor: Window,
+}
+
+impl<D> Anytime<D>
+where
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost,
+{
+    /// Bounds on the warped distance between `a` and `b` using the distance closure `distance`,
+    /// computed within `budget`. The upper bound is always computed, even if it alone exceeds
+    /// the budget.
+    pub fn with_closure<T>(
+        a: &[T],
+        b: &[T],
+        distance: impl Fn(&T, &T) -> D,
+        budget: Budget,
+    ) -> Self {
+        let started = Instant::now();
+        let (n, m) = (a.len(), b.len());
+        let diagonal = Window::from_guides(&[], (n, m), 0);
+        let mut cells = (0..n)
+            .map(|i| diagonal.row(i))
+            .map(|(rb, re)| re - rb)
+            .sum::<usize>();
+        let upper = DynamicTimeWarping::with_closure_and_param(
+            a,
+            b,
+            &distance,
+            Restriction::Window(diagonal),
+        )
+        .distance();
+        let exhausted = |cells: usize| match budget {
+            Budget::Cells(max) => cells > max,
+            Budget::Time(max) => started.elapsed() > max,
+        };
+        let mut ranges = vec![(0, m); n];
+        let mut lower = Element::Value(D::default());
+        let mut rows = 0;
+        let mut previous: Vec<Element<D>> = Vec::new();
+        while rows < n && !exhausted(cells + m) {
+            let i = rows;
+            let mut row: Vec<Element<D>> = Vec::with_capacity(m);
+            for j in 0..m {
+                let best = [
+                    (i > 0 && j > 0).then(|| previous[j - 1].clone()),
+                    (i > 0).then(|| previous[j].clone()),
+                    (j > 0).then(|| row[j - 1].clone()),
+                ]
+                .into_iter()
+                .flatten()
+                .reduce(|best, e| if e < best { e } else { best })
+                .unwrap_or(Element::Value(D::default()));
+                row.push(best + Element::Value(distance(&a[i], &b[j])));
+            }
+            lower = row
+                .iter()
+                .cloned()
+                .reduce(|best, e| if e < best { e } else { best })
+                .unwrap();
+            let admitted = row
+                .iter()
+                .map(|e| *e <= Element::Value(upper.clone()))
+                .collect::<Vec<bool>>();
+            ranges[i] = match (
+                admitted.iter().position(|x| *x),
+                admitted.iter().rposition(|x| *x),
+            ) {
+                (Some(rb), Some(re)) => (rb, re + 1),
+                _ => (0, 0),
+            };
+            previous = row;
+            cells += m;
+            rows += 1;
+        }
+        if rows == n {
+            lower = previous.pop().unwrap();
+        }
+        let Element::Value(lower) = lower else {
+            panic!("Infinit distance")
+        };
+        Self {
+            upper: if rows == n { lower.clone() } else { upper },
+            lower,
+            rows,
+            exact: rows == n,
+            corridor: Window::new(ranges),
+        }
+    }
+
+    /// Lower bound on the warped distance.
+    pub fn lower(&self) -> D {
+        self.lower.clone()
+    }
+
+    /// Upper bound on the warped distance.
+    pub fn upper(&self) -> D {
+        self.upper.clone()
+    }
+
+    /// Whether the computation completed within the budget, such that the bounds are equal to
+    /// the warped distance.
+    pub fn is_exact(&self) -> bool {
+        self.exact
+    }
+
+    /// Number of rows of the full matrix completed within the budget.
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Corridor of the cells that may still lie on an optimal path: the cells of the completed
+    /// rows not exceeding the upper bound, and every cell of the remaining rows.
+    pub fn corridor(&self) -> &Window {
+        &self.corridor
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Anytime, Budget};
+    use crate::{Algorithm, DynamicTimeWarping};
+
+    #[test]
+    fn anytime_bounds() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let exact = DynamicTimeWarping::between(&a, &b).distance();
+        let distance = |x: &f64, y: &f64| f64::abs(x - y);
+        let partial = Anytime::with_closure(&a, &b, distance, Budget::Cells(22));
+        assert_eq!(partial.rows(), 2);
+        assert!(!partial.is_exact());
+        assert!(partial.lower() <= exact && exact <= partial.upper());
+        assert!(partial.corridor().row(0).1 <= 6);
+        assert_eq!(partial.corridor().row(4), (0, 6));
+        let complete = Anytime::with_closure(&a, &b, distance, Budget::Cells(usize::MAX));
+        assert!(complete.is_exact());
+        assert_eq!((complete.lower(), complete.upper()), (exact, exact));
+    }
+}
@@ -0,0 +1,147 @@
+use super::{dynamic_programming::path_stop, restriction::Restriction};
+
+/// Sequence of bits packed in 64-bit words, such as a binarized activity trace.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Bitmap {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl Bitmap {
+    /// Bitmap of the bits `bits`.
+    pub fn new(bits: &[bool]) -> Self {
+        let words = bits
+            .chunks(64)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0, |word, (k, bit)| word | (u64::from(*bit) << k))
+            })
+            .collect();
+        Self {
+            words,
+            len: bits.len(),
+        }
+    }
+
+    /// Bitmap of the first `len` bits of `words`, starting from the least significant bit of the
+    /// first word.
+    pub fn from_words(words: Vec<u64>, len: usize) -> Self {
+        assert!(len <= words.len() * 64, "Words should hold `len` bits");
+        Self { words, len }
+    }
+
+    /// Number of bits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the bitmap has no bit.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Bit at index `i`.
+    pub fn get(&self, i: usize) -> bool {
+        assert!(i < self.len, "Index out of bounds");
+        self.words[i / 64] >> (i % 64) & 1 == 1
+    }
+}
+
+impl From<&[bool]> for Bitmap {
+    fn from(bits: &[bool]) -> Self {
+        Self::new(bits)
+    }
+}
+
+/// Warped distance between the bitmaps `a` and `b` with a cost of one for every pair of different
+/// bits, optionally restricted to a Sakoe-Chiba band of width `band`. The path stops at the last
+/// cell of the band as with [`Restriction::Band`] if the bottom-right corner lies outside of it.
+///
+/// The costs of a row are computed 64 at a time by comparing whole words, and only two rows of
+/// the matrix are kept.
+pub fn binary_distance(a: &Bitmap, b: &Bitmap, band: Option<usize>) -> usize {
+    assert!(
+        !a.is_empty() && !b.is_empty(),
+        "Sequences should not be empty"
+    );
+    let m = b.len();
+    let stop = match band {
+        Some(band) => path_stop(&Restriction::Band(band), (a.len(), m)),
+        None => (a.len() - 1, m - 1),
+    };
+    let mut previous = vec![usize::MAX; m];
+    let mut current = vec![usize::MAX; m];
+    let mut costs = vec![0_u8; m];
+    for i in 0..=stop.0 {
+        let (rb, re) = match band {
+            Some(band) => (i.saturating_sub(band).min(m), (i + band + 1).min(m)),
+            None => (0, m),
+        };
+        let mask = if a.get(i) { u64::MAX } else { 0 };
+        for w in rb / 64..re.div_ceil(64) {
+            let diff = b.words[w] ^ mask;
+            let (begin, end) = ((w * 64).max(rb), ((w + 1) * 64).min(re));
+            for (j, cost) in costs[begin..end].iter_mut().enumerate() {
+                *cost = (diff >> ((begin + j) % 64) & 1) as u8;
+            }
+        }
+        current.fill(usize::MAX);
+        for j in rb..re {
+            let best = match (i, j) {
+                (0, 0) => 0,
+                (0, _) => current[j - 1],
+                (_, 0) => previous[j],
+                _ => previous[j - 1].min(previous[j]).min(current[j - 1]),
+            };
+            current[j] = best.saturating_add(costs[j] as usize);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    match previous[stop.1] {
+        usize::MAX => panic!("Infinit distance"),
+        distance => distance,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{binary_distance, Bitmap};
+    use crate::{Algorithm, DynamicTimeWarping, Restriction};
+
+    #[test]
+    fn bitmap_bits() {
+        let bits = (0..130).map(|i| i % 3 == 0).collect::<Vec<bool>>();
+        let bitmap = Bitmap::new(&bits);
+        assert_eq!(bitmap.len(), 130);
+        assert!((0..130).all(|i| bitmap.get(i) == bits[i]));
+        assert_eq!(
+            Bitmap::from_words(vec![0b101], 3),
+            Bitmap::new(&[true, false, true])
+        );
+    }
+
+    #[test]
+    fn binary_matches_closure() {
+        let a = (0..150).map(|i| (i / 7) % 2 == 0).collect::<Vec<bool>>();
+        let b = (0..140).map(|i| (i / 5) % 3 == 0).collect::<Vec<bool>>();
+        let cost = |x: &bool, y: &bool| usize::from(x != y);
+        let (ba, bb) = (Bitmap::new(&a), Bitmap::new(&b));
+        let dtw = DynamicTimeWarping::with_closure(&a, &b, cost);
+        assert_eq!(binary_distance(&ba, &bb, None), dtw.distance());
+        let banded =
+            DynamicTimeWarping::with_closure_and_param(&a[..140], &b, cost, Restriction::Band(10));
+        let ba = Bitmap::new(&a[..140]);
+        assert_eq!(binary_distance(&ba, &bb, Some(10)), banded.distance());
+        for (n, m, band) in [(10, 2, 1), (2, 10, 1), (70, 3, 5), (3, 70, 66)] {
+            let (a, b) = (&a[..n], &b[..m]);
+            let banded =
+                DynamicTimeWarping::with_closure_and_param(a, b, cost, Restriction::Band(band));
+            assert_eq!(
+                binary_distance(&Bitmap::new(a), &Bitmap::new(b), Some(band)),
+                banded.distance()
+            );
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use std::ops::Add;
+
+use super::{
+    dynamic_programming::{path_stop, Element, Parameters},
+    utils::Backpointers,
+};
+use crate::{Algorithm, Cost, ParameterizedAlgorithm};
+
+/// Dynamic time warping keeping only two rows of accumulated costs and the predecessor chosen at
+/// each cell, packed in two bits, when the path and the distance are all that is needed.
+///
+/// The memory footprint is a quarter of a byte per cell instead of the size of an accumulated
+/// cost, and the path is recovered by following the predecessors. Only the single-step patterns
+/// are supported.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Compact<D> {
+    distance: D,
+    stop: (usize, usize),
+    predecessors: &'static [(usize, usize)],
+    backpointers: Backpointers,
+}
+
+impl<D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost> Algorithm<D> for Compact<D> {
+    fn with_closure<T>(a: &[T], b: &[T], distance: impl Fn(&T, &T) -> D) -> Self {
+        Self::with_closure_and_param(a, b, distance, Parameters::default())
+    }
+
+    fn distance(&self) -> D {
+        self.distance.clone()
+    }
+
+    fn path(&self) -> Vec<(usize, usize)> {
+        self.backpointers
+            .path(self.stop.0, self.stop.1, self.predecessors)
+    }
+}
+
+impl<D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost> ParameterizedAlgorithm<D>
+    for Compact<D>
+{
+    type Param = Parameters;
+
+    fn with_closure_and_param<T>(
+        a: &[T],
+        b: &[T],
+        distance: impl Fn(&T, &T) -> D,
+        hyper_parameters: Self::Param,
+    ) -> Self {
+        let shape = (a.len(), b.len());
+        let predecessors = hyper_parameters.single_step_pattern().predecessors();
+        let restriction = hyper_parameters.global_restriction(shape);
+        let distance = hyper_parameters.local_cost(shape, |i, j| distance(&a[i], &b[j]));
+        let stop = path_stop(&restriction, shape);
+        let mut backpointers = Backpointers::new(shape);
+        let mut previous: Vec<Element<D>> = vec![Element::Inf; shape.1];
+        let mut current: Vec<Element<D>> = vec![Element::Inf; shape.1];
+        let mut distance_at_stop = Element::Inf;
+        for i in 0..shape.0 {
+            current.fill(Element::Inf);
+            for j in restriction.row_range(shape, i) {
+                let best = predecessors
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (di, dj))| *di <= i && *dj <= j)
+                    .map(|(p, (di, dj))| match di {
+                        0 => (p, &current[j - dj]),
+                        _ => (p, &previous[j - dj]),
+                    })
+                    .reduce(|best, candidate| {
+                        if candidate.1 < best.1 {
+                            candidate
+                        } else {
+                            best
+                        }
+                    });
+                current[j] = match best {
+                    Some((p, cost)) => {
+                        backpointers.set((i, j), Some(p));
+                        cost.clone() + Element::Value(distance(i, j))
+                    }
+                    None if (i, j) == (0, 0) => Element::Value(distance(i, j)),
+                    None => Element::Inf,
+                };
+            }
+            if i == stop.0 {
+                distance_at_stop = current[stop.1].clone();
+            }
+            std::mem::swap(&mut previous, &mut current);
+        }
+        let Element::Value(distance) = distance_at_stop else {
+            panic!("Infinit distance")
+        };
+        let path_length = backpointers.path(stop.0, stop.1, predecessors).len();
+        Self {
+            distance: hyper_parameters.normalize(&distance, shape, path_length),
+            stop,
+            predecessors,
+            backpointers,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Compact;
+    use crate::{
+        Algorithm, DynamicTimeWarping, Landmark, ParameterizedAlgorithm, Parameters, Restriction,
+        StepPattern,
+    };
+
+    #[test]
+    fn compact_matches_full_matrix() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0, 4.0, 4.0, 0.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0, 5.0, 1.0, 1.0, 3.0];
+        for param in [
+            Parameters::new(),
+            Parameters::new().restriction(Restriction::Band(3)),
+            Parameters::new().step_pattern(StepPattern::Asymmetric),
+            Parameters::new().landmark(Landmark::NoEarlierThan(2, 4)),
+        ] {
+            let full = DynamicTimeWarping::with_param(&a, &b, param.clone());
+            let compact = Compact::with_param(&a, &b, param);
+            assert_eq!(compact.distance(), full.distance());
+            assert_eq!(compact.path(), full.path());
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::{cmp::Ordering, fmt::Display, ops::Add};
+
+use crate::Cost;
+
+/// Cost accumulated with compensated (Kahan-Babuška-Neumaier) summation, carrying the rounding
+/// error of the additions alongside their sum.
+///
+/// Accumulating `Compensated` instead of `f64` keeps the running costs of very long alignments
+/// accurate to the last bit, such that ties are not flipped by rounding errors.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Compensated {
+    sum: f64,
+    compensation: f64,
+}
+
+impl Compensated {
+    /// Compensated cost with the value `value`.
+    pub fn new(value: f64) -> Self {
+        Self {
+            sum: value,
+            compensation: 0.0,
+        }
+    }
+
+    /// Value of the cost, with its rounding error compensated.
+    pub fn value(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+impl From<f64> for Compensated {
+    fn from(value: f64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl From<Compensated> for f64 {
+    fn from(cost: Compensated) -> Self {
+        cost.value()
+    }
+}
+
+impl Add for Compensated {
+    type Output = Compensated;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let sum = self.sum + rhs.sum;
+        let error = if self.sum.abs() >= rhs.sum.abs() {
+            (self.sum - sum) + rhs.sum
+        } else {
+            (rhs.sum - sum) + self.sum
+        };
+        Self {
+            sum,
+            compensation: self.compensation + rhs.compensation + error,
+        }
+    }
+}
+
+impl PartialEq for Compensated {
+    fn eq(&self, other: &Self) -> bool {
+        self.value() == other.value()
+    }
+}
+
+impl PartialOrd for Compensated {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value().partial_cmp(&other.value())
+    }
+}
+
+impl Cost for Compensated {
+    fn weighted(&self, weight: f64) -> Self {
+        Self {
+            sum: self.sum * weight,
+            compensation: self.compensation * weight,
+        }
+    }
+
+    fn mapped(&self, f: impl Fn(f64) -> f64) -> Self {
+        Self::new(f(self.value()))
+    }
+
+    fn is_finite(&self) -> bool {
+        self.value().is_finite()
+    }
+}
+
+impl Display for Compensated {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.value())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Compensated;
+    use crate::{Algorithm, DynamicTimeWarping};
+
+    #[test]
+    fn compensated_accumulation() {
+        let a = vec![0.0; 100_000];
+        let b = [0.1];
+        let naive = DynamicTimeWarping::with_closure(&a, &b, |x, y| f64::abs(x - y)).distance();
+        let compensated = DynamicTimeWarping::with_closure(&a, &b, |x: &f64, y: &f64| {
+            Compensated::new(f64::abs(x - y))
+        })
+        .distance();
+        assert_ne!(naive, 10_000.0);
+        assert_eq!(compensated.value(), 10_000.0);
+        assert_eq!(
+            Compensated::new(1e16) + Compensated::new(1.0) + Compensated::new(-1e16),
+            Compensated::new(1.0)
+        );
+    }
+}
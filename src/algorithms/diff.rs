@@ -0,0 +1,123 @@
+use std::{fmt::Display, ops::Range, ops::Sub};
+
+/// Kind of the steps of a run of a warping path.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum DiffKind {
+    /// Diagonal steps, matching elements of `a` and `b` one to one.
+    Matched,
+    /// Vertical steps, stretching an element of `b` over several elements of `a`.
+    StretchedA,
+    /// Horizontal steps, stretching an element of `a` over several elements of `b`.
+    StretchedB,
+}
+
+/// Maximal run of steps of the same kind along a warping path.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DiffRun<D> {
+    /// Kind of the steps of the run.
+    pub kind: DiffKind,
+    /// Indices of `a` covered by the run.
+    pub a: Range<usize>,
+    /// Indices of `b` covered by the run.
+    pub b: Range<usize>,
+    /// Accumulated cost added by the cells of the run.
+    pub cost: D,
+}
+
+impl DiffKind {
+    fn of_step(from: (usize, usize), to: (usize, usize)) -> Self {
+        match (to.0 > from.0, to.1 > from.1) {
+            (true, false) => DiffKind::StretchedA,
+            (false, true) => DiffKind::StretchedB,
+            _ => DiffKind::Matched,
+        }
+    }
+}
+
+impl Display for DiffKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DiffKind::Matched => write!(f, "matched"),
+            DiffKind::StretchedA => write!(f, "stretched in a"),
+            DiffKind::StretchedB => write!(f, "stretched in b"),
+        }
+    }
+}
+
+impl<D: Display> Display for DiffRun<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} a[{}..{}] b[{}..{}] cost {}",
+            self.kind, self.a.start, self.a.end, self.b.start, self.b.end, self.cost
+        )
+    }
+}
+
+/// Runs of `path`, where `accumulated` is the accumulated cost of each cell.
+pub(crate) fn diff<D: Clone + Default + Sub<D, Output = D>>(
+    path: &[(usize, usize)],
+    accumulated: impl Fn((usize, usize)) -> D,
+) -> Vec<DiffRun<D>> {
+    let mut runs: Vec<DiffRun<D>> = Vec::new();
+    let mut before = D::default();
+    for (k, cell) in path.iter().enumerate() {
+        let kind = match k {
+            0 => DiffKind::Matched,
+            _ => DiffKind::of_step(path[k - 1], *cell),
+        };
+        match runs.last_mut() {
+            Some(run) if run.kind == kind => {
+                run.a.end = cell.0 + 1;
+                run.b.end = cell.1 + 1;
+            }
+            _ => {
+                if k > 0 {
+                    before = accumulated(path[k - 1]);
+                }
+                runs.push(DiffRun {
+                    kind,
+                    a: cell.0..cell.0 + 1,
+                    b: cell.1..cell.1 + 1,
+                    cost: D::default(),
+                });
+            }
+        }
+        runs.last_mut().unwrap().cost = accumulated(*cell) - before.clone();
+    }
+    runs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{diff, DiffKind, DiffRun};
+
+    #[test]
+    fn diff_runs() {
+        let path = [(0, 0), (1, 1), (2, 1), (3, 1), (3, 2), (4, 3)];
+        let accumulated = |(i, j): (usize, usize)| (i + j) as i32;
+        let runs = diff(&path, accumulated);
+        let kinds = runs.iter().map(|r| r.kind).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                DiffKind::Matched,
+                DiffKind::StretchedA,
+                DiffKind::StretchedB,
+                DiffKind::Matched
+            ]
+        );
+        assert_eq!(
+            runs[1],
+            DiffRun {
+                kind: DiffKind::StretchedA,
+                a: 2..4,
+                b: 1..2,
+                cost: 2
+            }
+        );
+        assert_eq!(runs[0].cost, 2);
+        assert_eq!(runs.iter().map(|r| r.cost).sum::<i32>(), 7);
+        assert_eq!(runs[2].to_string(), "stretched in b a[3..4] b[2..3] cost 1");
+    }
+}
@@ -1,13 +1,44 @@
-use std::{cmp::Ordering, fmt::Display, iter::from_fn, ops::Add, usize};
+use std::{
+    cell::{Cell, RefCell},
+    cmp::Ordering,
+    fmt::Display,
+    ops::{Add, Sub},
+};
 
-use super::utils::Matrix;
-use crate::{Algorithm, ParameterizedAlgorithm};
+use super::{
+    alphabet::{optimize_table, CostTable},
+    diff::{diff, DiffRun},
+    max_run::{compute_state_path, optimize_states},
+    normalization::Normalization,
+    restriction::{Landmark, Restriction},
+    similarity::Similarity,
+    step_pattern::{compute_move_path, optimize_moves, StepPattern, TieBreak},
+    transform::Transform,
+    utils::{Backpointers, Layout, Matrix},
+};
+use crate::{Algorithm, Cost, Distance, Error, ParameterizedAlgorithm};
 
 #[derive(Debug, PartialEq, Clone)]
 /// Dynamic time warping computation using the standard dynamic programming method.
 pub struct DynamicTimeWarping<D> {
-    matrix: Matrix<Element<D>>,
+    matrix: Accumulated<D>,
     restriction: Restriction,
+    step_pattern: StepPattern,
+    normalization: Normalization,
+    square_root: bool,
+    trace: Trace<D>,
+    local_costs: Option<Matrix<Option<D>>>,
+}
+
+/// Information needed to recover the path besides the accumulated costs.
+#[derive(Debug, PartialEq, Clone)]
+enum Trace<D> {
+    /// Index of the predecessor chosen at each cell.
+    Backpointers(Backpointers),
+    /// Accumulated costs of every state of the max-run recursion.
+    States(usize, Matrix<Element<D>>),
+    /// Index of the move chosen at each cell.
+    Moves(Matrix<Option<u8>>),
 }
 
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -17,33 +48,194 @@ pub enum Element<T> {
     Value(T),
 }
 
-#[derive(Debug, PartialEq, Clone, Copy, Default)]
-pub enum Restriction {
-    #[default]
-    None,
-    Band(usize),
+/// Accumulated costs of a matrix, written row by row.
+trait CostMatrix<D> {
+    /// Number of rows and columns.
+    fn shape(&self) -> (usize, usize);
+
+    /// Accumulated cost of the cell `idx`.
+    fn cost(&self, idx: (usize, usize)) -> Element<D>;
+
+    /// Set the accumulated costs of the row `i` to `row`.
+    fn set_row(&mut self, i: usize, row: &[Element<D>]);
 }
 
-impl<D: PartialOrd + Clone + Default + Add<D, Output = D>> Algorithm<D> for DynamicTimeWarping<D> {
-    fn with_closure<T>(a: &[T], b: &[T], distance: impl Fn(&T, &T) -> D) -> Self {
-        DynamicTimeWarping::with_closure_and_param(a, b, distance, Restriction::None)
+impl<D: Clone> CostMatrix<D> for Matrix<Element<D>> {
+    fn shape(&self) -> (usize, usize) {
+        Matrix::shape(self)
     }
 
-    fn distance(&self) -> D {
-        let shape = self.matrix.shape();
-        let path_stop = match &self.restriction {
-            Restriction::None => (shape.0 - 1, shape.1 - 1),
-            Restriction::Band(band) => {
-                if shape.0 < shape.1 {
-                    (shape.0 - 1, (shape.1 - 1).min(shape.0 - 1 + band))
-                } else {
-                    ((shape.0 - 1).min(shape.1 - 1 + band), shape.1 - 1)
+    fn cost(&self, idx: (usize, usize)) -> Element<D> {
+        self.cell(idx).clone()
+    }
+
+    fn set_row(&mut self, i: usize, row: &[Element<D>]) {
+        let columns = Matrix::shape(self).1;
+        self.data_mut()[columns * i..columns * (i + 1)].clone_from_slice(row);
+    }
+}
+
+/// Accumulated costs stored as they are if the cost type has an infinity standing for the
+/// unreachable cells, otherwise wrapped in elements, or only those of the cells of a band.
+#[derive(Debug, PartialEq, Clone)]
+enum Accumulated<D> {
+    Plain {
+        costs: Matrix<D>,
+        infinity: D,
+    },
+    Wrapped(Matrix<Element<D>>),
+    Banded {
+        costs: Box<[Element<D>]>,
+        layout: Layout,
+    },
+}
+
+impl<D: Clone + Default + Cost> Accumulated<D> {
+    /// Accumulated costs of shape `shape` with every cell unreachable.
+    fn new(shape: (usize, usize)) -> Self {
+        match D::infinity() {
+            Some(infinity) => Accumulated::Plain {
+                costs: Matrix::fill(infinity.clone(), shape.0, shape.1),
+                infinity,
+            },
+            None => Accumulated::Wrapped(Matrix::fill(Element::Inf, shape.0, shape.1)),
+        }
+    }
+
+    /// Accumulated costs of the cells of `layout`, every cell being unreachable.
+    fn banded(layout: Layout) -> Self {
+        Accumulated::Banded {
+            costs: vec![Element::Inf; layout.len()].into_boxed_slice(),
+            layout,
+        }
+    }
+}
+
+impl<D: Clone + PartialEq> CostMatrix<D> for Accumulated<D> {
+    fn shape(&self) -> (usize, usize) {
+        match self {
+            Accumulated::Plain { costs, .. } => costs.shape(),
+            Accumulated::Wrapped(matrix) => matrix.shape(),
+            Accumulated::Banded { layout, .. } => layout.shape(),
+        }
+    }
+
+    fn cost(&self, idx: (usize, usize)) -> Element<D> {
+        match self {
+            Accumulated::Plain { costs, infinity } => match costs.cell(idx) {
+                cost if cost == infinity => Element::Inf,
+                cost => Element::Value(cost.clone()),
+            },
+            Accumulated::Wrapped(matrix) => matrix.cost(idx),
+            Accumulated::Banded { costs, layout } => layout
+                .position(idx)
+                .map_or(Element::Inf, |k| costs[k].clone()),
+        }
+    }
+
+    fn set_row(&mut self, i: usize, row: &[Element<D>]) {
+        match self {
+            Accumulated::Plain { costs, infinity } => {
+                let columns = costs.shape().1;
+                let cells = &mut costs.data_mut()[columns * i..columns * (i + 1)];
+                for (cell, element) in cells.iter_mut().zip(row) {
+                    *cell = match element {
+                        Element::Inf => infinity.clone(),
+                        Element::Value(v) => v.clone(),
+                    };
                 }
             }
+            Accumulated::Wrapped(matrix) => matrix.set_row(i, row),
+            Accumulated::Banded { costs, layout } => {
+                let columns = layout.columns(i);
+                if let Some(start) = layout.position((i, columns.start)) {
+                    costs[start..start + columns.len()].clone_from_slice(&row[columns]);
+                }
+            }
+        }
+    }
+}
+
+impl<D: Clone + PartialEq + Display> Display for Accumulated<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let shape = self.shape();
+        for i in 0..shape.0 {
+            for j in 0..shape.1 {
+                write!(f, "{} ", self.cost((i, j)))?
+            }
+            writeln!(f)?
+        }
+        Ok(())
+    }
+}
+
+/// Alignment ending at the cheapest cell of the last row or the last column.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FreeCorner<D> {
+    /// Cell at which the alignment ends.
+    pub end: (usize, usize),
+    /// Accumulated cost at `end`.
+    pub distance: D,
+    /// Warped path from `(0, 0)` to `end`.
+    pub path: Vec<(usize, usize)>,
+    /// Number of trailing elements of `a` and `b` left unmatched.
+    pub unmatched: (usize, usize),
+}
+
+/// Step of the warping path matching `a[i]` with `b[j]`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Step<'a, T, D> {
+    /// Index in `a`.
+    pub i: usize,
+    /// Index in `b`.
+    pub j: usize,
+    /// Matched element of `a`.
+    pub a: &'a T,
+    /// Matched element of `b`.
+    pub b: &'a T,
+    /// Cost contributed by the step.
+    pub local_cost: D,
+    /// Sum of the costs contributed by the steps up to this one.
+    pub cumulative_cost: D,
+}
+
+/// Hyper-parameters of the dynamic programming computation.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Parameters {
+    restriction: Restriction,
+    step_pattern: StepPattern,
+    max_run: Option<usize>,
+    landmarks: Vec<Landmark>,
+    transform: Transform,
+    cap: Option<f64>,
+    normalization: Normalization,
+    prune: bool,
+    phase_weight: Option<f64>,
+    retain_local_costs: bool,
+    square_root: bool,
+    tie_break: TieBreak,
+}
+
+impl<D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost> Algorithm<D>
+    for DynamicTimeWarping<D>
+{
+    fn with_closure<T>(a: &[T], b: &[T], distance: impl Fn(&T, &T) -> D) -> Self {
+        DynamicTimeWarping::with_closure_and_param(a, b, distance, Parameters::default())
+    }
+
+    fn distance(&self) -> D {
+        let path_length = match self.normalization {
+            Normalization::PathLength => self.path().len(),
+            _ => 0,
         };
-        match &self.matrix[path_stop] {
-            Element::Inf => panic!("Infinit distance"),
-            Element::Value(v) => v.clone(),
+        let distance = self.normalization.apply(
+            &self.accumulated_distance(),
+            self.matrix.shape(),
+            path_length,
+        );
+        match self.square_root {
+            true => distance.mapped(f64::sqrt),
+            false => distance,
         }
     }
 
@@ -53,10 +245,10 @@ impl<D: PartialOrd + Clone + Default + Add<D, Output = D>> Algorithm<D> for Dyna
     }
 }
 
-impl<D: PartialOrd + Clone + Default + Add<D, Output = D>> ParameterizedAlgorithm<D>
+impl<D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost> ParameterizedAlgorithm<D>
     for DynamicTimeWarping<D>
 {
-    type Param = Restriction;
+    type Param = Parameters;
 
     fn with_closure_and_param<T>(
         a: &[T],
@@ -64,16 +256,15 @@ impl<D: PartialOrd + Clone + Default + Add<D, Output = D>> ParameterizedAlgorith
         distance: impl Fn(&T, &T) -> D,
         hyper_parameters: Self::Param,
     ) -> Self {
-        let mut mat = Matrix::fill(Element::Inf, a.len(), b.len());
-        optimize_matrix(&mut mat, hyper_parameters, |i, j| distance(&a[i], &b[j]));
-        Self {
-            matrix: mat,
-            restriction: hyper_parameters,
-        }
+        Self::with_index_closure(
+            (a.len(), b.len()),
+            |i, j| distance(&a[i], &b[j]),
+            hyper_parameters,
+        )
     }
 }
 
-impl<D: Display> Display for DynamicTimeWarping<D> {
+impl<D: Clone + PartialEq + Display> Display for DynamicTimeWarping<D> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
@@ -84,6 +275,35 @@ impl<D: Display> Display for DynamicTimeWarping<D> {
 }
 
 impl<D: PartialOrd + Clone + Default> DynamicTimeWarping<D> {
+    /// Accumulated cost at the end of the path, before any normalization.
+    fn accumulated_distance(&self) -> D {
+        let path_stop = path_stop(&self.restriction, self.matrix.shape());
+        match self.matrix.cost(path_stop) {
+            Element::Inf => panic!("Infinit distance"),
+            Element::Value(v) => v,
+        }
+    }
+
+    /// Accumulated cost of every cell, or `None` for the cells left unreached by the
+    /// restriction, the step pattern or the pruning.
+    pub fn cost_matrix(&self) -> Matrix<Option<D>> {
+        let shape = self.matrix.shape();
+        Matrix::from_iter(
+            (0..shape.0 * shape.1).map(|k| match self.matrix.cost((k / shape.1, k % shape.1)) {
+                Element::Inf => None,
+                Element::Value(v) => Some(v),
+            }),
+            shape.0,
+            shape.1,
+        )
+    }
+
+    /// Local cost of every cell after its transformation, or `None` for the cells whose cost
+    /// was never computed, if retained by [`Parameters::retain_local_costs`].
+    pub fn local_cost_matrix(&self) -> Option<&Matrix<Option<D>>> {
+        self.local_costs.as_ref()
+    }
+
     pub fn path_from(&self, i: usize, j: usize) -> Vec<(usize, usize)> {
         let shape = self.matrix.shape();
         assert!(
@@ -96,66 +316,732 @@ impl<D: PartialOrd + Clone + Default> DynamicTimeWarping<D> {
             "Dimention 1 should be less than shape.1 = {}",
             shape.1
         );
-        compute_path(&self.matrix, i, j, Restriction::None)
+        match &self.trace {
+            Trace::Backpointers(backpointers) => {
+                compute_path(&self.matrix, backpointers, i, j, self.step_pattern)
+            }
+            Trace::States(max_run, states) => {
+                compute_state_path(states, i, j, self.step_pattern, *max_run)
+            }
+            Trace::Moves(moves) => compute_move_path(moves, i, j, self.step_pattern),
+        }
+    }
+
+    /// Alignment ending at the cheapest cell of the last row or the last column instead of the
+    /// bottom-right corner, e.g. when one of the sequences was truncated.
+    pub fn free_corner(&self) -> FreeCorner<D> {
+        let shape = self.matrix.shape();
+        let last_row = (0..shape.1).map(|j| (shape.0 - 1, j));
+        let last_col = (0..shape.0 - 1).map(|i| (i, shape.1 - 1));
+        let end = last_row
+            .chain(last_col)
+            .filter(|idx| self.matrix.cost(*idx) != Element::Inf)
+            .reduce(|best, idx| {
+                if self.matrix.cost(idx) < self.matrix.cost(best) {
+                    idx
+                } else {
+                    best
+                }
+            })
+            .expect("Infinit distance");
+        let distance = match self.matrix.cost(end) {
+            Element::Inf => unreachable!(),
+            Element::Value(v) => v,
+        };
+        FreeCorner {
+            end,
+            distance,
+            path: self.path_from(end.0, end.1),
+            unmatched: (shape.0 - 1 - end.0, shape.1 - 1 - end.1),
+        }
+    }
+
+    /// Every optimal warping path, i.e. of the least accumulated cost, enumerated lazily as
+    /// their number may grow exponentially with ties, e.g. for constant sequences. Only the
+    /// single-step patterns without max-run constraint are supported.
+    pub fn optimal_paths(&self) -> impl Iterator<Item = Vec<(usize, usize)>> + '_ {
+        self.assert_backpointers();
+        let end = path_stop(&self.restriction, self.matrix.shape());
+        let mut stack = match self.matrix.cost(end) {
+            Element::Inf => vec![],
+            Element::Value(_) => vec![vec![end]],
+        };
+        std::iter::from_fn(move || {
+            while let Some(mut reversed) = stack.pop() {
+                let last = reversed[reversed.len() - 1];
+                if last == (0, 0) {
+                    reversed.reverse();
+                    return Some(reversed);
+                }
+                for predecessor in self.optimal_predecessors(last).into_iter().rev() {
+                    let mut next = reversed.clone();
+                    next.push(predecessor);
+                    stack.push(next);
+                }
+            }
+            None
+        })
+    }
+
+    /// Number of optimal warping paths, saturating at `usize::MAX`. Only the single-step
+    /// patterns without max-run constraint are supported.
+    pub fn optimal_path_count(&self) -> usize {
+        self.assert_backpointers();
+        let end = path_stop(&self.restriction, self.matrix.shape());
+        let mut counts = Matrix::fill(0_usize, end.0 + 1, end.1 + 1);
+        for i in 0..=end.0 {
+            for j in 0..=end.1 {
+                counts[(i, j)] = match ((i, j), self.matrix.cost((i, j))) {
+                    (_, Element::Inf) => 0,
+                    ((0, 0), _) => 1,
+                    _ => self
+                        .optimal_predecessors((i, j))
+                        .iter()
+                        .fold(0, |count, p| count.saturating_add(counts[*p])),
+                };
+            }
+        }
+        counts[end]
+    }
+
+    /// Reached predecessors of the cell `idx` of the least accumulated cost, i.e. through which
+    /// an optimal path reaches `idx`.
+    fn optimal_predecessors(&self, idx: (usize, usize)) -> Vec<(usize, usize)> {
+        let candidates = self
+            .step_pattern
+            .predecessors()
+            .iter()
+            .filter(|(di, dj)| *di <= idx.0 && *dj <= idx.1)
+            .map(|(di, dj)| (idx.0 - di, idx.1 - dj))
+            .filter(|p| self.matrix.cost(*p) != Element::Inf)
+            .collect::<Vec<(usize, usize)>>();
+        let best = candidates
+            .iter()
+            .map(|p| self.matrix.cost(*p))
+            .reduce(|best, cost| if cost < best { cost } else { best });
+        candidates
+            .into_iter()
+            .filter(|p| Some(self.matrix.cost(*p)) == best)
+            .collect()
+    }
+
+    /// Assert that the path is recovered from backpointers, i.e. one predecessor per cell.
+    fn assert_backpointers(&self) {
+        assert!(
+            matches!(self.trace, Trace::Backpointers(_)),
+            "Backpointers require a single-step pattern without max-run constraint"
+        );
     }
 }
 
-impl Restriction {
-    pub fn contains(&self, index: (usize, usize), shape: (usize, usize)) -> bool {
-        let (rb, re) = self.range(shape, index.0);
-        match self {
-            Restriction::None => rb <= index.1 && index.1 < re,
-            Restriction::Band(_) => rb <= index.1 && index.1 < re,
-        }
-    }
-
-    pub fn iter(&self, shape: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
-        let restriction = *self;
-        let mut idx = None;
-        from_fn(move || -> Option<(usize, usize)> {
-            if let Some((i, j)) = idx {
-                match restriction {
-                    Restriction::None => {
-                        if i == shape.0 - 1 && j == shape.1 - 1 {
-                            idx = None
-                        } else if j == shape.1 - 1 {
-                            idx = Some((i + 1, 0));
+impl<D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost> DynamicTimeWarping<D> {
+    /// [`ParameterizedAlgorithm::with_closure_and_param`] with anything convertible to
+    /// [`Parameters`], e.g. a [`Restriction`] or a [`StepPattern`].
+    pub fn with_closure_and_param<T>(
+        a: &[T],
+        b: &[T],
+        distance: impl Fn(&T, &T) -> D,
+        hyper_parameters: impl Into<Parameters>,
+    ) -> Self {
+        <Self as ParameterizedAlgorithm<D>>::with_closure_and_param(
+            a,
+            b,
+            distance,
+            hyper_parameters.into(),
+        )
+    }
+
+    /// [`ParameterizedAlgorithm::with_cost_closure_and_param`] with anything convertible to
+    /// [`Parameters`].
+    pub fn with_cost_closure_and_param<T, C: Into<D>>(
+        a: &[T],
+        b: &[T],
+        distance: impl Fn(&T, &T) -> C,
+        hyper_parameters: impl Into<Parameters>,
+    ) -> Self {
+        <Self as ParameterizedAlgorithm<D>>::with_cost_closure_and_param(
+            a,
+            b,
+            distance,
+            hyper_parameters.into(),
+        )
+    }
+
+    /// [`ParameterizedAlgorithm::with_param`] with anything convertible to [`Parameters`].
+    pub fn with_param<T: Distance<D>>(
+        a: &[T],
+        b: &[T],
+        hyper_parameters: impl Into<Parameters>,
+    ) -> Self {
+        <Self as ParameterizedAlgorithm<D>>::with_param(a, b, hyper_parameters.into())
+    }
+
+    /// Dynamic time warping between the sequences of symbols `a` and `b`, where the cost of each
+    /// cell is looked up in the substitution costs of `table` instead of calling a closure.
+    pub fn with_lookup(
+        a: &[usize],
+        b: &[usize],
+        table: &CostTable<D>,
+        hyper_parameters: impl Into<Parameters>,
+    ) -> Self {
+        assert!(
+            a.iter().chain(b).all(|s| *s < table.symbols()),
+            "Symbols should be less than the number of symbols = {}",
+            table.symbols()
+        );
+        Self::with_index_closure(
+            (a.len(), b.len()),
+            |i, j| table.substitution_cost(a[i], b[j]).clone(),
+            hyper_parameters.into(),
+        )
+    }
+
+    /// [`DynamicTimeWarping::with_closure_and_param`] returning an error instead of panicking
+    /// when a sequence is empty, a local cost is not finite or no path connects the corners, and
+    /// instead of cutting the path off when the band is too narrow.
+    pub fn try_with_closure_and_param<T>(
+        a: &[T],
+        b: &[T],
+        distance: impl Fn(&T, &T) -> D,
+        hyper_parameters: impl Into<Parameters>,
+    ) -> Result<Self, Error> {
+        if a.is_empty() || b.is_empty() {
+            return Err(Error::EmptyInput);
+        }
+        let hyper_parameters = hyper_parameters.into();
+        let minimal = Restriction::minimal_band((a.len(), b.len()));
+        match hyper_parameters.restriction {
+            Restriction::Band(band) if band < minimal => return Err(Error::BandTooNarrow(minimal)),
+            Restriction::Itakura(slope) => {
+                Restriction::itakura(slope)?;
+            }
+            _ => (),
+        }
+        let finite = Cell::new(true);
+        let dtw = Self::with_index_closure(
+            (a.len(), b.len()),
+            |i, j| {
+                let cost = distance(&a[i], &b[j]);
+                if !cost.is_finite() {
+                    finite.set(false);
+                }
+                cost
+            },
+            hyper_parameters,
+        );
+        if !finite.get() {
+            return Err(Error::NonFiniteCost);
+        }
+        dtw.try_distance()?;
+        Ok(dtw)
+    }
+
+    /// [`Algorithm::between`] returning an error instead of panicking. See
+    /// [`DynamicTimeWarping::try_with_closure_and_param`].
+    pub fn try_between<T: Distance<D>>(a: &[T], b: &[T]) -> Result<Self, Error> {
+        Self::try_with_closure_and_param(a, b, |a, b| a.distance(b), Parameters::default())
+    }
+
+    /// [`ParameterizedAlgorithm::with_param`] returning an error instead of panicking. See
+    /// [`DynamicTimeWarping::try_with_closure_and_param`].
+    pub fn try_with_param<T: Distance<D>>(
+        a: &[T],
+        b: &[T],
+        hyper_parameters: impl Into<Parameters>,
+    ) -> Result<Self, Error> {
+        Self::try_with_closure_and_param(a, b, |a, b| a.distance(b), hyper_parameters)
+    }
+
+    /// Warped distance, or [`Error::Unreachable`] instead of panicking if no path connects the
+    /// corners.
+    pub fn try_distance(&self) -> Result<D, Error> {
+        let path_stop = path_stop(&self.restriction, self.matrix.shape());
+        match self.matrix.cost(path_stop) {
+            Element::Inf => Err(Error::Unreachable),
+            Element::Value(_) => Ok(self.distance()),
+        }
+    }
+
+    /// Warped distance between `a` and `b` without the path, keeping only two rows of
+    /// accumulated costs and path lengths such that the memory is linear in the length of `b`.
+    /// Only the single-step patterns are supported.
+    pub fn distance_only<T>(
+        a: &[T],
+        b: &[T],
+        distance: impl Fn(&T, &T) -> D,
+        hyper_parameters: impl Into<Parameters>,
+    ) -> D {
+        Self::two_rows(a, b, distance, hyper_parameters.into(), None).expect("Infinit distance")
+    }
+
+    /// Warped distance between `a` and `b` if it does not exceed `threshold`, e.g. the best
+    /// distance so far of a nearest neighbor search, or `None` otherwise. See
+    /// [`DynamicTimeWarping::with_closure_and_threshold`].
+    pub fn with_threshold<T: Distance<D>>(a: &[T], b: &[T], threshold: D) -> Option<D> {
+        Self::with_closure_and_threshold(
+            a,
+            b,
+            |a, b| a.distance(b),
+            Parameters::default(),
+            threshold,
+        )
+    }
+
+    /// [`DynamicTimeWarping::distance_only`] abandoned as soon as every cell of a row exceeds
+    /// `threshold`, returning `None` if the distance exceeds `threshold`. The local costs should
+    /// not be negative, such that the accumulated costs never decrease along a path.
+    pub fn with_closure_and_threshold<T>(
+        a: &[T],
+        b: &[T],
+        distance: impl Fn(&T, &T) -> D,
+        hyper_parameters: impl Into<Parameters>,
+        threshold: D,
+    ) -> Option<D> {
+        Self::two_rows(a, b, distance, hyper_parameters.into(), Some(threshold))
+    }
+
+    /// Distance computed with two rows, `None` if the end of the path is unreachable or, given a
+    /// threshold, if the distance exceeds it.
+    fn two_rows<T>(
+        a: &[T],
+        b: &[T],
+        distance: impl Fn(&T, &T) -> D,
+        hyper_parameters: Parameters,
+        threshold: Option<D>,
+    ) -> Option<D> {
+        let shape = (a.len(), b.len());
+        let predecessors = hyper_parameters.single_step_pattern().predecessors();
+        let restriction = hyper_parameters.global_restriction(shape);
+        let distance = hyper_parameters.local_cost(shape, |i, j| distance(&a[i], &b[j]));
+        let stop = path_stop(&restriction, shape);
+        let bound = threshold
+            .as_ref()
+            .map(|t| Element::Value(hyper_parameters.accumulated_bound(t, shape)));
+        let mut previous: Vec<(Element<D>, usize)> = vec![(Element::Inf, 0); shape.1];
+        let mut current: Vec<(Element<D>, usize)> = vec![(Element::Inf, 0); shape.1];
+        for i in 0..=stop.0 {
+            current.fill((Element::Inf, 0));
+            let range = restriction.row_range(shape, i);
+            for j in range.clone() {
+                let best = predecessors
+                    .iter()
+                    .filter(|(di, dj)| *di <= i && *dj <= j)
+                    .map(|(di, dj)| match di {
+                        0 => &current[j - dj],
+                        _ => &previous[j - dj],
+                    })
+                    .reduce(|best, candidate| {
+                        if candidate.0 < best.0 {
+                            candidate
                         } else {
-                            idx = Some((i, j + 1));
+                            best
                         }
+                    });
+                current[j] = match best {
+                    Some((cost, length)) => {
+                        (cost.clone() + Element::Value(distance(i, j)), length + 1)
                     }
-                    Restriction::Band(_) => {
-                        let (_, re) = restriction.range(shape, i);
-                        let (next_rb, _) = restriction.range(shape, i + 1);
-                        if (i == shape.0 - 1 && j == re - 1) || (next_rb >= shape.1) {
-                            idx = None
-                        } else if j == re - 1 {
-                            idx = Some((i + 1, next_rb));
-                        } else {
-                            idx = Some((i, j + 1));
-                        }
+                    None if (i, j) == (0, 0) => (Element::Value(distance(i, j)), 1),
+                    None => (Element::Inf, 0),
+                };
+            }
+            if let Some(bound) = &bound {
+                if current[range].iter().all(|(cost, _)| cost > bound) {
+                    return None;
+                }
+            }
+            std::mem::swap(&mut previous, &mut current);
+        }
+        let (Element::Value(distance), length) = &previous[stop.1] else {
+            return None;
+        };
+        let distance = hyper_parameters.normalize(distance, shape, *length);
+        match threshold {
+            Some(threshold) if distance > threshold => None,
+            _ => Some(distance),
+        }
+    }
+
+    fn with_index_closure(
+        shape: (usize, usize),
+        distance: impl Fn(usize, usize) -> D,
+        mut hyper_parameters: Parameters,
+    ) -> Self {
+        hyper_parameters.restriction = hyper_parameters.global_restriction(shape);
+        let distance = hyper_parameters.local_cost(shape, distance);
+        let local_costs = hyper_parameters
+            .retain_local_costs
+            .then(|| RefCell::new(Matrix::fill(None, shape.0, shape.1)));
+        let distance = |i, j| {
+            let cost = distance(i, j);
+            if let Some(local_costs) = &local_costs {
+                local_costs.borrow_mut()[(i, j)] = Some(cost.clone());
+            }
+            cost
+        };
+        assert!(
+            hyper_parameters.max_run.is_none() || hyper_parameters.step_pattern.is_single_step(),
+            "Max-run constraint requires a single-step pattern"
+        );
+        let (matrix, trace) = match hyper_parameters.max_run {
+            Some(max_run) => {
+                let mut mat = Matrix::fill(Element::Inf, shape.0, shape.1);
+                let count = 2 * max_run + 1;
+                let mut states = Matrix::fill(Element::Inf, shape.0, shape.1 * count);
+                optimize_states(
+                    &mut states,
+                    &mut mat,
+                    &hyper_parameters.restriction,
+                    hyper_parameters.step_pattern,
+                    max_run,
+                    distance,
+                );
+                (Accumulated::Wrapped(mat), Trace::States(max_run, states))
+            }
+            None if hyper_parameters.step_pattern.is_single_step() => {
+                let upper_bound = match hyper_parameters.step_pattern {
+                    StepPattern::Symmetric if hyper_parameters.prune => {
+                        staircase_cost(&hyper_parameters.restriction, shape, distance)
                     }
+                    _ => None,
                 };
-            } else {
-                idx = Some((0_usize, 0_usize));
+                let (mut matrix, mut backpointers) = match hyper_parameters.restriction {
+                    Restriction::Band(_)
+                    | Restriction::FittedBand(_)
+                    | Restriction::SlantedBand(_) => {
+                        let restriction = &hyper_parameters.restriction;
+                        let layout = Layout::new(shape, |i| restriction.row_range(shape, i));
+                        (
+                            Accumulated::banded(layout.clone()),
+                            Backpointers::with_layout(layout),
+                        )
+                    }
+                    _ => (Accumulated::new(shape), Backpointers::new(shape)),
+                };
+                optimize_matrix(
+                    &mut matrix,
+                    &mut backpointers,
+                    &hyper_parameters.restriction,
+                    hyper_parameters.step_pattern,
+                    hyper_parameters.tie_break,
+                    distance,
+                    upper_bound,
+                );
+                (matrix, Trace::Backpointers(backpointers))
+            }
+            None => {
+                let mut mat = Matrix::fill(Element::Inf, shape.0, shape.1);
+                let mut moves = Matrix::fill(None, shape.0, shape.1);
+                optimize_moves(
+                    &mut mat,
+                    &mut moves,
+                    &hyper_parameters.restriction,
+                    hyper_parameters.step_pattern,
+                    distance,
+                );
+                (Accumulated::Wrapped(mat), Trace::Moves(moves))
+            }
+        };
+        Self {
+            matrix,
+            restriction: hyper_parameters.restriction,
+            step_pattern: hyper_parameters.step_pattern,
+            normalization: hyper_parameters.normalization,
+            square_root: hyper_parameters.square_root,
+            trace,
+            local_costs: local_costs.map(RefCell::into_inner),
+        }
+    }
+
+    /// Dynamic time warping between the sequences of symbols `a` and `b` using the
+    /// substitution, insertion and deletion costs of `table`.
+    pub fn with_cost_table(a: &[usize], b: &[usize], table: &CostTable<D>) -> Self {
+        assert!(
+            a.iter().chain(b).all(|s| *s < table.symbols()),
+            "Symbols should be less than the number of symbols = {}",
+            table.symbols()
+        );
+        let mut matrix = Matrix::fill(Element::Inf, a.len(), b.len());
+        let mut moves = Matrix::fill(None, a.len(), b.len());
+        optimize_table(&mut matrix, &mut moves, &Restriction::None, a, b, table);
+        Self::with_moves(matrix, moves)
+    }
+
+    /// Alignment of the accumulated costs `matrix`, with the step chosen at each cell recorded
+    /// in `moves` in the order of the symmetric step pattern.
+    pub(super) fn with_moves(matrix: Matrix<Element<D>>, moves: Matrix<Option<u8>>) -> Self {
+        Self {
+            matrix: Accumulated::Wrapped(matrix),
+            restriction: Restriction::None,
+            step_pattern: StepPattern::Symmetric,
+            normalization: Normalization::None,
+            square_root: false,
+            trace: Trace::Moves(moves),
+            local_costs: None,
+        }
+    }
+}
+
+impl<D> DynamicTimeWarping<D>
+where
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost + Into<f64>,
+{
+    /// Similarity score in `[0, 1]` of the distance, normalized and square-rooted as set by the
+    /// parameters, e.g. [`Normalization::PathLength`] for a score comparable across lengths.
+    pub fn similarity(&self, kernel: Similarity) -> f64 {
+        kernel.score(self.distance().into())
+    }
+}
+
+impl<D> DynamicTimeWarping<D>
+where
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost + Sub<D, Output = D>,
+{
+    /// Runs of matched, vertical and horizontal steps along the path, with the index ranges
+    /// they cover and the cost they add.
+    pub fn diff(&self) -> Vec<DiffRun<D>> {
+        diff(&self.path(), |cell| match self.matrix.cost(cell) {
+            Element::Inf => panic!("Infinit distance"),
+            Element::Value(v) => v,
+        })
+    }
+
+    /// Steps of the warping path with the elements of `a` and `b` they match and the costs they
+    /// contribute. The local costs are those retained by [`Parameters::retain_local_costs`] if
+    /// any, and otherwise the increases of the accumulated cost along the path, which differ
+    /// from the local costs for the weighted step patterns.
+    pub fn steps<'a, T>(
+        &'a self,
+        a: &'a [T],
+        b: &'a [T],
+    ) -> impl Iterator<Item = Step<'a, T, D>> + 'a {
+        assert_eq!(
+            (a.len(), b.len()),
+            self.matrix.shape(),
+            "Sequences should match the shape of the matrix"
+        );
+        let accumulated = |cell| match self.matrix.cost(cell) {
+            Element::Inf => panic!("Infinit distance"),
+            Element::Value(v) => v,
+        };
+        let mut previous: Option<((usize, usize), D)> = None;
+        self.path().into_iter().map(move |(i, j)| {
+            let retained = self
+                .local_costs
+                .as_ref()
+                .and_then(|local_costs| local_costs[(i, j)].clone());
+            let local_cost = match (retained, &previous) {
+                (Some(local_cost), _) => local_cost,
+                (None, None) => accumulated((i, j)),
+                (None, Some((cell, _))) => accumulated((i, j)) - accumulated(*cell),
+            };
+            let cumulative_cost = match &previous {
+                Some((_, cumulative)) => cumulative.clone() + local_cost.clone(),
+                None => local_cost.clone(),
+            };
+            previous = Some(((i, j), cumulative_cost.clone()));
+            Step {
+                i,
+                j,
+                a: &a[i],
+                b: &b[j],
+                local_cost,
+                cumulative_cost,
             }
-            idx
         })
-        .fuse()
     }
+}
 
-    fn range(self, shape: (usize, usize), y: usize) -> (usize, usize) {
-        match self {
-            Restriction::None => (0, shape.1),
-            Restriction::Band(size) => {
-                let min = (y as f32 - size as f32).max(0.0) as usize;
-                let max = (y as f32 + size as f32 + 1.0).min(shape.1 as f32) as usize;
-                (min, max)
+impl Parameters {
+    /// Hyper-parameters with no restriction and the symmetric step pattern.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the global restriction of the warping path.
+    pub fn restriction(mut self, restriction: Restriction) -> Self {
+        self.restriction = restriction;
+        self
+    }
+
+    /// Set the local step pattern of the warping path.
+    pub fn step_pattern(mut self, step_pattern: StepPattern) -> Self {
+        self.step_pattern = step_pattern;
+        self
+    }
+
+    /// Limit the warping path to at most `max_run` consecutive horizontal steps and at most
+    /// `max_run` consecutive vertical steps.
+    pub fn max_run(mut self, max_run: usize) -> Self {
+        self.max_run = Some(max_run);
+        self
+    }
+
+    /// Add the ordering constraint `landmark`, enforced by pruning the cells violating it.
+    pub fn landmark(mut self, landmark: Landmark) -> Self {
+        self.landmarks.push(landmark);
+        self
+    }
+
+    /// Set the transformation applied to every local cost before it is accumulated.
+    pub fn transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Clamp every local cost, after its transformation, at `cap` such that a single corrupted
+    /// element cannot dominate the distance or bend the path.
+    pub fn cap(mut self, cap: f64) -> Self {
+        self.cap = Some(cap);
+        self
+    }
+
+    /// Weigh every local cost, after its transformation and its cap, by the logistic function
+    /// `1 / (1 + exp(-g * (|i - j| - max(n, m) / 2)))` of the phase difference of its cell, as
+    /// in weighted dynamic time warping (Jeong et al., "Weighted dynamic time warping for time
+    /// series classification"). The penalty `g` softly discourages large warpings instead of
+    /// the hard cutoff of a band, from a constant weight with a penalty of zero to a step
+    /// function with a large penalty.
+    pub fn phase_weight(mut self, g: f64) -> Self {
+        self.phase_weight = Some(g);
+        self
+    }
+
+    /// Retain the local costs computed along the way, returned by
+    /// [`DynamicTimeWarping::local_cost_matrix`], e.g. to plot them or to study the quality of
+    /// the alignment.
+    pub fn retain_local_costs(mut self, retain: bool) -> Self {
+        self.retain_local_costs = retain;
+        self
+    }
+
+    /// Prune the cells whose accumulated cost exceeds the cost of the path along the diagonal,
+    /// which cannot lie on the optimal path, skipping most of the matrix for similar sequences.
+    /// The local costs should not be negative, and only the symmetric step pattern without
+    /// max-run constraint is pruned.
+    pub fn prune(mut self, prune: bool) -> Self {
+        self.prune = prune;
+        self
+    }
+
+    /// Predecessor preferred among the ones of equal accumulated cost when recovering the path
+    /// of a single-step pattern, the diagonal one by default.
+    pub fn tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    /// Set the normalization of the warped distance.
+    pub fn normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Take the square root of the warped distance after its normalization, e.g. of the
+    /// accumulated squared differences as in [`Parameters::euclidean`].
+    pub fn square_root(mut self, square_root: bool) -> Self {
+        self.square_root = square_root;
+        self
+    }
+
+    /// Hyper-parameters of the Euclidean flavor of dynamic time warping used by tslearn and
+    /// dtaidistance: the local costs are squared and the warped distance is the square root of
+    /// their accumulation.
+    pub fn euclidean() -> Self {
+        Self::new().transform(Transform::Square).square_root(true)
+    }
+}
+
+impl Parameters {
+    /// Global restriction combined with the landmarks, for a matrix of shape `shape`.
+    pub(crate) fn global_restriction(&self, shape: (usize, usize)) -> Restriction {
+        match self.landmarks.is_empty() {
+            true => self.restriction.clone(),
+            false => self.restriction.with_landmarks(&self.landmarks, shape),
+        }
+    }
+
+    /// Single-step pattern of the computation, asserting that no constraint requires more than
+    /// a predecessor per cell to recover the path.
+    pub(crate) fn single_step_pattern(&self) -> StepPattern {
+        assert!(
+            self.max_run.is_none() && self.step_pattern.is_single_step(),
+            "Backpointers require a single-step pattern without max-run constraint"
+        );
+        self.step_pattern
+    }
+
+    /// Distance `distance` accumulated along a path of `path_length` cells of a matrix of shape
+    /// `shape`, normalized.
+    pub(crate) fn normalize<D: Cost + Clone>(
+        &self,
+        distance: &D,
+        shape: (usize, usize),
+        path_length: usize,
+    ) -> D {
+        let distance = self.normalization.apply(distance, shape, path_length);
+        match self.square_root {
+            true => distance.mapped(f64::sqrt),
+            false => distance,
+        }
+    }
+
+    /// Bound on the accumulated cost of the distances not exceeding `threshold` once normalized
+    /// for a matrix of shape `shape`.
+    pub(crate) fn accumulated_bound<D: Cost>(&self, threshold: &D, shape: (usize, usize)) -> D {
+        let divisor = self.normalization.max_divisor(shape);
+        match self.square_root {
+            true => threshold.mapped(|t| t * t * divisor),
+            false => threshold.weighted(divisor),
+        }
+    }
+
+    /// Local cost `distance` of a matrix of shape `shape` transformed, capped and weighted by
+    /// the phase difference.
+    pub(crate) fn local_cost<D: Cost>(
+        &self,
+        shape: (usize, usize),
+        distance: impl Fn(usize, usize) -> D,
+    ) -> impl Fn(usize, usize) -> D {
+        let (transform, cap, phase_weight) = (self.transform, self.cap, self.phase_weight);
+        let center = shape.0.max(shape.1) as f64 / 2.0;
+        let transformed = move |i, j| match (transform, cap) {
+            (Transform::Identity, None) => distance(i, j),
+            (_, None) => distance(i, j).mapped(|c| transform.apply(c)),
+            (_, Some(cap)) => distance(i, j).mapped(|c| transform.apply(c).min(cap)),
+        };
+        move |i, j| match phase_weight {
+            None => transformed(i, j),
+            Some(g) => {
+                let exponent = -g * (i.abs_diff(j) as f64 - center);
+                #[cfg(not(feature = "deterministic"))]
+                let decay = exponent.exp();
+                #[cfg(feature = "deterministic")]
+                let decay = super::similarity::portable_exp(exponent);
+                transformed(i, j).weighted(1.0 / (1.0 + decay))
             }
         }
     }
 }
 
+impl From<Restriction> for Parameters {
+    fn from(restriction: Restriction) -> Self {
+        Parameters::new().restriction(restriction)
+    }
+}
+
+impl From<StepPattern> for Parameters {
+    fn from(step_pattern: StepPattern) -> Self {
+        Parameters::new().step_pattern(step_pattern)
+    }
+}
+
+impl From<Transform> for Parameters {
+    fn from(transform: Transform) -> Self {
+        Parameters::new().transform(transform)
+    }
+}
+
 impl<T> PartialOrd for Element<T>
 where
     T: PartialOrd,
@@ -197,35 +1083,134 @@ where
     }
 }
 
+/// Cell at which the path of a matrix of shape `shape` under `restriction` stops, i.e. the
+/// last cell of the band if the bottom-right corner lies outside of it.
+pub(crate) fn path_stop(restriction: &Restriction, shape: (usize, usize)) -> (usize, usize) {
+    match restriction {
+        Restriction::Band(band) => {
+            if shape.0 < shape.1 {
+                (shape.0 - 1, (shape.1 - 1).min(shape.0 - 1 + band))
+            } else {
+                ((shape.0 - 1).min(shape.1 - 1 + band), shape.1 - 1)
+            }
+        }
+        _ => (shape.0 - 1, shape.1 - 1),
+    }
+}
+
+/// Accumulate the costs row by row, each row being computed from the slices of the previous and
+/// the current rows.
+///
+/// Given an `upper_bound` on the distance, the cells whose accumulated cost exceeds it are
+/// pruned as in PrunedDTW (Silva and Batista, "Speeding Up All-Pairwise Dynamic Time Warping
+/// Matrix Calculation"): each row starts at the first column reached in the previous row and
+/// stops at the first pruned cell past the last column reached in the previous row.
 fn optimize_matrix<D: Clone + PartialOrd + Add<D, Output = D>>(
-    matrix: &mut Matrix<Element<D>>,
-    restriction: Restriction,
+    matrix: &mut impl CostMatrix<D>,
+    backpointers: &mut Backpointers,
+    restriction: &Restriction,
+    step_pattern: StepPattern,
+    tie_break: TieBreak,
     distance: impl Fn(usize, usize) -> D,
+    upper_bound: Option<D>,
 ) {
-    restriction.iter(matrix.shape()).for_each(|(i, j)| {
-        matrix[(i, j)] = preceeding_cost(matrix, (i, j), restriction)
-            .map(|idx| matrix[idx].clone() + Element::Value(distance(i, j)))
-            .unwrap_or_else(|| Element::Value(distance(i, j)));
-    });
+    let shape = matrix.shape();
+    let predecessors = step_pattern.predecessors();
+    let order = tie_break.order(predecessors);
+    let upper_bound = upper_bound.map(Element::Value);
+    let mut reached = 0..shape.1;
+    let mut previous = vec![Element::Inf; shape.1];
+    let mut current = vec![Element::Inf; shape.1];
+    for i in 0..shape.0 {
+        current.fill(Element::Inf);
+        let range = restriction.row_range(shape, i);
+        let mut next = range.end..range.start;
+        for j in range.start.max(reached.start)..range.end {
+            if upper_bound.is_some() && j > reached.end + 1 && current[j - 1] == Element::Inf {
+                break;
+            }
+            let cell = |p: usize| {
+                let (di, dj) = predecessors[p];
+                match di {
+                    0 => &current[j - dj],
+                    _ => &previous[j - dj],
+                }
+            };
+            let predecessor = order
+                .iter()
+                .copied()
+                .filter(|p| predecessors[*p].0 <= i && predecessors[*p].1 <= j)
+                .reduce(|best, p| if cell(p) < cell(best) { p } else { best });
+            backpointers.set((i, j), predecessor);
+            current[j] = match predecessor {
+                Some(p) => cell(p).clone() + Element::Value(distance(i, j)),
+                None if (i, j) == (0, 0) => Element::Value(distance(i, j)),
+                None => Element::Inf,
+            };
+            if upper_bound
+                .as_ref()
+                .is_some_and(|bound| current[j] > *bound)
+            {
+                current[j] = Element::Inf;
+                backpointers.set((i, j), None);
+            }
+            if current[j] != Element::Inf {
+                next = next.start.min(j)..j;
+            }
+        }
+        if upper_bound.is_some() {
+            reached = next;
+        }
+        matrix.set_row(i, &current);
+        std::mem::swap(&mut previous, &mut current);
+    }
+}
+
+/// Cost of the path of a matrix of shape `shape` running along the diagonal and then along the
+/// last row or column, or `None` if the restriction does not admit it.
+fn staircase_cost<D: Add<D, Output = D>>(
+    restriction: &Restriction,
+    shape: (usize, usize),
+    distance: impl Fn(usize, usize) -> D,
+) -> Option<D> {
+    let diagonal = shape.0.min(shape.1);
+    (0..diagonal)
+        .map(|k| (k, k))
+        .chain((diagonal..shape.0).map(|i| (i, shape.1 - 1)))
+        .chain((diagonal..shape.1).map(|j| (shape.0 - 1, j)))
+        .map(|cell| {
+            restriction
+                .contains(cell, shape)
+                .then(|| distance(cell.0, cell.1))
+        })
+        .reduce(|sum, cost| Some(sum? + cost?))?
 }
 
+/// Path ending at `(i, j)` following the predecessors recorded in `backpointers`, or the
+/// cheapest predecessors for the cells without any, e.g. outside of the restriction.
 fn compute_path<D>(
-    matrix: &Matrix<Element<D>>,
+    matrix: &impl CostMatrix<D>,
+    backpointers: &Backpointers,
     i: usize,
     j: usize,
-    restriction: Restriction,
+    step_pattern: StepPattern,
 ) -> Vec<(usize, usize)>
 where
     D: PartialOrd,
 {
+    let predecessors = step_pattern.predecessors();
     let mut i = i;
     let mut j = j;
     let mut v = vec![(i, j)];
     while i != 0 || j != 0 {
-        if let Some((i_, j_)) = preceeding_cost(matrix, (i, j), restriction) {
-            v.push((i_, j_));
-            i = i_;
-            j = j_;
+        let predecessor = backpointers
+            .get((i, j))
+            .or_else(|| preceeding_cost(matrix, (i, j), &Restriction::None, step_pattern));
+        if let Some(p) = predecessor {
+            let (di, dj) = predecessors[p];
+            i -= di;
+            j -= dj;
+            v.push((i, j));
         } else {
             break;
         };
@@ -234,62 +1219,47 @@ where
     v
 }
 
+/// Index of the cheapest predecessor of the cell `index` in the predecessors of `step_pattern`.
 fn preceeding_cost<D: PartialOrd>(
-    matrix: &Matrix<D>,
+    matrix: &impl CostMatrix<D>,
     index: (usize, usize),
-    restriction: Restriction,
-) -> Option<(usize, usize)> {
+    restriction: &Restriction,
+    step_pattern: StepPattern,
+) -> Option<usize> {
     if restriction.contains(index, matrix.shape()) {
         let (i, j) = index;
-        if i != 0 && j != 0 {
-            match arg_min(
-                &matrix[(i - 1, j - 1)],
-                &matrix[(i - 1, j)],
-                &matrix[(i, j - 1)],
-            ) {
-                0 => Some((i - 1, j - 1)),
-                1 => Some((i - 1, j)),
-                2 => Some((i, j - 1)),
-                _ => panic!("I dont know what to say"),
-            }
-        } else if i != 0 {
-            Some((i - 1, j))
-        } else if j != 0 {
-            Some((i, j - 1))
-        } else {
-            None
-        }
+        step_pattern
+            .predecessors()
+            .iter()
+            .enumerate()
+            .filter(|(_, (di, dj))| *di <= i && *dj <= j)
+            .map(|(p, (di, dj))| (p, (i - di, j - dj)))
+            .reduce(|best, candidate| {
+                if matrix.cost(candidate.1) < matrix.cost(best.1) {
+                    candidate
+                } else {
+                    best
+                }
+            })
+            .map(|(p, _)| p)
     } else {
         None
     }
 }
 
-#[inline]
-fn arg_min<D: PartialOrd>(a: &D, b: &D, c: &D) -> usize {
-    if a > b {
-        if b > c {
-            2
-        } else {
-            1
-        }
-    } else if a > c {
-        2
-    } else {
-        0
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use crate::{
         algorithms::{
             dynamic_programming::{optimize_matrix, Element},
-            utils::Matrix,
+            utils::{Backpointers, Matrix},
         },
-        Restriction,
+        CostTable, DiffKind, Error, Landmark, Normalization, Parameters, Restriction, Similarity,
+        StepPattern, TieBreak, Window,
     };
 
-    use super::{compute_path, DynamicTimeWarping};
+    use super::{compute_path, Accumulated, DynamicTimeWarping};
+    use crate::Algorithm;
 
     #[test]
     fn compute_matrix_with_example() {
@@ -308,9 +1278,15 @@ mod tests {
         );
 
         let mut matrix = Matrix::fill(Element::Inf, a.len(), b.len());
-        optimize_matrix(&mut matrix, crate::Restriction::None, |i, j| {
-            f64::abs(a[i] - b[j])
-        });
+        optimize_matrix(
+            &mut matrix,
+            &mut Backpointers::new((a.len(), b.len())),
+            &crate::Restriction::None,
+            StepPattern::Symmetric,
+            TieBreak::Diagonal,
+            |i, j| f64::abs(a[i] - b[j]),
+            None,
+        );
         println!("Matrix:");
         println!("{}", matrix);
         assert!(matrix == expected_matrix);
@@ -358,9 +1334,15 @@ mod tests {
         );
 
         let mut mat = Matrix::fill(Element::Inf, a.len(), b.len());
-        optimize_matrix(&mut mat, crate::Restriction::Band(1), |i, j| {
-            f64::abs(a[i] - b[j])
-        });
+        optimize_matrix(
+            &mut mat,
+            &mut Backpointers::new((a.len(), b.len())),
+            &crate::Restriction::Band(1),
+            StepPattern::Symmetric,
+            TieBreak::Diagonal,
+            |i, j| f64::abs(a[i] - b[j]),
+            None,
+        );
         // println!("{}", dtw.matrix);
         // println!("{:?}", dtw.matrix.data().iter().zip(expected_matrix.data().iter()).map(|(e1, e2)| e1 == e2).collect::<Vec<bool>>());
         println!("Matrix:");
@@ -396,9 +1378,15 @@ mod tests {
         );
 
         let mut mat = Matrix::fill(Element::Inf, a.len(), b.len());
-        optimize_matrix(&mut mat, crate::Restriction::Band(1), |i, j| {
-            f64::abs(a[i] - b[j])
-        });
+        optimize_matrix(
+            &mut mat,
+            &mut Backpointers::new((a.len(), b.len())),
+            &crate::Restriction::Band(1),
+            StepPattern::Symmetric,
+            TieBreak::Diagonal,
+            |i, j| f64::abs(a[i] - b[j]),
+            None,
+        );
         // println!("{}", dtw.matrix);
         // println!("{:?}", dtw.matrix.data().iter().zip(expected_matrix.data().iter()).map(|(e1, e2)| e1 == e2).collect::<Vec<bool>>());
         println!("Matrix:");
@@ -432,9 +1420,15 @@ mod tests {
         );
 
         let mut mat = Matrix::fill(Element::Inf, a.len(), b.len());
-        optimize_matrix(&mut mat, crate::Restriction::Band(1), |i, j| {
-            f64::abs(a[i] - b[j])
-        });
+        optimize_matrix(
+            &mut mat,
+            &mut Backpointers::new((a.len(), b.len())),
+            &crate::Restriction::Band(1),
+            StepPattern::Symmetric,
+            TieBreak::Diagonal,
+            |i, j| f64::abs(a[i] - b[j]),
+            None,
+        );
         // println!("{}", dtw.matrix);
         // println!("{:?}", dtw.matrix.data().iter().zip(expected_matrix.data().iter()).map(|(e1, e2)| e1 == e2).collect::<Vec<bool>>());
         println!("Matrix:");
@@ -461,10 +1455,343 @@ mod tests {
             6,
         );
         let expected_path = [(0, 0), (0, 1), (1, 2), (2, 3), (2, 4), (3, 5), (4, 5)];
-        let founded_path = compute_path(&matrix, 4, 5, crate::Restriction::None);
+        let backpointers = Backpointers::new(matrix.shape());
+        let founded_path = compute_path(&matrix, &backpointers, 4, 5, StepPattern::Symmetric);
         assert!(expected_path == *founded_path);
     }
 
+    #[test]
+    fn recorded_backpointers_path() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let mut matrix = Matrix::fill(Element::Inf, a.len(), b.len());
+        let mut backpointers = Backpointers::new((a.len(), b.len()));
+        optimize_matrix(
+            &mut matrix,
+            &mut backpointers,
+            &Restriction::None,
+            StepPattern::Symmetric,
+            TieBreak::Diagonal,
+            |i, j| f64::abs(a[i] - b[j]),
+            None,
+        );
+        assert_eq!(backpointers.get((0, 0)), None);
+        assert_eq!(backpointers.get((0, 3)), Some(2));
+        assert_eq!(
+            compute_path(&matrix, &backpointers, 4, 5, StepPattern::Symmetric),
+            [(0, 0), (0, 1), (1, 2), (2, 3), (2, 4), (3, 5), (4, 5)]
+        );
+    }
+
+    #[test]
+    fn distance_only_matches_full_matrix() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0, 4.0, 4.0, 0.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0, 5.0, 1.0, 1.0, 3.0];
+        let cost = |x: &f64, y: &f64| f64::abs(x - y);
+        for param in [
+            Parameters::new(),
+            Parameters::new().restriction(Restriction::Band(3)),
+            Parameters::new().step_pattern(StepPattern::Asymmetric),
+            Parameters::new().landmark(Landmark::NoEarlierThan(2, 4)),
+        ] {
+            let full = DynamicTimeWarping::with_closure_and_param(&a, &b, cost, param.clone());
+            assert_eq!(
+                DynamicTimeWarping::distance_only(&a, &b, cost, param),
+                full.distance()
+            );
+        }
+    }
+
+    #[test]
+    fn normalized_distances() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let raw = DynamicTimeWarping::between(&a, &b);
+        for (normalization, divisor) in [
+            (Normalization::PathLength, raw.path().len() as f64),
+            (Normalization::Sum, 11.0),
+            (Normalization::Max, 6.0),
+        ] {
+            let param = Parameters::new().normalization(normalization);
+            let dtw = DynamicTimeWarping::with_param(&a, &b, param.clone());
+            assert_eq!(dtw.distance(), raw.distance() * (1.0 / divisor));
+            assert_eq!(
+                dtw.similarity(Similarity::Reciprocal),
+                1.0 / (1.0 + dtw.distance())
+            );
+            let cost = |x: &f64, y: &f64| f64::abs(x - y);
+            assert_eq!(
+                DynamicTimeWarping::distance_only(&a, &b, cost, param),
+                dtw.distance()
+            );
+        }
+    }
+
+    #[test]
+    fn fallible_constructors() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let dtw = DynamicTimeWarping::try_between(&a, &b).unwrap();
+        assert_eq!(dtw, DynamicTimeWarping::between(&a, &b));
+        assert_eq!(
+            DynamicTimeWarping::<f64>::try_between(&[], &b),
+            Err(Error::EmptyInput)
+        );
+        assert_eq!(
+            DynamicTimeWarping::try_between(&a, &[2.0, f64::NAN]),
+            Err(Error::NonFiniteCost)
+        );
+        assert_eq!(
+            DynamicTimeWarping::try_with_param(&a, &b[..2], Restriction::Band(2)),
+            Err(Error::BandTooNarrow(3))
+        );
+        assert_eq!(
+            DynamicTimeWarping::try_with_param(&a, &b, Restriction::Itakura(1.0)),
+            Err(Error::InvalidSlope)
+        );
+        let fitted = DynamicTimeWarping::with_param(&a, &b[..2], Restriction::FittedBand(2));
+        assert_eq!(fitted.path().last(), Some(&(4, 1)));
+        let narrow = Parameters::new().step_pattern(StepPattern::Asymmetric);
+        assert_eq!(
+            DynamicTimeWarping::try_with_param(&a, &[0.0; 12], narrow),
+            Err(Error::Unreachable)
+        );
+    }
+
+    #[test]
+    fn abandoned_beyond_threshold() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        assert_eq!(DynamicTimeWarping::with_threshold(&a, &b, 9.0), Some(9.0));
+        assert_eq!(DynamicTimeWarping::with_threshold(&a, &b, 8.5), None);
+        let cost = |x: &f64, y: &f64| f64::abs(x - y);
+        let param = Parameters::new().normalization(Normalization::PathLength);
+        let normalized = DynamicTimeWarping::distance_only(&a, &b, cost, param.clone());
+        let within =
+            DynamicTimeWarping::with_closure_and_threshold(&a, &b, cost, param, normalized);
+        assert_eq!(within, Some(normalized));
+    }
+
+    #[test]
+    fn phase_weighted_costs() {
+        let a = [0.0, 0.0, 1.0, 2.0, 1.0, 0.0, 0.0, 0.0];
+        let b = [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 2.0, 1.0];
+        let weighted =
+            |g: f64| DynamicTimeWarping::with_param(&a, &b, Parameters::new().phase_weight(g));
+        let flat = weighted(0.0);
+        let raw = DynamicTimeWarping::between(&a, &b);
+        assert_eq!(flat.distance(), raw.distance() * 0.5);
+        let steep = weighted(10.0);
+        let dominant = |dtw: &DynamicTimeWarping<f64>| {
+            dtw.path()
+                .iter()
+                .map(|(i, j)| i.abs_diff(*j))
+                .max()
+                .unwrap()
+        };
+        assert_eq!(dominant(&raw), 3);
+        assert!(dominant(&steep) < dominant(&raw));
+    }
+
+    #[cfg(feature = "deterministic")]
+    #[test]
+    fn portable_phase_weight() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let dtw = DynamicTimeWarping::with_param(&a, &b, Parameters::new().phase_weight(0.5));
+        assert_eq!(dtw.distance(), 2.551155389622618);
+    }
+
+    #[test]
+    fn plain_costs_with_infinity() {
+        let a = [1, 3, 9, 2, 1];
+        let b = [2, 0, 0, 8, 7, 2];
+        let float = |s: &[i64]| s.iter().map(|x| *x as f64).collect::<Vec<f64>>();
+        let integers = DynamicTimeWarping::<u64>::with_closure(&a, &b, |x: &i64, y| x.abs_diff(*y));
+        let floats = DynamicTimeWarping::<f64>::between(&float(&a), &float(&b));
+        assert!(matches!(integers.matrix, Accumulated::Wrapped(_)));
+        assert!(matches!(floats.matrix, Accumulated::Plain { .. }));
+        assert_eq!(floats.distance(), integers.distance() as f64);
+        assert_eq!(floats.path(), integers.path());
+        let banded =
+            DynamicTimeWarping::<f64>::with_param(&float(&a), &float(&b), Restriction::Band(0));
+        assert_eq!(banded.cost_matrix()[(0, 3)], None);
+    }
+
+    #[test]
+    fn ties_between_optimal_paths() {
+        let zeros = [0.0; 3];
+        let dtw = DynamicTimeWarping::between(&zeros, &zeros);
+        assert_eq!(dtw.path(), vec![(0, 0), (1, 1), (2, 2)]);
+        assert_eq!(dtw.optimal_path_count(), 13);
+        let paths = dtw.optimal_paths().collect::<Vec<Vec<(usize, usize)>>>();
+        assert_eq!(paths[0], dtw.path());
+        let mut distinct = paths.clone();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(distinct.len(), 13);
+        let expansion = Parameters::new().tie_break(TieBreak::Expansion);
+        let expanded = DynamicTimeWarping::with_param(&zeros, &zeros, expansion);
+        assert_eq!(
+            expanded.path(),
+            vec![(0, 0), (0, 1), (0, 2), (1, 2), (2, 2)]
+        );
+        assert!(paths.contains(&expanded.path()));
+        let (a, b) = ([1.0, 2.0, 3.0], [1.0, 2.0, 2.0, 3.0]);
+        let dtw = DynamicTimeWarping::between(&a, &b);
+        assert_eq!(dtw.optimal_path_count(), 1);
+        assert_eq!(dtw.optimal_paths().collect::<Vec<_>>(), vec![dtw.path()]);
+    }
+
+    #[test]
+    fn banded_storage() {
+        let a = (0..200).map(|k| (k % 17) as f64).collect::<Vec<f64>>();
+        let b = (0..180).map(|k| (k % 13) as f64).collect::<Vec<f64>>();
+        let shape = (a.len(), b.len());
+        for restriction in [Restriction::Band(25), Restriction::FittedBand(3)] {
+            let banded = DynamicTimeWarping::with_param(&a, &b, restriction.clone());
+            let window = Restriction::Window(Window::new(
+                (0..shape.0)
+                    .map(|i| restriction.row_range(shape, i))
+                    .map(|range| (range.start, range.end))
+                    .collect(),
+            ));
+            let dense = DynamicTimeWarping::with_param(&a, &b, window);
+            let Accumulated::Banded { costs, .. } = &banded.matrix else {
+                panic!("Band should be stored banded");
+            };
+            assert!(costs.len() < shape.0 * shape.1 / 3);
+            assert!(matches!(dense.matrix, Accumulated::Plain { .. }));
+            assert_eq!(banded.distance(), dense.distance());
+            assert_eq!(banded.path(), dense.path());
+            assert_eq!(banded.cost_matrix(), dense.cost_matrix());
+        }
+    }
+
+    #[test]
+    fn euclidean_flavor() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let squared = DynamicTimeWarping::with_closure(&a, &b, |x: &f64, y| (x - y) * (x - y));
+        let euclidean = DynamicTimeWarping::with_param(&a, &b, Parameters::euclidean());
+        assert_eq!(euclidean.distance(), squared.distance().sqrt());
+        assert_eq!(euclidean.path(), squared.path());
+        let param = Parameters::euclidean();
+        let only = DynamicTimeWarping::distance_only(&a, &b, |x, y| f64::abs(x - y), param);
+        assert_eq!(only, euclidean.distance());
+        let threshold = |t: f64| {
+            DynamicTimeWarping::with_closure_and_threshold(
+                &a,
+                &b,
+                |x, y| f64::abs(x - y),
+                Parameters::euclidean(),
+                t,
+            )
+        };
+        assert_eq!(threshold(euclidean.distance()), Some(euclidean.distance()));
+        assert_eq!(threshold(euclidean.distance() - 0.1), None);
+    }
+
+    #[test]
+    fn cost_matrices() {
+        let a = [1.0, 3.0, 9.0, 2.0];
+        let b = [2.0, 0.0, 8.0];
+        let dtw =
+            DynamicTimeWarping::with_param(&a, &b, Parameters::new().retain_local_costs(true));
+        let costs = dtw.cost_matrix();
+        assert_eq!(costs.shape(), (4, 3));
+        assert_eq!(costs[(3, 2)], Some(dtw.distance()));
+        let local = dtw.local_cost_matrix().unwrap();
+        assert_eq!(local.row(0), &[Some(1.0), Some(1.0), Some(7.0)]);
+        assert_eq!(costs[(0, 1)], Some(2.0));
+        assert!(DynamicTimeWarping::between(&a, &b)
+            .local_cost_matrix()
+            .is_none());
+        let banded = DynamicTimeWarping::with_param(
+            &a,
+            &b,
+            Parameters::new()
+                .restriction(Restriction::Band(0))
+                .retain_local_costs(true),
+        );
+        assert_eq!(banded.cost_matrix()[(0, 2)], None);
+        assert_eq!(banded.local_cost_matrix().unwrap()[(0, 2)], None);
+    }
+
+    #[test]
+    fn path_steps() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let dtw = DynamicTimeWarping::between(&a, &b);
+        let steps = dtw.steps(&a, &b).collect::<Vec<_>>();
+        assert_eq!(steps.len(), dtw.path().len());
+        for (step, (i, j)) in steps.iter().zip(dtw.path()) {
+            assert_eq!((step.i, step.j, *step.a, *step.b), (i, j, a[i], b[j]));
+            assert_eq!(step.local_cost, f64::abs(a[i] - b[j]));
+        }
+        assert_eq!(steps[steps.len() - 1].cumulative_cost, dtw.distance());
+        let weighted = DynamicTimeWarping::with_param(
+            &a,
+            &b,
+            Parameters::new()
+                .step_pattern(StepPattern::Symmetric2)
+                .retain_local_costs(true),
+        );
+        assert!(weighted
+            .steps(&a, &b)
+            .all(|step| step.local_cost == f64::abs(step.a - step.b)));
+    }
+
+    #[test]
+    fn pruned_matches_full_matrix() {
+        let a = (0..40)
+            .map(|i| f64::sin(i as f64 / 5.0))
+            .collect::<Vec<f64>>();
+        let b = (0..35)
+            .map(|i| f64::sin(i as f64 / 4.0 + 0.3))
+            .collect::<Vec<f64>>();
+        for param in [
+            Parameters::new(),
+            Parameters::new().restriction(Restriction::Band(6)),
+            Parameters::new().restriction(Restriction::Itakura(2.0)),
+            Parameters::new().landmark(Landmark::NoEarlierThan(20, 15)),
+        ] {
+            let full = DynamicTimeWarping::with_param(&a, &b, param.clone());
+            let pruned = DynamicTimeWarping::with_param(&a, &b, param.prune(true));
+            assert_eq!(pruned.distance(), full.distance());
+            assert_eq!(pruned.path(), full.path());
+        }
+        let pruned = DynamicTimeWarping::with_param(&a, &a, Parameters::new().prune(true));
+        let reached = pruned
+            .cost_matrix()
+            .data()
+            .iter()
+            .filter(|e| e.is_some())
+            .count();
+        assert!(reached < a.len() * a.len() / 2);
+    }
+
+    #[test]
+    fn compute_matrix_asymmetric_first_row_unreachable() {
+        let a = [0.0; 3];
+        let b = [0.0; 4];
+        let mut mat = Matrix::fill(Element::Inf, a.len(), b.len());
+        optimize_matrix(
+            &mut mat,
+            &mut Backpointers::new((a.len(), b.len())),
+            &Restriction::None,
+            StepPattern::Asymmetric,
+            TieBreak::Diagonal,
+            |i, j| f64::abs(a[i] - b[j]),
+            None,
+        );
+        assert_eq!(mat[(0, 0)], Element::Value(0.0));
+        for j in 1..b.len() {
+            assert_eq!(mat[(0, j)], Element::Inf);
+        }
+        assert_eq!(mat[(2, 3)], Element::Value(0.0));
+    }
+
     #[test]
     fn partial_ord_element() {
         assert!(Element::Value(-1) < Element::Value(0));
@@ -473,18 +1800,87 @@ mod tests {
     }
 
     #[test]
-    fn iter_contain_restriction() {
-        let shape = (5_usize, 6_usize);
-        let no_rest = Restriction::None;
-        let restriction = Restriction::Band(1);
-        let all_indices = no_rest.iter(shape).collect::<Vec<(usize, usize)>>();
-        let band_indices = restriction.iter(shape).collect::<Vec<(usize, usize)>>();
-        for idx in all_indices.into_iter() {
-            assert_eq!(
-                band_indices.contains(&idx),
-                restriction.contains(idx, shape)
-            );
-        }
+    fn free_corner_with_truncated_sequence() {
+        let a = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+        let b = [1.0, 2.0, 3.0, 4.0];
+        let dtw = DynamicTimeWarping::between(&a, &b);
+        let corner = dtw.free_corner();
+        assert_eq!(corner.end, (3, 3));
+        assert_eq!(corner.distance, 0.0);
+        assert_eq!(corner.path, [(0, 0), (1, 1), (2, 2), (3, 3)]);
+        assert_eq!(corner.unmatched, (2, 0));
+    }
+
+    #[test]
+    fn similarity_of_normalized_distance() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let raw = DynamicTimeWarping::between(&a, &b);
+        assert_eq!(raw.similarity(Similarity::Reciprocal), 1.0 / (1.0 + 9.0));
+        let param = Parameters::new().normalization(Normalization::PathLength);
+        let dtw = DynamicTimeWarping::with_param(&a, &b, param);
+        assert_eq!(
+            dtw.similarity(Similarity::Reciprocal),
+            1.0 / (1.0 + 9.0 / 7.0)
+        );
+        let euclidean = DynamicTimeWarping::with_param(&a, &b, Parameters::euclidean());
+        assert_eq!(
+            euclidean.similarity(Similarity::Exponential(2.0)),
+            Similarity::Exponential(2.0).score(f64::sqrt(euclidean.accumulated_distance()))
+        );
+        assert_eq!(
+            DynamicTimeWarping::between(&a, &a).similarity(Similarity::Reciprocal),
+            1.0
+        );
+    }
+
+    #[test]
+    fn lookup_matches_closure() {
+        let table = CostTable::substitution(vec![vec![0, 2, 5], vec![2, 0, 1], vec![5, 1, 0]]);
+        let a = [0, 1, 2, 2, 0];
+        let b = [0, 2, 1, 0];
+        let closure =
+            DynamicTimeWarping::with_closure(&a, &b, |x, y| *table.substitution_cost(*x, *y));
+        let lookup = DynamicTimeWarping::with_lookup(&a, &b, &table, Parameters::default());
+        assert_eq!(lookup.distance(), closure.distance());
+        assert_eq!(lookup.path(), closure.path());
+        let band = DynamicTimeWarping::with_lookup(&a, &b, &table, Restriction::Band(1));
+        assert!(band.distance() >= closure.distance());
+    }
+
+    #[test]
+    fn diff_report() {
+        let a = [1.0, 2.0, 2.0, 2.0, 5.0];
+        let b = [1.0, 2.0, 5.0];
+        let runs = DynamicTimeWarping::between(&a, &b).diff();
+        assert_eq!(runs.len(), 3);
+        assert_eq!(
+            (runs[1].kind, runs[1].a.clone()),
+            (DiffKind::StretchedA, 2..4)
+        );
+        assert_eq!(runs.iter().map(|r| r.cost).sum::<f64>(), 0.0);
+    }
+
+    #[test]
+    fn landmark_constrains_path() {
+        let a = [0.0, 1.0, 2.0, 3.0];
+        let b = [0.0, 0.0, 1.0, 2.0, 3.0];
+        let free = DynamicTimeWarping::between(&a, &b);
+        assert!(free.path().contains(&(0, 1)));
+        let param = Parameters::new().landmark(Landmark::NoLaterThan(0, 0));
+        let dtw = DynamicTimeWarping::with_param(&a, &b, param);
+        assert!(dtw.path().iter().all(|(i, j)| *i != 0 || *j == 0));
+        assert_eq!(dtw.path()[1], (1, 1));
+        assert!(dtw.distance() >= free.distance());
+    }
+
+    #[test]
+    fn capped_costs() {
+        let a = [0.0, 1.0, 2.0, 50.0, 3.0];
+        let b = [0.0, 1.0, 2.0, 3.0];
+        let dtw = DynamicTimeWarping::with_param(&a, &b, Parameters::new().cap(5.0));
+        assert_eq!(dtw.distance(), 5.0);
+        assert_eq!(dtw.path(), [(0, 0), (1, 1), (2, 2), (3, 2), (4, 3)]);
     }
 
     fn sized_send_sync_unpin_check<T: Sized + Send + Sync + Unpin>() {}
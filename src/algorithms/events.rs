@@ -0,0 +1,219 @@
+use std::ops::Add;
+
+use super::{alphabet::CostTable, dynamic_programming::DynamicTimeWarping};
+use crate::{Algorithm, Cost};
+
+/// Alignment of two sequences of categorical events, such as the activities of process traces.
+#[derive(Debug, PartialEq, Clone)]
+pub struct EventAlignment<'a, E, D> {
+    distance: D,
+    pairs: Vec<EventPair<'a, E>>,
+}
+
+/// Pair of events aligned together, with their indices `(i, j)` in `a` and `b`, classified by
+/// the step of the warping path reaching it.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum EventPair<'a, E> {
+    /// Equal events reached by a diagonal step, or the first pair.
+    Matched {
+        index: (usize, usize),
+        a: &'a E,
+        b: &'a E,
+    },
+    /// Different events reached by a diagonal step, or the first pair, one substituted by the
+    /// other.
+    Substituted {
+        index: (usize, usize),
+        a: &'a E,
+        b: &'a E,
+    },
+    /// Event `b` inserted by a horizontal step, aligned with the event `a` of the previous pair.
+    Inserted {
+        index: (usize, usize),
+        a: &'a E,
+        b: &'a E,
+    },
+    /// Event `a` deleted by a vertical step, aligned with the event `b` of the previous pair.
+    Deleted {
+        index: (usize, usize),
+        a: &'a E,
+        b: &'a E,
+    },
+}
+
+impl<'a, E, D> EventAlignment<'a, E, D>
+where
+    E: PartialEq,
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost,
+{
+    /// Alignment of the events `a` and `b` using the cost closure `cost`.
+    pub fn with_closure(a: &'a [E], b: &'a [E], cost: impl Fn(&E, &E) -> D) -> Self {
+        let dtw = DynamicTimeWarping::with_closure(a, b, cost);
+        Self::from_dtw(a, b, dtw, |i, j| a[i] == b[j])
+    }
+
+    /// Alignment of the events `a` and `b` using the costs of `table`, where `symbol` maps each
+    /// event to its symbol in the table. Events of the same symbol are matched.
+    pub fn with_table(
+        a: &'a [E],
+        b: &'a [E],
+        table: &CostTable<D>,
+        symbol: impl Fn(&E) -> usize,
+    ) -> Self {
+        let symbols = |events: &[E]| events.iter().map(&symbol).collect::<Vec<usize>>();
+        let (sa, sb) = (symbols(a), symbols(b));
+        let dtw = DynamicTimeWarping::with_cost_table(&sa, &sb, table);
+        Self::from_dtw(a, b, dtw, |i, j| sa[i] == sb[j])
+    }
+
+    /// Pairs of the path of `dtw`, where `same(i, j)` tells whether `a[i]` and `b[j]` match.
+    fn from_dtw(
+        a: &'a [E],
+        b: &'a [E],
+        dtw: DynamicTimeWarping<D>,
+        same: impl Fn(usize, usize) -> bool,
+    ) -> Self {
+        let mut previous: Option<(usize, usize)> = None;
+        let mut pairs = Vec::new();
+        for (i, j) in dtw.path() {
+            let (index, a, b) = ((i, j), &a[i], &b[j]);
+            pairs.push(match previous {
+                Some((pi, _)) if pi == i => EventPair::Inserted { index, a, b },
+                Some((_, pj)) if pj == j => EventPair::Deleted { index, a, b },
+                _ if same(i, j) => EventPair::Matched { index, a, b },
+                _ => EventPair::Substituted { index, a, b },
+            });
+            previous = Some(index);
+        }
+        Self {
+            distance: dtw.distance(),
+            pairs,
+        }
+    }
+}
+
+impl<'a, E, D: Clone> EventAlignment<'a, E, D> {
+    /// Cost of the alignment.
+    pub fn distance(&self) -> D {
+        self.distance.clone()
+    }
+
+    /// Aligned pairs of events, in the order of the warping path.
+    pub fn pairs(&self) -> &[EventPair<'a, E>] {
+        &self.pairs
+    }
+
+    /// Number of matched pairs of events.
+    pub fn matches(&self) -> usize {
+        self.pairs.iter().filter(|pair| pair.is_match()).count()
+    }
+
+    /// Number of substituted pairs of events.
+    pub fn substitutions(&self) -> usize {
+        self.count(|pair| matches!(pair, EventPair::Substituted { .. }))
+    }
+
+    /// Number of inserted events of `b`.
+    pub fn insertions(&self) -> usize {
+        self.count(|pair| matches!(pair, EventPair::Inserted { .. }))
+    }
+
+    /// Number of deleted events of `a`.
+    pub fn deletions(&self) -> usize {
+        self.count(|pair| matches!(pair, EventPair::Deleted { .. }))
+    }
+
+    fn count(&self, kind: impl Fn(&EventPair<'a, E>) -> bool) -> usize {
+        self.pairs.iter().filter(|pair| kind(pair)).count()
+    }
+}
+
+impl<E> EventPair<'_, E> {
+    /// Indices `(i, j)` of the events in `a` and `b`.
+    pub fn index(&self) -> (usize, usize) {
+        match self {
+            EventPair::Matched { index, .. }
+            | EventPair::Substituted { index, .. }
+            | EventPair::Inserted { index, .. }
+            | EventPair::Deleted { index, .. } => *index,
+        }
+    }
+
+    /// Whether the events are matched.
+    pub fn is_match(&self) -> bool {
+        matches!(self, EventPair::Matched { .. })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventAlignment, EventPair};
+    use crate::CostTable;
+
+    #[derive(Debug, PartialEq, Clone, Copy)]
+    enum Event {
+        Start,
+        Review,
+        Approve,
+        Reject,
+    }
+
+    #[test]
+    fn align_events() {
+        use Event::*;
+        let a = [Start, Review, Approve];
+        let b = [Start, Review, Review, Reject];
+        let alignment = EventAlignment::with_closure(&a, &b, |x, y| if x == y { 0 } else { 1 });
+        assert_eq!(alignment.distance(), 1);
+        assert_eq!(alignment.matches(), 2);
+        assert_eq!(alignment.substitutions(), 1);
+        assert_eq!(alignment.insertions(), 1);
+        assert_eq!(
+            alignment.pairs()[2],
+            EventPair::Inserted {
+                index: (1, 2),
+                a: &Review,
+                b: &Review
+            }
+        );
+        let deleted = EventAlignment::with_closure(&b, &a, |x, y| if x == y { 0 } else { 1 });
+        assert_eq!((deleted.deletions(), deleted.insertions()), (1, 0));
+        assert_eq!(
+            alignment.pairs()[3],
+            EventPair::Substituted {
+                index: (2, 3),
+                a: &Approve,
+                b: &Reject
+            }
+        );
+
+        let table = CostTable::new(
+            vec![
+                vec![0.0, 1.0, 1.0, 1.0],
+                vec![1.0, 0.0, 1.0, 1.0],
+                vec![1.0, 1.0, 0.0, 0.5],
+                vec![1.0, 1.0, 0.5, 0.0],
+            ],
+            vec![0.25; 4],
+            vec![1.0; 4],
+        );
+        let alignment = EventAlignment::with_table(&a, &b, &table, |e| *e as usize);
+        assert_eq!(alignment.distance(), 0.75);
+        assert_eq!(alignment.pairs().last().unwrap().index(), (2, 3));
+        assert_eq!(alignment.insertions(), 1);
+        let merged = |e: &Event| match e {
+            Reject => Approve as usize,
+            e => *e as usize,
+        };
+        let alignment = EventAlignment::with_table(&a, &b, &table, merged);
+        assert_eq!(alignment.distance(), 0.25);
+        assert_eq!(
+            alignment.pairs()[3],
+            EventPair::Matched {
+                index: (2, 3),
+                a: &Approve,
+                b: &Reject
+            }
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use std::ops::Add;
+
+use super::{dynamic_programming::Element, restriction::Window};
+use crate::{Algorithm, Cost, ParameterizedAlgorithm};
+
+/// Approximate dynamic time warping by FastDTW (Salvador and Chan, "Toward Accurate Dynamic Time
+/// Warping in Linear Time and Space"), with the radius of the refinement as parameter.
+///
+/// The sequences are coarsened by sampling every other element, aligned recursively, and the
+/// coarse path projected onto the finer resolution is refined within `radius` cells around it.
+/// Only the cells of the corridor are stored, such that the time and the memory are linear in
+/// the lengths of the sequences for a fixed radius.
+///
+/// Unlike the original algorithm, which averages pairs of elements, the elements are sampled
+/// since they are of an arbitrary type. [`multiscale::fast_dtw`](crate::multiscale::fast_dtw)
+/// averages the elements of numeric series and reports the radius and a bound on the error.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FastDtw<D> {
+    distance: D,
+    path: Vec<(usize, usize)>,
+}
+
+impl<D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost> Algorithm<D> for FastDtw<D> {
+    fn with_closure<T>(a: &[T], b: &[T], distance: impl Fn(&T, &T) -> D) -> Self {
+        Self::with_closure_and_param(a, b, distance, 1)
+    }
+
+    fn distance(&self) -> D {
+        self.distance.clone()
+    }
+
+    fn path(&self) -> Vec<(usize, usize)> {
+        self.path.clone()
+    }
+}
+
+impl<D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost> ParameterizedAlgorithm<D>
+    for FastDtw<D>
+{
+    type Param = usize;
+
+    fn with_closure_and_param<T>(
+        a: &[T],
+        b: &[T],
+        distance: impl Fn(&T, &T) -> D,
+        radius: Self::Param,
+    ) -> Self {
+        let (distance, path) = fast((a.len(), b.len()), 1, radius, &|i, j| {
+            distance(&a[i], &b[j])
+        });
+        Self { distance, path }
+    }
+}
+
+/// Alignment of the elements of every `stride`-th index, refined within `radius` cells around
+/// the alignment of the elements of every `2 * stride`-th index.
+fn fast<D: PartialOrd + Clone + Add<D, Output = D>>(
+    shape: (usize, usize),
+    stride: usize,
+    radius: usize,
+    distance: &impl Fn(usize, usize) -> D,
+) -> (D, Vec<(usize, usize)>) {
+    let level = (shape.0.div_ceil(stride), shape.1.div_ceil(stride));
+    let window = if level.0.min(level.1) <= radius + 2 {
+        Window::new(vec![(0, level.1); level.0])
+    } else {
+        let (_, coarse) = fast(shape, 2 * stride, radius, distance);
+        Window::from_path(&coarse, level, 2, radius)
+    };
+    windowed(level, &window, |i, j| distance(i * stride, j * stride))
+}
+
+/// Distance and path of the alignment of shape `shape` within `window`, storing only the cells
+/// of the window and the direction of the step reaching each of them.
+fn windowed<D: PartialOrd + Clone + Add<D, Output = D>>(
+    shape: (usize, usize),
+    window: &Window,
+    distance: impl Fn(usize, usize) -> D,
+) -> (D, Vec<(usize, usize)>) {
+    let rows = (0..shape.0).map(|i| window.row(i)).collect::<Vec<_>>();
+    let mut costs: Vec<Vec<Element<D>>> = Vec::with_capacity(shape.0);
+    let mut steps: Vec<Vec<(usize, usize)>> = Vec::with_capacity(shape.0);
+    for (i, (rb, re)) in rows.iter().copied().enumerate() {
+        let mut row_costs: Vec<Element<D>> = Vec::with_capacity(re - rb);
+        let mut row_steps = Vec::with_capacity(re - rb);
+        for j in rb..re {
+            let cell = |(di, dj): (usize, usize)| -> Element<D> {
+                if di > i || dj > j {
+                    return Element::Inf;
+                }
+                let (pi, pj) = (i - di, j - dj);
+                match di {
+                    0 if pj >= rb => row_costs[pj - rb].clone(),
+                    1 if rows[pi].0 <= pj && pj < rows[pi].1 => costs[pi][pj - rows[pi].0].clone(),
+                    _ => Element::Inf,
+                }
+            };
+            let (step, best) = [(1, 1), (1, 0), (0, 1)]
+                .into_iter()
+                .map(|step| (step, cell(step)))
+                .reduce(|best, candidate| {
+                    if candidate.1 < best.1 {
+                        candidate
+                    } else {
+                        best
+                    }
+                })
+                .unwrap();
+            let cost = match best {
+                Element::Inf if (i, j) == (0, 0) => Element::Value(distance(i, j)),
+                best => best + Element::Value(distance(i, j)),
+            };
+            row_costs.push(cost);
+            row_steps.push(step);
+        }
+        costs.push(row_costs);
+        steps.push(row_steps);
+    }
+    let (mut i, mut j) = (shape.0 - 1, shape.1 - 1);
+    let distance = match costs[i].get(j.wrapping_sub(rows[i].0)) {
+        Some(Element::Value(v)) => v.clone(),
+        _ => panic!("Infinit distance"),
+    };
+    let mut path = vec![(i, j)];
+    while (i, j) != (0, 0) {
+        let (di, dj) = steps[i][j - rows[i].0];
+        (i, j) = (i - di, j - dj);
+        path.push((i, j));
+    }
+    path.reverse();
+    (distance, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FastDtw;
+    use crate::{Algorithm, DynamicTimeWarping, ParameterizedAlgorithm};
+
+    #[test]
+    fn fast_dtw_approximates_exact() {
+        let a = (0..300)
+            .map(|i| f64::sin(i as f64 / 20.0))
+            .collect::<Vec<f64>>();
+        let b = (0..250)
+            .map(|i| f64::sin(i as f64 / 15.0 + 0.5))
+            .collect::<Vec<f64>>();
+        let exact = DynamicTimeWarping::between(&a, &b);
+        let fast = FastDtw::with_param(&a, &b, 4);
+        assert!(fast.distance() >= exact.distance() - 1e-9);
+        assert!(fast.distance() <= 1.05 * exact.distance());
+        let path = fast.path();
+        assert_eq!((path[0], path[path.len() - 1]), ((0, 0), (299, 249)));
+        assert!(path
+            .windows(2)
+            .all(|w| w[1].0 - w[0].0 <= 1 && w[1].1 - w[0].1 <= 1));
+        let wide = FastDtw::with_param(&a[..50], &b[..40], 100);
+        let exact = DynamicTimeWarping::between(&a[..50], &b[..40]);
+        assert_eq!(wide.distance(), exact.distance());
+        assert_eq!(wide.path(), exact.path());
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::VecDeque;
+
+use crate::Distance;
+
+/// Online recognizer of templates in a stream of samples, reporting every non-overlapping
+/// occurrence whose warped distance to a template is below a threshold.
+///
+/// Each template is matched against every subsequence of the stream ending at the latest sample
+/// with the incremental recursion of SPRING (Sakurai et al., "Stream Monitoring under the Time
+/// Warping Distance"), in `O(len)` time per sample and template.
+#[derive(Debug, Clone)]
+pub struct GestureRecognizer<T> {
+    templates: Vec<Vec<T>>,
+    threshold: f64,
+    states: Vec<Vec<(f64, usize)>>,
+    pending: Vec<Option<Detection>>,
+    buffer: VecDeque<T>,
+    capacity: usize,
+    time: usize,
+}
+
+/// Occurrence of a template in the stream.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Detection {
+    /// Index of the template.
+    pub template: usize,
+    /// Time of the first sample of the occurrence.
+    pub start: usize,
+    /// Time of the last sample of the occurrence.
+    pub end: usize,
+    /// Warped distance between the occurrence and the template.
+    pub score: f64,
+}
+
+impl<T: Distance<f64> + Clone> GestureRecognizer<T> {
+    /// Recognizer of `templates` with the distance threshold `threshold`, keeping the latest
+    /// `capacity` samples in its buffer.
+    pub fn new(templates: Vec<Vec<T>>, threshold: f64, capacity: usize) -> Self {
+        assert!(
+            templates.iter().all(|t| !t.is_empty()),
+            "Templates should not be empty"
+        );
+        Self {
+            states: templates
+                .iter()
+                .map(|t| vec![(f64::INFINITY, 0); t.len()])
+                .collect(),
+            pending: vec![None; templates.len()],
+            templates,
+            threshold,
+            buffer: VecDeque::with_capacity(capacity),
+            capacity,
+            time: 0,
+        }
+    }
+
+    /// Recognizer of the single template `query` with the distance threshold `threshold` and no
+    /// buffer, i.e. SPRING proper, in `O(m)` memory for a query of length `m`.
+    pub fn spring(query: Vec<T>, threshold: f64) -> Self {
+        Self::new(vec![query], threshold, 0)
+    }
+
+    /// Feed the next sample of the stream, returning the occurrences confirmed by it. An
+    /// occurrence is confirmed once no later sample can extend it into a better one.
+    pub fn push(&mut self, sample: T) -> Vec<Detection> {
+        let t = self.time;
+        let mut detections = Vec::new();
+        for (k, template) in self.templates.iter().enumerate() {
+            let previous = &self.states[k];
+            let mut column: Vec<(f64, usize)> = Vec::with_capacity(template.len());
+            for (i, x) in template.iter().enumerate() {
+                let cost = sample.distance(x);
+                let best = match i {
+                    0 => [(0.0, t), previous[0]],
+                    _ => [column[i - 1], previous[i]],
+                }
+                .into_iter()
+                .chain((i > 0).then(|| previous[i - 1]))
+                .reduce(|best, candidate| {
+                    if candidate.0 < best.0 {
+                        candidate
+                    } else {
+                        best
+                    }
+                })
+                .unwrap();
+                column.push((best.0 + cost, best.1));
+            }
+            if let Some(detection) = self.pending[k] {
+                if column
+                    .iter()
+                    .all(|(d, s)| *d >= detection.score || *s > detection.end)
+                {
+                    detections.push(detection);
+                    self.pending[k] = None;
+                    for cell in column.iter_mut().filter(|(_, s)| *s <= detection.end) {
+                        cell.0 = f64::INFINITY;
+                    }
+                }
+            }
+            let (score, start) = column[column.len() - 1];
+            if score <= self.threshold && self.pending[k].is_none_or(|p| score < p.score) {
+                self.pending[k] = Some(Detection {
+                    template: k,
+                    start,
+                    end: t,
+                    score,
+                });
+            }
+            self.states[k] = column;
+        }
+        if self.buffer.len() == self.capacity {
+            self.buffer.pop_front();
+        }
+        if self.capacity > 0 {
+            self.buffer.push_back(sample);
+        }
+        self.time += 1;
+        detections
+    }
+
+    /// End the stream, returning the occurrences still waiting for a confirmation, since no later
+    /// sample can extend them. Samples fed afterwards start new occurrences.
+    pub fn finish(&mut self) -> Vec<Detection> {
+        let mut detections = Vec::new();
+        for (states, pending) in self.states.iter_mut().zip(&mut self.pending) {
+            if let Some(detection) = pending.take() {
+                for cell in states.iter_mut().filter(|(_, s)| *s <= detection.end) {
+                    cell.0 = f64::INFINITY;
+                }
+                detections.push(detection);
+            }
+        }
+        detections
+    }
+
+    /// Samples of `detection` if they are all still in the buffer.
+    pub fn samples(&self, detection: &Detection) -> Option<Vec<T>> {
+        let first = self.time - self.buffer.len();
+        (detection.start >= first && detection.end < self.time).then(|| {
+            self.buffer
+                .range(detection.start - first..=detection.end - first)
+                .cloned()
+                .collect()
+        })
+    }
+
+    /// Number of samples fed so far.
+    pub fn time(&self) -> usize {
+        self.time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Detection, GestureRecognizer};
+
+    #[test]
+    fn detect_templates_in_stream() {
+        let templates = vec![vec![1.0, 5.0, 1.0], vec![-3.0, -3.0]];
+        let mut recognizer = GestureRecognizer::new(templates, 1.0, 8);
+        let stream = [0.0, 0.0, 1.0, 5.0, 5.0, 1.0, 0.0, 0.0, -3.0, -2.5, 0.0, 0.0];
+        let detections = stream
+            .iter()
+            .flat_map(|x| recognizer.push(*x))
+            .collect::<Vec<Detection>>();
+        assert_eq!(
+            detections,
+            [
+                Detection {
+                    template: 0,
+                    start: 2,
+                    end: 5,
+                    score: 0.0
+                },
+                Detection {
+                    template: 1,
+                    start: 8,
+                    end: 8,
+                    score: 0.0
+                }
+            ]
+        );
+        assert_eq!(recognizer.samples(&detections[0]), None);
+        assert_eq!(recognizer.samples(&detections[1]), Some(vec![-3.0]));
+        let mut spring = GestureRecognizer::spring(vec![1.0, 5.0, 1.0], 1.0);
+        let matches = stream
+            .iter()
+            .flat_map(|x| spring.push(*x))
+            .collect::<Vec<Detection>>();
+        assert_eq!(matches, detections[..1]);
+        assert_eq!(spring.samples(&matches[0]), None);
+    }
+
+    #[test]
+    fn finish_confirms_pending_occurrence() {
+        let mut recognizer = GestureRecognizer::spring(vec![1.0, 5.0, 1.0], 1.0);
+        let stream = [0.0, 1.0, 5.0, 1.0];
+        assert!(stream.iter().all(|x| recognizer.push(*x).is_empty()));
+        let detection = Detection {
+            template: 0,
+            start: 1,
+            end: 3,
+            score: 0.0,
+        };
+        assert_eq!(recognizer.finish(), [detection]);
+        assert_eq!(recognizer.finish(), []);
+        assert_eq!(recognizer.push(1.0), []);
+        assert_eq!(recognizer.finish(), []);
+    }
+}
@@ -0,0 +1,138 @@
+use std::ops::{Add, Range};
+
+use super::dynamic_programming::{DynamicTimeWarping, Element};
+use crate::Cost;
+
+impl<D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost> DynamicTimeWarping<D> {
+    /// Warped distance between `a` and `b` with the symmetric step pattern and no restriction,
+    /// along with the warped path, keeping only two rows of accumulated costs such that the
+    /// memory is linear in the lengths of `a` and `b`, e.g. for series whose matrix does not fit
+    /// in memory.
+    ///
+    /// As in Hirschberg's algorithm, the cheapest step from the middle row to the next one is
+    /// found from the accumulated costs from the start and towards the end, and the path is
+    /// recovered recursively above and below the step. The local costs are computed about twice
+    /// as many times as by [`DynamicTimeWarping::distance_only`].
+    pub fn linear_memory_path<T>(
+        a: &[T],
+        b: &[T],
+        distance: impl Fn(&T, &T) -> D,
+    ) -> (D, Vec<(usize, usize)>) {
+        assert!(
+            !a.is_empty() && !b.is_empty(),
+            "Sequences should not be empty"
+        );
+        let cost = |i: usize, j: usize| distance(&a[i], &b[j]);
+        let mut path = Vec::with_capacity(a.len() + b.len());
+        split(0..a.len(), 0..b.len(), &cost, &mut path);
+        let distance = path
+            .iter()
+            .map(|(i, j)| cost(*i, *j))
+            .reduce(|sum, cost| sum + cost)
+            .expect("Path should not be empty");
+        (distance, path)
+    }
+}
+
+/// Append to `path` the cheapest path from the first to the last cell of the block of the rows
+/// `rows` and the columns `columns`.
+fn split<D: PartialOrd + Clone + Add<D, Output = D>>(
+    rows: Range<usize>,
+    columns: Range<usize>,
+    cost: &impl Fn(usize, usize) -> D,
+    path: &mut Vec<(usize, usize)>,
+) {
+    if rows.len() == 1 {
+        path.extend(columns.map(|j| (rows.start, j)));
+        return;
+    }
+    if columns.len() == 1 {
+        path.extend(rows.map(|i| (i, columns.start)));
+        return;
+    }
+    let middle = rows.start + (rows.len() - 1) / 2;
+    let width = columns.len();
+    let forward = last_row(rows.start..middle + 1, columns.clone(), cost);
+    // Accumulated costs from the cells of the row below the middle one to the last cell,
+    // computed on the reversed block such that `backward[width - 1 - j]` is that of column `j`.
+    let backward = last_row(middle + 1..rows.end, 0..width, |i, j| {
+        cost(rows.end - 1 - (i - middle - 1), columns.end - 1 - j)
+    });
+    let mut best: Option<(usize, usize, Element<D>)> = None;
+    for (k, from) in forward.into_iter().enumerate() {
+        for next in [k, k + 1].into_iter().filter(|next| *next < width) {
+            let total = from.clone() + backward[width - 1 - next].clone();
+            if best.as_ref().is_none_or(|(_, _, best)| total < *best) {
+                best = Some((k, next, total));
+            }
+        }
+    }
+    let (k, next, _) = best.expect("Row should not be empty");
+    split(
+        rows.start..middle + 1,
+        columns.start..columns.start + k + 1,
+        cost,
+        path,
+    );
+    split(
+        middle + 1..rows.end,
+        columns.start + next..columns.end,
+        cost,
+        path,
+    );
+}
+
+/// Accumulated costs of the last row of the block of the rows `rows` and the columns `columns`
+/// with the symmetric step pattern from its first cell, computed with two rows.
+fn last_row<D: PartialOrd + Clone + Add<D, Output = D>>(
+    rows: Range<usize>,
+    columns: Range<usize>,
+    cost: impl Fn(usize, usize) -> D,
+) -> Vec<Element<D>> {
+    let mut previous = vec![Element::Inf; columns.len()];
+    let mut current = vec![Element::Inf; columns.len()];
+    for i in rows.clone() {
+        for (k, j) in columns.clone().enumerate() {
+            let local = Element::Value(cost(i, j));
+            current[k] = match (i == rows.start, k) {
+                (true, 0) => local,
+                (true, _) => current[k - 1].clone() + local,
+                (false, 0) => previous[0].clone() + local,
+                (false, _) => {
+                    let candidates = [&previous[k], &current[k - 1]];
+                    let best = candidates.into_iter().fold(&previous[k - 1], |best, c| {
+                        if c < best {
+                            c
+                        } else {
+                            best
+                        }
+                    });
+                    best.clone() + local
+                }
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{algorithms::utils::series, Algorithm, DynamicTimeWarping};
+
+    #[test]
+    fn linear_memory_path_is_optimal() {
+        let cost = |x: &f64, y: &f64| f64::abs(x - y);
+        for (n, m) in [(1, 1), (1, 7), (7, 1), (2, 2), (13, 40), (40, 13), (33, 33)] {
+            let (a, b) = (series(n, 3), series(m, 11));
+            let dtw = DynamicTimeWarping::between(&a, &b);
+            let (distance, path) = DynamicTimeWarping::linear_memory_path(&a, &b, cost);
+            assert!((distance - dtw.distance()).abs() < 1e-9);
+            assert_eq!(path.first(), Some(&(0, 0)));
+            assert_eq!(path.last(), Some(&(n - 1, m - 1)));
+            assert!(path
+                .windows(2)
+                .all(|w| [(0, 1), (1, 0), (1, 1)].contains(&(w[1].0 - w[0].0, w[1].1 - w[0].1))));
+        }
+    }
+}
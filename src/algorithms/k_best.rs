@@ -0,0 +1,112 @@
+use std::{cmp::Ordering, ops::Add};
+
+use super::{
+    dynamic_programming::{path_stop, DynamicTimeWarping, Parameters},
+    utils::Matrix,
+};
+use crate::Cost;
+
+/// Accumulated cost of a partial path reaching a cell, with the index of the predecessor it
+/// comes from in the step pattern and the rank of the partial path it extends there.
+#[derive(Debug, Clone)]
+struct Entry<D> {
+    cost: D,
+    from: Option<(usize, usize)>,
+}
+
+impl<D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost> DynamicTimeWarping<D> {
+    /// The `k` cheapest distinct warping paths between `a` and `b` with their distances, from the
+    /// cheapest one, e.g. to assess the uncertainty of the alignment. Fewer paths are returned if
+    /// there are less than `k` of them.
+    ///
+    /// As in the parallel list Viterbi algorithm, the `k` cheapest partial paths reaching each
+    /// cell are kept, such that the time and the memory are `k` times those of the optimal path.
+    /// The paths are ranked by their accumulated cost, before any normalization. Only the
+    /// single-step patterns are supported.
+    pub fn k_best_paths<T>(
+        a: &[T],
+        b: &[T],
+        distance: impl Fn(&T, &T) -> D,
+        hyper_parameters: impl Into<Parameters>,
+        k: usize,
+    ) -> Vec<(D, Vec<(usize, usize)>)> {
+        let hyper_parameters = hyper_parameters.into();
+        let shape = (a.len(), b.len());
+        let predecessors = hyper_parameters.single_step_pattern().predecessors();
+        let restriction = hyper_parameters.global_restriction(shape);
+        let distance = hyper_parameters.local_cost(shape, |i, j| distance(&a[i], &b[j]));
+        let stop = path_stop(&restriction, shape);
+        let mut lists: Matrix<Vec<Entry<D>>> = Matrix::fill(Vec::new(), shape.0, shape.1);
+        for i in 0..=stop.0 {
+            for j in restriction.row_range(shape, i) {
+                let local = distance(i, j);
+                let mut candidates = match (i, j) {
+                    (0, 0) => vec![Entry {
+                        cost: local,
+                        from: None,
+                    }],
+                    _ => predecessors
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, (di, dj))| *di <= i && *dj <= j)
+                        .flat_map(|(p, (di, dj))| {
+                            lists[(i - di, j - dj)]
+                                .iter()
+                                .enumerate()
+                                .map(move |(rank, entry)| (p, rank, entry))
+                        })
+                        .map(|(p, rank, entry)| Entry {
+                            cost: entry.cost.clone() + local.clone(),
+                            from: Some((p, rank)),
+                        })
+                        .collect::<Vec<Entry<D>>>(),
+                };
+                candidates.sort_by(|x, y| x.cost.partial_cmp(&y.cost).unwrap_or(Ordering::Equal));
+                candidates.truncate(k);
+                lists[(i, j)] = candidates;
+            }
+        }
+        (0..lists[stop].len())
+            .map(|rank| {
+                let cost = lists[stop][rank].cost.clone();
+                let (mut cell, mut rank) = (stop, rank);
+                let mut path = vec![cell];
+                while let Some((p, previous)) = lists[cell][rank].from {
+                    let (di, dj) = predecessors[p];
+                    (cell, rank) = ((cell.0 - di, cell.1 - dj), previous);
+                    path.push(cell);
+                }
+                path.reverse();
+                (hyper_parameters.normalize(&cost, shape, path.len()), path)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Algorithm, DynamicTimeWarping, Parameters};
+
+    #[test]
+    fn k_cheapest_paths() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let cost = |x: &f64, y: &f64| f64::abs(x - y);
+        let dtw = DynamicTimeWarping::between(&a, &b);
+        let best = DynamicTimeWarping::k_best_paths(&a, &b, cost, Parameters::new(), 10);
+        assert_eq!(best.len(), 10);
+        assert_eq!(best[0].0, dtw.distance());
+        assert!(best.windows(2).all(|w| w[0].0 <= w[1].0));
+        let mut paths = best.iter().map(|(_, path)| path).collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+        assert_eq!(paths.len(), 10);
+        for (distance, path) in &best {
+            let along = path.iter().map(|(i, j)| cost(&a[*i], &b[*j])).sum::<f64>();
+            assert_eq!(*distance, along);
+        }
+        let single =
+            DynamicTimeWarping::k_best_paths(&[1.0], &[2.0, 3.0], cost, Parameters::new(), 4);
+        assert_eq!(single, vec![(3.0, vec![(0, 0), (0, 1)])]);
+    }
+}
@@ -0,0 +1,199 @@
+use std::ops::{Add, Mul, Sub};
+
+use super::dynamic_programming::DynamicTimeWarping;
+
+/// Local cost of the vectorized computation of [`DynamicTimeWarping::vectorized_distance`].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum LaneCost {
+    /// `|x - y|`.
+    #[default]
+    Absolute,
+    /// `(x - y)^2`.
+    Squared,
+}
+
+/// Float processed in vector lanes by [`DynamicTimeWarping::vectorized_distance`].
+pub trait Lane:
+    Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    /// Positive infinity.
+    const INFINITY: Self;
+
+    /// Absolute value of `self`.
+    fn abs(self) -> Self;
+
+    /// Set every cell `cells[k]` to the least of `diagonal[k]`, `up[k]` and `left[k]` plus the
+    /// local cost `cost` between `xs[k]` and `ys[k]`, several cells at a time where the target
+    /// has vector instructions for `Self`, one at a time otherwise.
+    fn relax(cells: &mut [Self], predecessors: [&[Self]; 3], pairs: [&[Self]; 2], cost: LaneCost) {
+        relax_scalar(cells, predecessors, pairs, cost);
+    }
+}
+
+/// [`Lane::relax`] one cell at a time.
+fn relax_scalar<F: Lane>(
+    cells: &mut [F],
+    [diagonal, up, left]: [&[F]; 3],
+    [xs, ys]: [&[F]; 2],
+    cost: LaneCost,
+) {
+    let min = |x: F, y: F| if y < x { y } else { x };
+    for (k, cell) in cells.iter_mut().enumerate() {
+        let local = match cost {
+            LaneCost::Absolute => (xs[k] - ys[k]).abs(),
+            LaneCost::Squared => (xs[k] - ys[k]) * (xs[k] - ys[k]),
+        };
+        *cell = min(min(diagonal[k], up[k]), left[k]) + local;
+    }
+}
+
+macro_rules! impl_lane {
+    ($($t:ty => $width:literal, $vector:ident, [$load:ident, $store:ident, $set:ident,
+        $min:ident, $add:ident, $sub:ident, $mul:ident, $andnot:ident]);*) => {
+        $(impl Lane for $t {
+            const INFINITY: Self = <$t>::INFINITY;
+
+            fn abs(self) -> Self {
+                <$t>::abs(self)
+            }
+
+            #[cfg(target_arch = "x86_64")]
+            fn relax(
+                cells: &mut [Self],
+                [diagonal, up, left]: [&[Self]; 3],
+                [xs, ys]: [&[Self]; 2],
+                cost: LaneCost,
+            ) {
+                use std::arch::x86_64::*;
+                let count = cells.len();
+                assert!(
+                    [diagonal, up, left, xs, ys].iter().all(|s| s.len() == count),
+                    "Slices should have as many elements as the cells"
+                );
+                let whole = count / $width * $width;
+                // SAFETY: SSE2 is part of the x86_64 baseline, and the unaligned loads and stores
+                // of `$width` elements from `k < whole <= count` stay within the slices of `count`
+                // elements.
+                unsafe {
+                    let load = |s: &[Self], k: usize| -> $vector { $load(s.as_ptr().add(k)) };
+                    let sign = $set(-0.0);
+                    for k in (0..whole).step_by($width) {
+                        let best = $min($min(load(diagonal, k), load(up, k)), load(left, k));
+                        let difference = $sub(load(xs, k), load(ys, k));
+                        let local = match cost {
+                            LaneCost::Absolute => $andnot(sign, difference),
+                            LaneCost::Squared => $mul(difference, difference),
+                        };
+                        $store(cells.as_mut_ptr().add(k), $add(best, local));
+                    }
+                }
+                relax_scalar(
+                    &mut cells[whole..],
+                    [&diagonal[whole..], &up[whole..], &left[whole..]],
+                    [&xs[whole..], &ys[whole..]],
+                    cost,
+                );
+            }
+        })*
+    };
+}
+
+impl_lane!(
+    f32 => 4, __m128, [_mm_loadu_ps, _mm_storeu_ps, _mm_set1_ps,
+        _mm_min_ps, _mm_add_ps, _mm_sub_ps, _mm_mul_ps, _mm_andnot_ps];
+    f64 => 2, __m128d, [_mm_loadu_pd, _mm_storeu_pd, _mm_set1_pd,
+        _mm_min_pd, _mm_add_pd, _mm_sub_pd, _mm_mul_pd, _mm_andnot_pd]
+);
+
+impl<F: Lane> DynamicTimeWarping<F> {
+    /// Warped distance between `a` and `b` with the symmetric step pattern and the local cost
+    /// `cost`, computed anti-diagonal by anti-diagonal.
+    ///
+    /// The cells of an anti-diagonal only depend on the two previous ones, such that they are
+    /// computed from contiguous slices with SSE2 instructions on x86_64, processing four `f32` or
+    /// two `f64` cells at once. The memory is linear in the length of `a`. Unlike
+    /// [`Algorithm::between`](crate::Algorithm::between), neither the matrix nor the path is
+    /// kept, such that it is the fast path for the distance alone.
+    pub fn vectorized_distance(a: &[F], b: &[F], cost: LaneCost) -> F {
+        assert!(
+            !a.is_empty() && !b.is_empty(),
+            "Sequences should not be empty"
+        );
+        let (n, m) = (a.len(), b.len());
+        let reversed = b.iter().rev().copied().collect::<Vec<F>>();
+        let mut before = vec![F::INFINITY; n + 1];
+        let mut previous = vec![F::INFINITY; n + 1];
+        let mut current = vec![F::INFINITY; n + 1];
+        let local = |x: F, y: F| match cost {
+            LaneCost::Absolute => (x - y).abs(),
+            LaneCost::Squared => (x - y) * (x - y),
+        };
+        for d in 0..n + m - 1 {
+            let (low, high) = ((d + 1).saturating_sub(m), d.min(n - 1));
+            // The element of `b` matched with `a[i]` on the anti-diagonal `d` is
+            // `reversed[m - 1 - d + i]`.
+            let offset = m - 1 + low - d;
+            let mut start = low;
+            if low == 0 {
+                current[0] = match d {
+                    0 => local(a[0], reversed[offset]),
+                    _ => previous[0] + local(a[0], reversed[offset]),
+                };
+                start = 1;
+            }
+            if start > high {
+                std::mem::swap(&mut before, &mut previous);
+                std::mem::swap(&mut previous, &mut current);
+                continue;
+            }
+            let count = high + 1 - start;
+            let skip = start - low;
+            F::relax(
+                &mut current[start..start + count],
+                [
+                    &before[start - 1..start - 1 + count],
+                    &previous[start - 1..start - 1 + count],
+                    &previous[start..start + count],
+                ],
+                [
+                    &a[start..start + count],
+                    &reversed[offset + skip..offset + skip + count],
+                ],
+                cost,
+            );
+            std::mem::swap(&mut before, &mut previous);
+            std::mem::swap(&mut previous, &mut current);
+        }
+        previous[n - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LaneCost;
+    use crate::{algorithms::utils::series, Algorithm, DynamicTimeWarping};
+
+    #[test]
+    fn vectorized_matches_dynamic_programming() {
+        for (n, m) in [(1, 1), (1, 7), (7, 1), (13, 40), (40, 13), (33, 33)] {
+            let (a, b) = (series(n, 3), series(m, 11));
+            let absolute = DynamicTimeWarping::between(&a, &b).distance();
+            let squared = DynamicTimeWarping::with_closure(&a, &b, |x: &f64, y| (x - y) * (x - y));
+            assert_eq!(
+                DynamicTimeWarping::vectorized_distance(&a, &b, LaneCost::Absolute),
+                absolute
+            );
+            assert_eq!(
+                DynamicTimeWarping::vectorized_distance(&a, &b, LaneCost::Squared),
+                squared.distance()
+            );
+            let narrow = |s: &[f64]| s.iter().map(|x| *x as f32).collect::<Vec<f32>>();
+            let single = DynamicTimeWarping::vectorized_distance(
+                &narrow(&a),
+                &narrow(&b),
+                LaneCost::Absolute,
+            );
+            assert!((single as f64 - absolute).abs() < 1e-3 * absolute.max(1.0));
+        }
+    }
+}
@@ -0,0 +1,146 @@
+//! Recursion limiting the number of consecutive horizontal or vertical steps of the path.
+//!
+//! Each cell holds `2 * max_run + 1` states: state `0` is reached by a diagonal step (or is the
+//! origin), states `1..=max_run` by a run of that many horizontal steps, and states
+//! `max_run + 1..=2 * max_run` by a run of `state - max_run` vertical steps. The states of cell
+//! `(i, j)` are stored in row `i` at columns `j * states..(j + 1) * states`.
+
+use std::ops::Add;
+
+use super::{
+    dynamic_programming::Element, restriction::Restriction, step_pattern::StepPattern,
+    utils::Matrix,
+};
+
+pub(crate) fn optimize_states<D: Clone + PartialOrd + Add<D, Output = D>>(
+    states: &mut Matrix<Element<D>>,
+    matrix: &mut Matrix<Element<D>>,
+    restriction: &Restriction,
+    step_pattern: StepPattern,
+    max_run: usize,
+    distance: impl Fn(usize, usize) -> D,
+) {
+    let count = 2 * max_run + 1;
+    restriction.iter(matrix.shape()).for_each(|(i, j)| {
+        let cost = distance(i, j);
+        for s in 0..count {
+            states[(i, j * count + s)] =
+                match preceeding_state(states, (i, j, s), step_pattern, max_run) {
+                    Some(idx) => state(states, idx, max_run).clone() + Element::Value(cost.clone()),
+                    None if (i, j, s) == (0, 0, 0) => Element::Value(cost.clone()),
+                    None => Element::Inf,
+                };
+        }
+        matrix[(i, j)] =
+            state(states, (i, j, best_state(states, (i, j), max_run)), max_run).clone();
+    });
+}
+
+pub(crate) fn compute_state_path<D: PartialOrd>(
+    states: &Matrix<Element<D>>,
+    i: usize,
+    j: usize,
+    step_pattern: StepPattern,
+    max_run: usize,
+) -> Vec<(usize, usize)> {
+    let mut idx = (i, j, best_state(states, (i, j), max_run));
+    let mut v = vec![(i, j)];
+    while let Some(prev) = preceeding_state(states, idx, step_pattern, max_run) {
+        v.push((prev.0, prev.1));
+        idx = prev;
+    }
+    v.reverse();
+    v
+}
+
+fn state<D>(
+    states: &Matrix<Element<D>>,
+    idx: (usize, usize, usize),
+    max_run: usize,
+) -> &Element<D> {
+    &states[(idx.0, idx.1 * (2 * max_run + 1) + idx.2)]
+}
+
+fn best_state<D: PartialOrd>(
+    states: &Matrix<Element<D>>,
+    (i, j): (usize, usize),
+    max_run: usize,
+) -> usize {
+    (0..2 * max_run + 1)
+        .reduce(|best, s| {
+            if state(states, (i, j, s), max_run) < state(states, (i, j, best), max_run) {
+                s
+            } else {
+                best
+            }
+        })
+        .unwrap()
+}
+
+fn preceeding_state<D: PartialOrd>(
+    states: &Matrix<Element<D>>,
+    (i, j, s): (usize, usize, usize),
+    step_pattern: StepPattern,
+    max_run: usize,
+) -> Option<(usize, usize, usize)> {
+    let count = 2 * max_run + 1;
+    let offsets = step_pattern.predecessors();
+    let horizontal = offsets.contains(&(0, 1)) && j != 0;
+    let vertical = offsets.contains(&(1, 0)) && i != 0;
+    let candidates: Vec<(usize, usize, usize)> = if s == 0 {
+        offsets
+            .iter()
+            .filter(|(di, dj)| *di != 0 && *dj != 0 && *di <= i && *dj <= j)
+            .flat_map(|(di, dj)| (0..count).map(move |ps| (i - di, j - dj, ps)))
+            .collect()
+    } else if s == 1 && horizontal {
+        std::iter::once(0)
+            .chain(max_run + 1..count)
+            .map(|ps| (i, j - 1, ps))
+            .collect()
+    } else if s <= max_run && horizontal {
+        vec![(i, j - 1, s - 1)]
+    } else if s == max_run + 1 && vertical {
+        (0..=max_run).map(|ps| (i - 1, j, ps)).collect()
+    } else if s > max_run + 1 && vertical {
+        vec![(i - 1, j, s - 1)]
+    } else {
+        vec![]
+    };
+    candidates.into_iter().reduce(|best, idx| {
+        if state(states, idx, max_run) < state(states, best, max_run) {
+            idx
+        } else {
+            best
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compute_state_path, optimize_states};
+    use crate::{
+        algorithms::{dynamic_programming::Element, utils::Matrix},
+        Restriction, StepPattern,
+    };
+
+    #[test]
+    fn max_run_limits_horizontal_steps() {
+        let a = [0.0, 5.0];
+        let b = [0.0, 0.0, 0.0, 5.0];
+        let max_run = 1;
+        let mut states = Matrix::fill(Element::Inf, a.len(), b.len() * (2 * max_run + 1));
+        let mut matrix = Matrix::fill(Element::Inf, a.len(), b.len());
+        optimize_states(
+            &mut states,
+            &mut matrix,
+            &Restriction::None,
+            StepPattern::Symmetric,
+            max_run,
+            |i, j| f64::abs(a[i] - b[j]),
+        );
+        let path = compute_state_path(&states, 1, 3, StepPattern::Symmetric, max_run);
+        assert_eq!(path, [(0, 0), (0, 1), (1, 2), (1, 3)]);
+        assert_eq!(matrix[(1, 3)], Element::Value(5.0));
+    }
+}
@@ -0,0 +1,231 @@
+use std::ops::Add;
+
+use super::{
+    dynamic_programming::{DynamicTimeWarping, Element},
+    step_pattern::StepPattern,
+    utils::Matrix,
+};
+use crate::{Algorithm, Cost};
+
+/// Policy for the cost of pairs involving missing values.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Missing<D> {
+    /// Pairs involving a missing value cost the given penalty.
+    Penalty(D),
+    /// Missing values are replaced by the last observed value, or by the first observed value
+    /// when no value was observed before.
+    CarryForward,
+    /// Only pairs of observed values contribute to the cost, and every observed value is matched
+    /// with at least one observed value of the other sequence, as when aligning the observed
+    /// values alone. Missing values are matched along at no cost, such that a gap cannot absorb
+    /// a run of the other sequence.
+    PairwiseComplete,
+}
+
+impl<D: Clone + Default> Missing<D> {
+    /// Local cost between `a[i]` and `b[j]` under this policy.
+    pub(crate) fn closure<'a, T>(
+        &'a self,
+        a: &'a [Option<T>],
+        b: &'a [Option<T>],
+        distance: impl Fn(&T, &T) -> D + 'a,
+    ) -> impl Fn(usize, usize) -> D + 'a {
+        let (fill_a, fill_b) = match self {
+            Missing::CarryForward => (carry_forward(a), carry_forward(b)),
+            _ => (
+                a.iter()
+                    .enumerate()
+                    .map(|(i, v)| v.as_ref().map(|_| i))
+                    .collect(),
+                b.iter()
+                    .enumerate()
+                    .map(|(j, v)| v.as_ref().map(|_| j))
+                    .collect(),
+            ),
+        };
+        move |i, j| match (fill_a[i], fill_b[j]) {
+            (Some(i), Some(j)) => match (&a[i], &b[j]) {
+                (Some(x), Some(y)) => distance(x, y),
+                _ => unreachable!(),
+            },
+            _ => match self {
+                Missing::Penalty(penalty) => penalty.clone(),
+                _ => D::default(),
+            },
+        }
+    }
+}
+
+impl<D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost> DynamicTimeWarping<D> {
+    /// Dynamic time warping between sequences `a` and `b` with missing values, using the distance
+    /// closure `distance` between observed values and the policy `missing` otherwise.
+    pub fn with_missing<T>(
+        a: &[Option<T>],
+        b: &[Option<T>],
+        missing: Missing<D>,
+        distance: impl Fn(&T, &T) -> D,
+    ) -> Self {
+        let cost = missing.closure(a, b, distance);
+        if missing == Missing::PairwiseComplete {
+            let mut matrix = Matrix::fill(Element::Inf, a.len(), b.len());
+            let mut moves = Matrix::fill(None, a.len(), b.len());
+            optimize_complete(&mut matrix, &mut moves, a, b, cost);
+            return Self::with_moves(matrix, moves);
+        }
+        let indices = |n: usize| (0..n).collect::<Vec<usize>>();
+        Self::with_closure(&indices(a.len()), &indices(b.len()), |i, j| cost(*i, *j))
+    }
+
+    /// Dynamic time warping between sequences `a` and `b` using the distance closure `distance`,
+    /// where the elements flagged by `mask_a` and `mask_b` are matched at zero cost.
+    pub fn with_masks<T>(
+        a: &[T],
+        b: &[T],
+        mask_a: &[bool],
+        mask_b: &[bool],
+        distance: impl Fn(&T, &T) -> D,
+    ) -> Self {
+        assert!(
+            a.len() == mask_a.len() && b.len() == mask_b.len(),
+            "Masks should have the same length as the sequences"
+        );
+        let indices = |n: usize| (0..n).collect::<Vec<usize>>();
+        Self::with_closure(&indices(a.len()), &indices(b.len()), |i, j| {
+            if mask_a[*i] || mask_b[*j] {
+                D::default()
+            } else {
+                distance(&a[*i], &b[*j])
+            }
+        })
+    }
+}
+
+/// Accumulate the costs of the pairs of observed values of `a` and `b`, recording the chosen step
+/// at each cell in `trace` in the order of the symmetric step pattern. A cell pairing a missing
+/// value with an observed one is only entered along the observed one, e.g. vertically if `a[i]`
+/// is missing, such that every observed value is first matched with an observed value. Before
+/// the first observed value of a sequence, such a cell is also only left along the observed one.
+/// Every cell is free if one of the sequences has no observed value.
+fn optimize_complete<T, D: Clone + Default + PartialOrd + Add<D, Output = D>>(
+    matrix: &mut Matrix<Element<D>>,
+    trace: &mut Matrix<Option<u8>>,
+    a: &[Option<T>],
+    b: &[Option<T>],
+    cost: impl Fn(usize, usize) -> D,
+) {
+    let first = |series: &[Option<T>]| series.iter().position(Option::is_some);
+    let firsts = first(a).zip(first(b));
+    let along = |(i, j): (usize, usize)| match (a[i].is_some(), b[j].is_some()) {
+        (false, true) => Some((1, 0)),
+        (true, false) => Some((0, 1)),
+        _ => None,
+    };
+    let admits = |step: (usize, usize), (i, j): (usize, usize)| {
+        let (k, l) = (i - step.0, j - step.1);
+        match firsts {
+            Some((first_a, first_b)) => {
+                along((i, j)).is_none_or(|direction| direction == step)
+                    && match along((k, l)) {
+                        Some(direction) if k < first_a || l < first_b => direction == step,
+                        _ => true,
+                    }
+            }
+            None => true,
+        }
+    };
+    let steps = StepPattern::Symmetric.predecessors();
+    let (n, m) = matrix.shape();
+    for (i, j) in (0..n).flat_map(|i| (0..m).map(move |j| (i, j))) {
+        let local = match (&a[i], &b[j]) {
+            (Some(_), Some(_)) => cost(i, j),
+            _ => D::default(),
+        };
+        if (i, j) == (0, 0) {
+            matrix[(0, 0)] = Element::Value(local);
+            continue;
+        }
+        let best = steps
+            .iter()
+            .enumerate()
+            .filter(|(_, (di, dj))| *di <= i && *dj <= j && admits((*di, *dj), (i, j)))
+            .map(|(s, (di, dj))| (s, matrix[(i - di, j - dj)].clone()))
+            .reduce(|best, candidate| {
+                if candidate.1 < best.1 {
+                    candidate
+                } else {
+                    best
+                }
+            });
+        if let Some((s, accumulated)) = best.filter(|(_, cost)| *cost != Element::Inf) {
+            matrix[(i, j)] = accumulated + Element::Value(local);
+            trace[(i, j)] = Some(s as u8);
+        }
+    }
+}
+
+fn carry_forward<T>(series: &[Option<T>]) -> Vec<Option<usize>> {
+    let first = series.iter().position(Option::is_some);
+    let mut last = first;
+    series
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            if v.is_some() {
+                last = Some(i);
+            }
+            last
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{carry_forward, Missing};
+    use crate::{Algorithm, DynamicTimeWarping};
+
+    #[test]
+    fn carry_forward_indices() {
+        let series = [None, Some(1.0), None, None, Some(2.0), None];
+        assert_eq!(
+            carry_forward(&series),
+            [Some(1), Some(1), Some(1), Some(1), Some(4), Some(4)]
+        );
+        assert_eq!(carry_forward::<f64>(&[None, None]), [None, None]);
+    }
+
+    #[test]
+    fn missing_closure_policies() {
+        let a = [Some(1.0), None];
+        let b = [Some(3.0), None];
+        let distance = |x: &f64, y: &f64| f64::abs(x - y);
+        let penalty = Missing::Penalty(10.0);
+        let cost = penalty.closure(&a, &b, distance);
+        assert_eq!((cost(0, 0), cost(0, 1), cost(1, 1)), (2.0, 10.0, 10.0));
+        let forward = Missing::CarryForward;
+        let cost = forward.closure(&a, &b, distance);
+        assert_eq!((cost(0, 0), cost(0, 1), cost(1, 1)), (2.0, 2.0, 2.0));
+        let complete = Missing::PairwiseComplete;
+        let cost = complete.closure(&a, &b, distance);
+        assert_eq!((cost(0, 0), cost(0, 1), cost(1, 1)), (2.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn pairwise_complete_gap_matches_one_value() {
+        let distance = |x: &f64, y: &f64| f64::abs(x - y);
+        let a = [Some(0.0), None, Some(0.0)];
+        let b = [Some(0.0), Some(5.0), Some(5.0), Some(5.0), Some(0.0)];
+        let dtw = DynamicTimeWarping::with_missing(&a, &b, Missing::PairwiseComplete, distance);
+        assert_eq!(dtw.distance(), 15.0);
+        assert_eq!(dtw.path().iter().filter(|(i, _)| *i == 1).count(), 1);
+        let leading = [None, Some(1.0)];
+        let b = [Some(4.0), Some(1.0)];
+        let dtw =
+            DynamicTimeWarping::with_missing(&leading, &b, Missing::PairwiseComplete, distance);
+        assert_eq!(dtw.distance(), 3.0);
+        assert_eq!(dtw.path(), [(0, 0), (1, 0), (1, 1)]);
+        let unobserved = [None, None];
+        let dtw =
+            DynamicTimeWarping::with_missing(&unobserved, &b, Missing::PairwiseComplete, distance);
+        assert_eq!(dtw.distance(), 0.0);
+    }
+}
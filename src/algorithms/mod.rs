@@ -1,4 +1,65 @@
+mod alphabet;
+mod anchored;
+mod anytime;
+mod binary;
+mod compact;
+mod compensated;
+mod diff;
 mod dynamic_programming;
+mod events;
+mod fast;
+mod gesture;
+mod hirschberg;
+mod k_best;
+#[cfg(feature = "simd")]
+mod lanes;
+mod max_run;
+mod missing;
+mod multivariate;
+mod normalization;
+mod online;
+mod restriction;
+mod run_length;
+mod segmented;
+mod similarity;
+mod soft;
+mod step_pattern;
+mod subsequence;
+mod transform;
+mod trim;
 mod utils;
+#[cfg(feature = "rayon")]
+mod wavefront;
 
-pub use dynamic_programming::{DynamicTimeWarping, Restriction};
\ No newline at end of file
+pub use alphabet::CostTable;
+pub use anchored::Anchored;
+pub use anytime::{Anytime, Budget};
+pub use binary::{binary_distance, Bitmap};
+pub use compact::Compact;
+pub use compensated::Compensated;
+pub use diff::{DiffKind, DiffRun};
+pub use dynamic_programming::{DynamicTimeWarping, FreeCorner, Parameters, Step};
+pub use events::{EventAlignment, EventPair};
+pub use fast::FastDtw;
+pub use gesture::{Detection, GestureRecognizer};
+#[cfg(feature = "simd")]
+pub use lanes::{Lane, LaneCost};
+pub use missing::Missing;
+pub use multivariate::{Multivariate, MultivariateAlignment};
+pub use normalization::Normalization;
+pub use online::OnlineDtw;
+pub use restriction::{Custom, Landmark, Restriction, Window};
+pub use run_length::{run_length_distance, run_length_encode};
+pub use segmented::{Segmentation, Segmented};
+pub use similarity::Similarity;
+pub use soft::SoftDtw;
+pub use step_pattern::{LocalContinuity, SlopeWeighting, StepPattern, TieBreak};
+pub use subsequence::{Open, Subsequence};
+pub use transform::Transform;
+pub use trim::{Trim, Trimmed};
+pub use utils::Matrix;
+
+pub(crate) use dynamic_programming::Element;
+#[cfg(feature = "deterministic")]
+pub(crate) use similarity::{portable_acos, portable_powf};
+pub(crate) use subsequence::accumulate as accumulate_subsequence;
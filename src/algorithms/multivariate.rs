@@ -0,0 +1,117 @@
+use super::dynamic_programming::{DynamicTimeWarping, Parameters};
+use crate::Algorithm;
+
+/// Strategy of dynamic time warping between multivariate series, e.g. of the `(x, y, z)`
+/// samples of an accelerometer.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Multivariate {
+    /// Single warping of all the dimensions together, with the Euclidean distance between the
+    /// vectors as local cost.
+    #[default]
+    Dependent,
+    /// Separate warping of every dimension, with the absolute difference as local cost, and the
+    /// distances summed over the dimensions.
+    Independent,
+}
+
+/// Alignment of two multivariate series computed with a [`Multivariate`] strategy.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MultivariateAlignment {
+    distance: f64,
+    paths: Vec<Vec<(usize, usize)>>,
+}
+
+impl MultivariateAlignment {
+    /// Alignment of the series of vectors `a` and `b` of a common dimension by the strategy
+    /// `strategy`, with the hyper-parameters `hyper_parameters` applied to every warping.
+    pub fn new<V: AsRef<[f64]>>(
+        a: &[V],
+        b: &[V],
+        strategy: Multivariate,
+        hyper_parameters: impl Into<Parameters>,
+    ) -> Self {
+        let dimension = a.first().or(b.first()).map_or(0, |v| v.as_ref().len());
+        assert!(
+            a.iter().chain(b).all(|v| v.as_ref().len() == dimension),
+            "Vectors should have the same dimension"
+        );
+        let hyper_parameters = hyper_parameters.into();
+        match strategy {
+            Multivariate::Dependent => {
+                let dtw = DynamicTimeWarping::with_closure_and_param(
+                    a,
+                    b,
+                    |x, y| {
+                        x.as_ref()
+                            .iter()
+                            .zip(y.as_ref())
+                            .map(|(x, y)| (x - y) * (x - y))
+                            .sum::<f64>()
+                            .sqrt()
+                    },
+                    hyper_parameters,
+                );
+                Self {
+                    distance: dtw.distance(),
+                    paths: vec![dtw.path()],
+                }
+            }
+            Multivariate::Independent => {
+                let dtws = (0..dimension)
+                    .map(|d| {
+                        DynamicTimeWarping::with_closure_and_param(
+                            a,
+                            b,
+                            |x, y| f64::abs(x.as_ref()[d] - y.as_ref()[d]),
+                            hyper_parameters.clone(),
+                        )
+                    })
+                    .collect::<Vec<DynamicTimeWarping<f64>>>();
+                Self {
+                    distance: dtws.iter().map(|dtw| dtw.distance()).sum(),
+                    paths: dtws.iter().map(|dtw| dtw.path()).collect(),
+                }
+            }
+        }
+    }
+
+    /// Warped distance between the series, summed over the dimensions by the independent
+    /// strategy.
+    pub fn distance(&self) -> f64 {
+        self.distance
+    }
+
+    /// Warped paths between the series, a single one by the dependent strategy and one per
+    /// dimension by the independent strategy.
+    pub fn paths(&self) -> &[Vec<(usize, usize)>] {
+        &self.paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Multivariate, MultivariateAlignment};
+    use crate::{Algorithm, DynamicTimeWarping, Parameters};
+
+    #[test]
+    fn dependent_and_independent() {
+        let a = [[0.0, 1.0], [1.0, 1.0], [2.0, 0.0], [2.0, 0.0]];
+        let b = [[0.0, 1.0], [2.0, 1.0], [2.0, 1.0], [2.0, 0.0]];
+        let dependent =
+            MultivariateAlignment::new(&a, &b, Multivariate::Dependent, Parameters::new());
+        assert_eq!(dependent.paths().len(), 1);
+        assert_eq!(dependent.paths()[0].last(), Some(&(3, 3)));
+        let independent =
+            MultivariateAlignment::new(&a, &b, Multivariate::Independent, Parameters::new());
+        let column = |s: &[[f64; 2]], d: usize| s.iter().map(|v| v[d]).collect::<Vec<f64>>();
+        let per_dimension = (0..2)
+            .map(|d| DynamicTimeWarping::between(&column(&a, d), &column(&b, d)))
+            .collect::<Vec<DynamicTimeWarping<f64>>>();
+        assert_eq!(
+            independent.distance(),
+            per_dimension[0].distance() + per_dimension[1].distance()
+        );
+        assert_eq!(independent.paths()[1], per_dimension[1].path());
+        assert!(independent.distance() <= dependent.distance() * 2.0_f64.sqrt());
+    }
+}
@@ -0,0 +1,46 @@
+use crate::Cost;
+
+/// Normalization of the warped distance, such that the distances between pairs of sequences of
+/// different lengths are comparable.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Normalization {
+    /// The accumulated cost, left unchanged.
+    #[default]
+    None,
+    /// The accumulated cost divided by the number of cells of the path.
+    PathLength,
+    /// The accumulated cost divided by `n + m`, the sum of the lengths of the sequences.
+    Sum,
+    /// The accumulated cost divided by `max(n, m)`.
+    Max,
+}
+
+impl Normalization {
+    /// Normalized distance of the accumulated cost `distance` between sequences of lengths
+    /// `shape` along a path of `path_length` cells.
+    pub fn apply<D: Cost + Clone>(
+        &self,
+        distance: &D,
+        shape: (usize, usize),
+        path_length: usize,
+    ) -> D {
+        let divisor = match self {
+            Normalization::None => return distance.clone(),
+            Normalization::PathLength => path_length,
+            Normalization::Sum => shape.0 + shape.1,
+            Normalization::Max => shape.0.max(shape.1),
+        };
+        distance.weighted(1.0 / divisor as f64)
+    }
+
+    /// Greatest divisor of a distance between sequences of lengths `shape`, the longest path
+    /// having `n + m - 1` cells.
+    pub(crate) fn max_divisor(&self, shape: (usize, usize)) -> f64 {
+        match self {
+            Normalization::None => 1.0,
+            Normalization::PathLength => (shape.0 + shape.1 - 1) as f64,
+            Normalization::Sum => (shape.0 + shape.1) as f64,
+            Normalization::Max => shape.0.max(shape.1) as f64,
+        }
+    }
+}
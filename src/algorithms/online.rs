@@ -0,0 +1,123 @@
+use std::collections::VecDeque;
+
+use crate::Distance;
+
+/// Dynamic time warping between a fixed reference and a query fed one sample at a time, e.g.
+/// from a sensor stream.
+///
+/// Every sample adds a column of accumulated costs computed from the previous one, in `O(n)`
+/// time for a reference of length `n`. Only the latest `window` columns are retained, bounding
+/// the memory and the part of the path that can be recovered.
+#[derive(Debug, Clone)]
+pub struct OnlineDtw<T> {
+    reference: Vec<T>,
+    columns: VecDeque<Vec<f64>>,
+    window: usize,
+    time: usize,
+}
+
+impl<T: Distance<f64>> OnlineDtw<T> {
+    /// Online alignment against `reference`, retaining the latest `window` columns.
+    pub fn new(reference: Vec<T>, window: usize) -> Self {
+        assert!(!reference.is_empty(), "Reference should not be empty");
+        assert!(window > 0, "Window should be positive");
+        Self {
+            reference,
+            columns: VecDeque::with_capacity(window),
+            window,
+            time: 0,
+        }
+    }
+
+    /// Feed the next sample of the query, returning the warped distance between the reference
+    /// and the query so far.
+    pub fn push(&mut self, sample: T) -> f64 {
+        let previous = self.columns.back();
+        let mut column: Vec<f64> = Vec::with_capacity(self.reference.len());
+        for (i, x) in self.reference.iter().enumerate() {
+            let cost = x.distance(&sample);
+            let best = match (i, previous) {
+                (0, None) => 0.0,
+                (0, Some(previous)) => previous[0],
+                (_, None) => column[i - 1],
+                (_, Some(previous)) => [previous[i - 1], column[i - 1], previous[i]]
+                    .into_iter()
+                    .reduce(|best, candidate| if candidate < best { candidate } else { best })
+                    .unwrap(),
+            };
+            column.push(best + cost);
+        }
+        if self.columns.len() == self.window {
+            self.columns.pop_front();
+        }
+        self.columns.push_back(column);
+        self.time += 1;
+        self.distance().unwrap()
+    }
+
+    /// Warped distance between the reference and the query so far, or `None` before the first
+    /// sample.
+    pub fn distance(&self) -> Option<f64> {
+        self.columns.back().map(|column| column[column.len() - 1])
+    }
+
+    /// Latest part of the warped path within the retained columns, up to the cell matching the
+    /// last elements of the reference and of the query, with the query indexed by time.
+    pub fn path(&self) -> Vec<(usize, usize)> {
+        let Some(last) = self.columns.len().checked_sub(1) else {
+            return Vec::new();
+        };
+        let first = self.time - self.columns.len();
+        let cost = |i: usize, k: usize| self.columns[k][i];
+        let (mut i, mut k) = (self.reference.len() - 1, last);
+        let mut path = vec![(i, first + k)];
+        while k > 0 || (i > 0 && first == 0) {
+            let predecessor = [(1, 1), (1, 0), (0, 1)]
+                .into_iter()
+                .filter(|(di, dk)| *di <= i && *dk <= k)
+                .reduce(|best, (di, dk)| {
+                    if cost(i - di, k - dk) < cost(i - best.0, k - best.1) {
+                        (di, dk)
+                    } else {
+                        best
+                    }
+                })
+                .unwrap();
+            (i, k) = (i - predecessor.0, k - predecessor.1);
+            path.push((i, first + k));
+        }
+        path.reverse();
+        path
+    }
+
+    /// Number of samples fed so far.
+    pub fn time(&self) -> usize {
+        self.time
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OnlineDtw;
+    use crate::{Algorithm, DynamicTimeWarping};
+
+    #[test]
+    fn online_matches_offline() {
+        let reference = vec![1.0, 3.0, 9.0, 2.0, 1.0];
+        let query = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let mut online = OnlineDtw::new(reference.clone(), query.len());
+        let mut windowed = OnlineDtw::new(reference.clone(), 2);
+        assert_eq!(online.distance(), None);
+        for t in 0..query.len() {
+            let offline = DynamicTimeWarping::between(&reference, &query[..=t]);
+            assert_eq!(online.push(query[t]), offline.distance());
+            assert_eq!(windowed.push(query[t]), offline.distance());
+        }
+        let offline = DynamicTimeWarping::between(&reference, &query);
+        assert_eq!(online.path(), offline.path());
+        let tail = windowed.path();
+        assert!(tail.iter().all(|(_, t)| *t >= 4));
+        assert!(offline.path().ends_with(&tail));
+        assert_eq!(windowed.time(), 6);
+    }
+}
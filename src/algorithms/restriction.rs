@@ -0,0 +1,437 @@
+use std::{ops::Range, sync::Arc};
+
+use crate::Error;
+
+/// Global constraint on the cells the warping path may visit.
+///
+/// It is not `Copy`, since [`Restriction::Window`] and [`Restriction::Custom`] hold their rows
+/// and their predicate behind a reference count, such that cloning it stays cheap.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub enum Restriction {
+    /// Every cell is admitted.
+    #[default]
+    None,
+    /// Sakoe-Chiba band admitting the cells `(i, j)` with `|i - j| <= width`. If the width is
+    /// less than [`Restriction::minimal_band`], the bottom-right corner lies outside of the band
+    /// and the path is cut off at the last cell of the band. Only the cells of the band are
+    /// stored, such that the memory is linear in the length of the sequences.
+    Band(usize),
+    /// Sakoe-Chiba band of width `width` widened to [`Restriction::minimal_band`] if narrower,
+    /// such that the path always ends at the bottom-right corner.
+    FittedBand(usize),
+    /// Band slanted along the line from the origin to the bottom-right corner, admitting the
+    /// cells `(i, j)` with `|(j + 1) - (i + 1) * m / n| <= width` for a matrix of shape `(n, m)`,
+    /// as `slantedBandWindow` of the R dtw package with its one-based indices. It coincides with
+    /// [`Restriction::Band`] for sequences of equal lengths, and the first cell is only admitted
+    /// if the width is at least `m / n - 1`.
+    SlantedBand(usize),
+    /// Arbitrary corridor given as a range of columns for each row.
+    Window(Window),
+    /// Bounds `(min, max)` of the slope of the warping path, i.e. of the progress in `b` per
+    /// step of progress in `a`, from the start and towards the end of the path.
+    Slope(f64, f64),
+    /// Itakura parallelogram with the maximum slope `s`, i.e. the slope bounds `(1 / s, s)`. The
+    /// slope should be greater than one, since at most the diagonal is admitted otherwise, which
+    /// [`Restriction::itakura`] and the fallible constructors of the alignments check.
+    Itakura(f64),
+    /// Cells admitted by a predicate, e.g. a data-driven window.
+    Custom(Custom),
+}
+
+/// Ordering constraint between an index of `a` and an index of `b`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Landmark {
+    /// `NoLaterThan(p, q)`: `a[p]` is matched with no index of `b` later than `q`.
+    NoLaterThan(usize, usize),
+    /// `NoEarlierThan(p, q)`: `a[p]` is matched with no index of `b` earlier than `q`.
+    NoEarlierThan(usize, usize),
+}
+
+/// Corridor admitting the columns `begin..end` in each row.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Window {
+    rows: Arc<[(usize, usize)]>,
+}
+
+/// Predicate `admits(i, j, shape)` telling whether the cell `(i, j)` of a matrix of shape
+/// `shape` is admitted. The admitted cells of each row should be contiguous, as every column
+/// between the first and the last admitted ones of a row is admitted.
+#[derive(Clone)]
+pub struct Custom {
+    admits: Arc<Admits>,
+}
+
+/// Predicate of a [`Custom`] restriction.
+type Admits = dyn Fn(usize, usize, (usize, usize)) -> bool + Send + Sync;
+
+impl Restriction {
+    /// Itakura parallelogram with the maximum slope `slope`, or [`Error::InvalidSlope`] if it is
+    /// not greater than one.
+    pub fn itakura(slope: f64) -> Result<Self, Error> {
+        match slope > 1.0 {
+            true => Ok(Restriction::Itakura(slope)),
+            false => Err(Error::InvalidSlope),
+        }
+    }
+
+    /// Minimal width of a Sakoe-Chiba band admitting the bottom-right corner of a matrix of
+    /// shape `shape`, i.e. the difference between the lengths of the sequences.
+    pub fn minimal_band(shape: (usize, usize)) -> usize {
+        shape.0.abs_diff(shape.1)
+    }
+
+    /// Whether the cell `index` of a matrix of shape `shape` is admitted.
+    pub fn contains(&self, index: (usize, usize), shape: (usize, usize)) -> bool {
+        let (rb, re) = self.range(shape, index.0);
+        rb <= index.1 && index.1 < re
+    }
+
+    /// Cells of a matrix of shape `shape` admitted by the restriction, row by row and column by
+    /// column within each row, i.e. in the order they are computed.
+    pub fn iter(&self, shape: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        let restriction = self.clone();
+        (0..shape.0).flat_map(move |i| {
+            let (rb, re) = restriction.range(shape, i);
+            (rb..re).map(move |j| (i, j))
+        })
+    }
+
+    /// Columns admitted in row `i` of a matrix of shape `shape`, empty if the row has no admitted
+    /// cell.
+    pub fn row_range(&self, shape: (usize, usize), i: usize) -> Range<usize> {
+        let (rb, re) = self.range(shape, i);
+        rb..re.max(rb)
+    }
+
+    /// Corridor of the cells admitted by the restriction that also satisfy `landmarks`.
+    pub(crate) fn with_landmarks(&self, landmarks: &[Landmark], shape: (usize, usize)) -> Self {
+        let mut rows = (0..shape.0)
+            .map(|i| self.range(shape, i))
+            .collect::<Vec<(usize, usize)>>();
+        for landmark in landmarks {
+            match *landmark {
+                Landmark::NoLaterThan(p, q) if p < shape.0 => rows[p].1 = rows[p].1.min(q + 1),
+                Landmark::NoEarlierThan(p, q) if p < shape.0 => rows[p].0 = rows[p].0.max(q),
+                _ => {}
+            }
+        }
+        Restriction::Window(Window::new(rows))
+    }
+
+    fn range(&self, shape: (usize, usize), y: usize) -> (usize, usize) {
+        match self {
+            Restriction::None => (0, shape.1),
+            Restriction::Band(size) => {
+                let min = (y as f32 - *size as f32).max(0.0) as usize;
+                let max = (y as f32 + *size as f32 + 1.0).min(shape.1 as f32) as usize;
+                (min, max)
+            }
+            Restriction::FittedBand(size) => {
+                Restriction::Band((*size).max(Self::minimal_band(shape))).range(shape, y)
+            }
+            Restriction::SlantedBand(width) => {
+                let (n, m) = (shape.0 as i64, shape.1 as i64);
+                let (center, spread) = ((y as i64 + 1) * m, *width as i64 * n);
+                let rb = -(spread - center).div_euclid(n) - 1;
+                let re = (center + spread).div_euclid(n);
+                (
+                    rb.clamp(0, m) as usize,
+                    re.clamp(0, m).max(rb.clamp(0, m)) as usize,
+                )
+            }
+            Restriction::Window(window) => window
+                .rows
+                .get(y)
+                .map(|(rb, re)| (*rb, (*re).min(shape.1)))
+                .unwrap_or((0, 0)),
+            Restriction::Custom(custom) => {
+                let admits = |j: &usize| (custom.admits)(y, *j, shape);
+                match (0..shape.1).find(admits) {
+                    Some(rb) => (rb, (rb..shape.1).rfind(admits).map_or(rb, |j| j) + 1),
+                    None => (0, 0),
+                }
+            }
+            Restriction::Itakura(slope) => Restriction::Slope(1.0 / slope, *slope).range(shape, y),
+            Restriction::Slope(min, max) => {
+                let (y, rows, cols) = (y as f64, shape.0 as f64 - 1.0, shape.1 as f64 - 1.0);
+                let lower = (min * y).max(cols - max * (rows - y));
+                let upper = (max * y).min(cols - min * (rows - y));
+                let rb = (lower - 1e-9).ceil().max(0.0) as usize;
+                let re = ((upper + 1e-9).floor() + 1.0).clamp(0.0, shape.1 as f64) as usize;
+                (rb, re.max(rb))
+            }
+        }
+    }
+}
+
+impl Custom {
+    /// Restriction admitting the cells `(i, j)` of a matrix of shape `shape` for which
+    /// `admits(i, j, shape)` holds.
+    pub fn new(
+        admits: impl Fn(usize, usize, (usize, usize)) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            admits: Arc::new(admits),
+        }
+    }
+}
+
+impl std::fmt::Debug for Custom {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Custom").finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for Custom {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.admits, &other.admits)
+    }
+}
+
+impl Window {
+    /// Corridor admitting the columns `rows[i].0..rows[i].1` in row `i`.
+    pub fn new(rows: Vec<(usize, usize)>) -> Self {
+        Self { rows: rows.into() }
+    }
+
+    /// Corridor of shape `shape` covering the cells of `path` computed on a grid `factor` times
+    /// coarser, widened by `radius` cells in every direction.
+    pub fn from_path(
+        path: &[(usize, usize)],
+        shape: (usize, usize),
+        factor: usize,
+        radius: usize,
+    ) -> Self {
+        let bounds = |len: usize| {
+            (0..len.div_ceil(factor) + 1)
+                .map(|c| (c * factor).min(len))
+                .collect::<Vec<usize>>()
+        };
+        Self::from_segments(path, &bounds(shape.0), &bounds(shape.1), radius)
+    }
+
+    /// Corridor covering the cells of `path` computed on segments of the rows and the columns,
+    /// widened by `radius` cells in every direction. Segment `s` spans the rows
+    /// `row_bounds[s]..row_bounds[s + 1]`, and likewise for the columns, and the last bounds
+    /// are the shape of the corridor.
+    pub fn from_segments(
+        path: &[(usize, usize)],
+        row_bounds: &[usize],
+        col_bounds: &[usize],
+        radius: usize,
+    ) -> Self {
+        let shape = (
+            row_bounds.last().copied().unwrap_or(0),
+            col_bounds.last().copied().unwrap_or(0),
+        );
+        let mut rows = vec![(usize::MAX, 0); shape.0];
+        for (ci, cj) in path {
+            let cols = (col_bounds[*cj], col_bounds[cj + 1]);
+            for row in &mut rows[row_bounds[*ci]..row_bounds[ci + 1]] {
+                *row = (row.0.min(cols.0), row.1.max(cols.1));
+            }
+        }
+        let rows = (0..shape.0)
+            .map(|i| {
+                rows[i.saturating_sub(radius)..(i + radius + 1).min(shape.0)]
+                    .iter()
+                    .filter(|(rb, re)| rb < re)
+                    .fold((usize::MAX, 0), |(rb, re), row| {
+                        (rb.min(row.0), re.max(row.1))
+                    })
+            })
+            .map(|(rb, re)| {
+                if rb < re {
+                    (rb.saturating_sub(radius), (re + radius).min(shape.1))
+                } else {
+                    (0, 0)
+                }
+            })
+            .collect();
+        Self::new(rows)
+    }
+
+    /// Corridor of shape `shape` admitting the cells within `radius` columns of the expected path
+    /// through the guide points `guides`, interpolated linearly between them. Unlike anchors,
+    /// the path is not forced through the guide points. The window admits no cell if the shape
+    /// has no cell.
+    pub fn from_guides(guides: &[(usize, usize)], shape: (usize, usize), radius: usize) -> Self {
+        if shape.0 == 0 || shape.1 == 0 {
+            return Self::new(vec![(0, 0); shape.0]);
+        }
+        let mut posts = vec![(0.0, 0.0)];
+        posts.extend(guides.iter().map(|(i, j)| (*i as f64, *j as f64)));
+        posts.push(((shape.0 - 1) as f64, (shape.1 - 1) as f64));
+        assert!(
+            posts
+                .windows(2)
+                .all(|w| w[0].0 <= w[1].0 && w[0].1 <= w[1].1),
+            "Guide points should be sorted in both sequences and lie within the shape"
+        );
+        let expected = |i: usize| {
+            let i = (i as f64).min(posts[posts.len() - 1].0);
+            let k = posts.partition_point(|(pi, _)| *pi < i).max(1);
+            let ((i0, j0), (i1, j1)) = (posts[k - 1], posts[k]);
+            match i1 - i0 {
+                0.0 => j1,
+                di => j0 + (j1 - j0) * (i - i0) / di,
+            }
+        };
+        let rows = (0..shape.0)
+            .map(|i| {
+                let rb = expected(i).round() as usize;
+                let re = expected(i + 1).round() as usize;
+                (rb.saturating_sub(radius), (re + radius + 1).min(shape.1))
+            })
+            .collect();
+        Self::new(rows)
+    }
+
+    /// Range of columns admitted in row `i`.
+    pub fn row(&self, i: usize) -> (usize, usize) {
+        self.rows.get(i).copied().unwrap_or((0, 0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Custom, Landmark, Restriction, Window};
+    use crate::Error;
+
+    #[test]
+    fn iter_contain_restriction() {
+        let shape = (5_usize, 6_usize);
+        let no_rest = Restriction::None;
+        let restriction = Restriction::Band(1);
+        let all_indices = no_rest.iter(shape).collect::<Vec<(usize, usize)>>();
+        let band_indices = restriction.iter(shape).collect::<Vec<(usize, usize)>>();
+        for idx in all_indices.into_iter() {
+            assert_eq!(
+                band_indices.contains(&idx),
+                restriction.contains(idx, shape)
+            );
+            assert_eq!(
+                restriction.row_range(shape, idx.0).contains(&idx.1),
+                restriction.contains(idx, shape)
+            );
+        }
+    }
+
+    #[test]
+    fn window_from_path() {
+        let path = [(0, 0), (1, 1), (1, 2)];
+        let window = Window::from_path(&path, (4, 5), 2, 0);
+        assert_eq!(
+            (0..4).map(|i| window.row(i)).collect::<Vec<_>>(),
+            [(0, 2), (0, 2), (2, 5), (2, 5)]
+        );
+        let window = Window::from_path(&path, (4, 5), 2, 1);
+        assert_eq!(
+            (0..4).map(|i| window.row(i)).collect::<Vec<_>>(),
+            [(0, 3), (0, 5), (0, 5), (1, 5)]
+        );
+        let window = Window::from_segments(&path, &[0, 1, 4], &[0, 2, 3, 5], 0);
+        assert_eq!(
+            (0..4).map(|i| window.row(i)).collect::<Vec<_>>(),
+            [(0, 2), (2, 5), (2, 5), (2, 5)]
+        );
+        let window = Window::from_path(&path, (4, 5), 2, 1);
+        let restriction = Restriction::Window(window);
+        assert!(restriction.contains((3, 4), (4, 5)));
+        assert!(!restriction.contains((3, 0), (4, 5)));
+    }
+
+    #[test]
+    fn window_from_guides() {
+        let window = Window::from_guides(&[(2, 6)], (5, 9), 1);
+        assert_eq!(
+            (0..5).map(|i| window.row(i)).collect::<Vec<_>>(),
+            [(0, 5), (2, 8), (5, 9), (6, 9), (7, 9)]
+        );
+        let window = Window::from_guides(&[], (3, 3), 0);
+        assert_eq!(
+            (0..3).map(|i| window.row(i)).collect::<Vec<_>>(),
+            [(0, 2), (1, 3), (2, 3)]
+        );
+        assert_eq!(Window::from_guides(&[], (0, 0), 2), Window::new(Vec::new()));
+        assert_eq!(Window::from_guides(&[], (2, 0), 2).row(1), (0, 0));
+    }
+
+    #[test]
+    fn landmarks_prune_rows() {
+        let landmarks = [Landmark::NoLaterThan(1, 2), Landmark::NoEarlierThan(3, 4)];
+        let restriction = Restriction::Band(1).with_landmarks(&landmarks, (5, 6));
+        let Restriction::Window(window) = restriction else {
+            panic!("Landmarks should yield a window");
+        };
+        assert_eq!(
+            (0..5).map(|i| window.row(i)).collect::<Vec<_>>(),
+            [(0, 2), (0, 3), (1, 4), (4, 5), (3, 6)]
+        );
+    }
+
+    #[test]
+    fn slope_restriction() {
+        let restriction = Restriction::Slope(0.5, 2.0);
+        let shape = (5, 5);
+        let rows = (0..5)
+            .map(|i| restriction.range(shape, i))
+            .collect::<Vec<_>>();
+        assert_eq!(rows, [(0, 1), (1, 3), (1, 4), (2, 4), (4, 5)]);
+        assert_eq!(restriction.row_range(shape, 2), 1..4);
+        let restriction = Restriction::Slope(1.0, 1.0);
+        assert!(restriction.iter((4, 4)).all(|(i, j)| i == j));
+        assert_eq!(restriction.iter((4, 6)).count(), 0);
+    }
+
+    #[test]
+    fn slanted_band() {
+        // Rows of `slantedBandWindow(row(w), col(w), 4, 10, window.size = 1)` in R.
+        let shape = (4, 10);
+        let rows = (0..4)
+            .map(|i| Restriction::SlantedBand(1).row_range(shape, i))
+            .collect::<Vec<_>>();
+        assert_eq!(rows, [1..3, 3..6, 6..8, 8..10]);
+        let rows = (0..3)
+            .map(|i| Restriction::SlantedBand(2).row_range((3, 7), i))
+            .collect::<Vec<_>>();
+        assert_eq!(rows, [0..4, 2..6, 4..7]);
+        for width in 0..4 {
+            let (slanted, band) = (Restriction::SlantedBand(width), Restriction::Band(width));
+            assert!((0..6).all(|i| slanted.row_range((6, 6), i) == band.row_range((6, 6), i)));
+        }
+    }
+
+    #[test]
+    fn custom_predicate() {
+        let custom = Custom::new(|i, j, (n, _)| j >= i && j <= i + n);
+        let restriction = Restriction::Custom(custom.clone());
+        let rows = (0..3)
+            .map(|i| restriction.row_range((3, 5), i))
+            .collect::<Vec<_>>();
+        assert_eq!(rows, [0..4, 1..5, 2..5]);
+        assert!(!restriction.contains((2, 1), (3, 5)));
+        assert_eq!(restriction, Restriction::Custom(custom));
+        let empty = Restriction::Custom(Custom::new(|_, _, _| false));
+        assert_eq!(empty.iter((3, 5)).count(), 0);
+    }
+
+    #[test]
+    fn itakura_parallelogram() {
+        let shape = (7, 7);
+        let restriction = Restriction::Itakura(2.0);
+        let rows = (0..7)
+            .map(|i| restriction.row_range(shape, i))
+            .collect::<Vec<_>>();
+        assert_eq!(rows, [0..1, 1..3, 1..5, 2..5, 2..6, 4..6, 6..7]);
+        assert!((0..7).all(|i| restriction.contains((i, i), shape)));
+    }
+
+    #[test]
+    fn itakura_slope_below_one() {
+        assert_eq!(Restriction::itakura(2.0), Ok(Restriction::Itakura(2.0)));
+        assert_eq!(Restriction::itakura(1.0), Err(Error::InvalidSlope));
+        assert_eq!(Restriction::itakura(f64::NAN), Err(Error::InvalidSlope));
+        assert!(Restriction::Itakura(0.5).row_range((7, 7), 3).is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+use std::{cmp::Ordering, collections::VecDeque, ops::Add};
+
+use super::dynamic_programming::Element;
+use crate::Cost;
+
+/// Run-length encoding of `series` as `(value, length)` pairs.
+pub fn run_length_encode<T: PartialEq + Clone>(series: &[T]) -> Vec<(T, usize)> {
+    let mut runs: Vec<(T, usize)> = Vec::new();
+    for value in series {
+        match runs.last_mut() {
+            Some((last, length)) if last == value => *length += 1,
+            _ => runs.push((value.clone(), 1)),
+        }
+    }
+    runs
+}
+
+/// Warped distance between the run-length encoded sequences `a` and `b` using the distance
+/// closure `distance`, which should be non-negative.
+///
+/// Every pair of runs forms a block of the matrix with a constant local cost, of which only the
+/// last row and the last column are computed.
+pub fn run_length_distance<T, D>(
+    a: &[(T, usize)],
+    b: &[(T, usize)],
+    distance: impl Fn(&T, &T) -> D,
+) -> D
+where
+    D: Clone + Default + PartialOrd + Add<D, Output = D> + Cost,
+{
+    assert!(
+        a.iter().chain(b).all(|(_, length)| *length > 0),
+        "Runs should not be empty"
+    );
+    assert!(
+        !a.is_empty() && !b.is_empty(),
+        "Sequences should not be empty"
+    );
+    let mut rows: Vec<Vec<Element<D>>> = b.iter().map(|(_, m)| vec![Element::Inf; *m]).collect();
+    for (p, (x, n)) in a.iter().enumerate() {
+        let mut column = vec![Element::Inf; *n];
+        let mut corner = match p {
+            0 => Element::Value(D::default()),
+            _ => Element::Inf,
+        };
+        for (q, (y, m)) in b.iter().enumerate() {
+            let cost = distance(x, y);
+            let top = std::iter::once(corner.clone())
+                .chain(rows[q].iter().cloned())
+                .collect::<Vec<Element<D>>>();
+            let left = std::iter::once(corner)
+                .chain(column.iter().cloned())
+                .collect::<Vec<Element<D>>>();
+            corner = rows[q][m - 1].clone();
+            let row = edge(&top, *n, *m, &cost)
+                .into_iter()
+                .zip(cross(&left, *n, *m, &cost))
+                .map(|(e1, e2)| min(e1, e2))
+                .collect();
+            column = edge(&left, *m, *n, &cost)
+                .into_iter()
+                .zip(cross(&top, *m, *n, &cost))
+                .map(|(e1, e2)| min(e1, e2))
+                .collect();
+            rows[q] = row;
+        }
+    }
+    match rows.last().and_then(|row| row.last()) {
+        Some(Element::Value(v)) => v.clone(),
+        _ => panic!("Infinit distance"),
+    }
+}
+
+/// `out[k] = min(ext[t] + cost * max(span, k + 1 - t))` for `t <= k + 1`, i.e. the cost of the
+/// cells along the far edge of a block entered from the boundary `ext` facing it.
+fn edge<D>(ext: &[Element<D>], span: usize, len: usize, cost: &D) -> Vec<Element<D>>
+where
+    D: Clone + PartialOrd + Add<D, Output = D> + Cost,
+{
+    let times = |k: usize| Element::Value(cost.weighted(k as f64));
+    let mut window: VecDeque<usize> = VecDeque::new();
+    let mut far = Element::Inf;
+    (0..len)
+        .map(|k| {
+            if k == 0 {
+                window.push_back(0);
+            }
+            while window
+                .back()
+                .is_some_and(|t| ext[k + 1].partial_cmp(&ext[*t]) != Some(Ordering::Greater))
+            {
+                window.pop_back();
+            }
+            window.push_back(k + 1);
+            while window.front().is_some_and(|t| t + span < k + 1) {
+                window.pop_front();
+            }
+            if k >= span {
+                far = min(
+                    far.clone() + times(1),
+                    ext[k - span].clone() + times(span + 1),
+                );
+            }
+            min(ext[window[0]].clone() + times(span), far.clone())
+        })
+        .collect()
+}
+
+/// `out[k] = min(ext[u] + cost * max(span - u, k + 1))` for `u <= span`, i.e. the cost of the
+/// cells along the far edge of a block entered from the boundary `ext` adjacent to it.
+fn cross<D>(ext: &[Element<D>], span: usize, len: usize, cost: &D) -> Vec<Element<D>>
+where
+    D: Clone + PartialOrd + Add<D, Output = D> + Cost,
+{
+    let times = |k: usize| Element::Value(cost.weighted(k as f64));
+    let mut suffix = ext.to_vec();
+    for u in (0..span).rev() {
+        suffix[u] = min(suffix[u].clone(), suffix[u + 1].clone());
+    }
+    let mut prefix = vec![Element::Inf; span + 1];
+    for u in 0..span {
+        prefix[u + 1] = min(prefix[u].clone(), ext[u].clone() + times(span - u));
+    }
+    (0..len)
+        .map(|k| {
+            let split = span.saturating_sub(k + 1);
+            min(suffix[split].clone() + times(k + 1), prefix[split].clone())
+        })
+        .collect()
+}
+
+fn min<D: PartialOrd>(e1: Element<D>, e2: Element<D>) -> Element<D> {
+    if e2 < e1 {
+        e2
+    } else {
+        e1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run_length_distance, run_length_encode};
+    use crate::{Algorithm, DynamicTimeWarping};
+
+    #[test]
+    fn encode() {
+        assert_eq!(
+            run_length_encode(&[1, 1, 2, 2, 2, 1]),
+            [(1, 2), (2, 3), (1, 1)]
+        );
+        assert_eq!(run_length_encode::<i32>(&[]), []);
+    }
+
+    #[test]
+    fn run_length_matches_dense() {
+        let a = [0.0, 0.0, 0.0, 3.0, 3.0, 1.0, 1.0, 1.0, 1.0, 5.0, 2.0, 2.0];
+        let b = [1.0, 1.0, 4.0, 4.0, 4.0, 4.0, 0.0, 2.0, 2.0, 2.0];
+        for (a, b) in [
+            (&a[..], &b[..]),
+            (&b[..], &a[..]),
+            (&a[..2], &b[..]),
+            (&a, &a),
+        ] {
+            let dense = DynamicTimeWarping::between(a, b).distance();
+            let rle = run_length_distance(&run_length_encode(a), &run_length_encode(b), |x, y| {
+                f64::abs(x - y)
+            });
+            assert_eq!(rle, dense);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::ops::{Add, Sub};
+
+use super::dynamic_programming::{DynamicTimeWarping, Parameters};
+use crate::{Algorithm, Cost};
+
+/// Division of two sequences into corresponding segments aligned independently.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Segmentation {
+    /// As many segments as needed for the longer sequence to be split into segments of at most
+    /// the given length, both sequences being split into segments of near-equal lengths.
+    Length(usize),
+    /// Cells `(i, j)` starting a new segment at the index `i` of the first sequence and the
+    /// index `j` of the second one, strictly increasing in both sequences.
+    Splits(Vec<(usize, usize)>),
+}
+
+impl Segmentation {
+    /// Starting cells of the segments of sequences of lengths `shape`, the first one included.
+    fn starts(&self, shape: (usize, usize)) -> Vec<(usize, usize)> {
+        match self {
+            Segmentation::Length(length) => {
+                assert!(*length > 0, "Segment length should be positive");
+                let count = shape.0.max(shape.1).div_ceil(*length);
+                assert!(
+                    count <= shape.0.min(shape.1),
+                    "Sequences should be long enough for every segment to be non-empty"
+                );
+                (0..count)
+                    .map(|s| (s * shape.0 / count, s * shape.1 / count))
+                    .collect()
+            }
+            Segmentation::Splits(splits) => {
+                let mut starts = vec![(0, 0)];
+                starts.extend_from_slice(splits);
+                assert!(
+                    starts
+                        .windows(2)
+                        .all(|w| w[0].0 < w[1].0 && w[0].1 < w[1].1)
+                        && starts
+                            .last()
+                            .is_some_and(|(i, j)| *i < shape.0 && *j < shape.1),
+                    "Splits should be strictly increasing in both sequences and lie within them"
+                );
+                starts
+            }
+        }
+    }
+}
+
+/// Alignment of two sequences decomposed into independent alignments of corresponding segments,
+/// e.g. of very long recordings whose global alignment is both slow and meaningless.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Segmented<D> {
+    segments: Vec<DynamicTimeWarping<D>>,
+    offsets: Vec<(usize, usize)>,
+}
+
+impl<D> Segmented<D>
+where
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Sub<D, Output = D> + Cost,
+{
+    /// Dynamic time warping between the segments `segmentation` of `a` and `b`, using the
+    /// distance closure `distance`.
+    pub fn with_closure<T>(
+        a: &[T],
+        b: &[T],
+        segmentation: &Segmentation,
+        distance: impl Fn(&T, &T) -> D,
+    ) -> Self {
+        Self::with_closure_and_param(a, b, segmentation, distance, Parameters::default())
+    }
+
+    /// Dynamic time warping between the segments `segmentation` of `a` and `b`, using the
+    /// distance closure `distance` and the hyper-parameters `hyper_parameters` within every
+    /// segment.
+    pub fn with_closure_and_param<T>(
+        a: &[T],
+        b: &[T],
+        segmentation: &Segmentation,
+        distance: impl Fn(&T, &T) -> D,
+        hyper_parameters: impl Into<Parameters>,
+    ) -> Self {
+        let hyper_parameters = hyper_parameters.into();
+        let offsets = segmentation.starts((a.len(), b.len()));
+        let mut ends = offsets[1..].to_vec();
+        ends.push((a.len(), b.len()));
+        let segments = offsets
+            .iter()
+            .zip(&ends)
+            .map(|((si, sj), (ei, ej))| {
+                DynamicTimeWarping::with_closure_and_param(
+                    &a[*si..*ei],
+                    &b[*sj..*ej],
+                    &distance,
+                    hyper_parameters.clone(),
+                )
+            })
+            .collect();
+        Self { segments, offsets }
+    }
+
+    /// Alignments of the segments.
+    pub fn segments(&self) -> &[DynamicTimeWarping<D>] {
+        &self.segments
+    }
+
+    /// Starting cells of the segments.
+    pub fn offsets(&self) -> &[(usize, usize)] {
+        &self.offsets
+    }
+
+    /// Warped distances between the segments.
+    pub fn distances(&self) -> Vec<D> {
+        self.segments.iter().map(|s| s.distance()).collect()
+    }
+
+    /// Sum of the warped distances between the segments.
+    pub fn distance(&self) -> D {
+        self.segments
+            .iter()
+            .fold(D::default(), |acc, segment| acc + segment.distance())
+    }
+
+    /// Warped paths of the segments stitched into a path between the sequences.
+    pub fn path(&self) -> Vec<(usize, usize)> {
+        self.segments
+            .iter()
+            .zip(&self.offsets)
+            .flat_map(|(segment, (oi, oj))| {
+                segment
+                    .path()
+                    .into_iter()
+                    .map(move |(i, j)| (i + oi, j + oj))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Segmentation, Segmented};
+    use crate::{Algorithm, DynamicTimeWarping};
+
+    #[test]
+    fn segments_are_aligned_independently() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0, 0.0, 4.0];
+        let b = [2.0, 0.0, 8.0, 7.0, 2.0, 0.0, 5.0, 4.0];
+        let abs = |x: &f64, y: &f64| f64::abs(x - y);
+        let split = Segmented::with_closure(&a, &b, &Segmentation::Splits(vec![(5, 5)]), abs);
+        let head = DynamicTimeWarping::between(&a[..5], &b[..5]);
+        let tail = DynamicTimeWarping::between(&a[5..], &b[5..]);
+        assert_eq!(split.distances(), vec![head.distance(), tail.distance()]);
+        assert_eq!(split.distance(), head.distance() + tail.distance());
+        let path = split.path();
+        assert_eq!(path[head.path().len()], (5, 5));
+        assert_eq!(path.last(), Some(&(6, 7)));
+        assert!(path
+            .windows(2)
+            .all(|w| w[0].0 <= w[1].0 && w[0].1 <= w[1].1));
+        let fixed = Segmented::with_closure(&a, &b, &Segmentation::Length(3), abs);
+        assert_eq!(fixed.offsets(), &[(0, 0), (2, 2), (4, 5)]);
+        let whole = Segmented::with_closure(&a, &b, &Segmentation::Length(8), abs);
+        assert_eq!(
+            whole.distance(),
+            DynamicTimeWarping::between(&a, &b).distance()
+        );
+    }
+}
@@ -0,0 +1,199 @@
+/// Kernel mapping a non-negative distance to a similarity score in `[0, 1]`.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Similarity {
+    /// `1 / (1 + d)`.
+    #[default]
+    Reciprocal,
+    /// `exp(-d / scale)`.
+    Exponential(f64),
+}
+
+impl Similarity {
+    /// Similarity score of the distance `distance`.
+    pub fn score(&self, distance: f64) -> f64 {
+        match self {
+            Similarity::Reciprocal => 1.0 / (1.0 + distance),
+            #[cfg(not(feature = "deterministic"))]
+            Similarity::Exponential(scale) => (-distance / scale).exp(),
+            #[cfg(feature = "deterministic")]
+            Similarity::Exponential(scale) => portable_exp(-distance / scale),
+        }
+    }
+}
+
+/// `e^x` computed with the basic IEEE 754 operations only, unlike the platform's `exp`, such
+/// that it yields the same bits on every platform.
+#[cfg(any(feature = "deterministic", test))]
+pub(crate) fn portable_exp(x: f64) -> f64 {
+    const LN2_HI: f64 = 6.931_471_803_691_238e-1;
+    const LN2_LO: f64 = 1.908_214_929_270_587_7e-10;
+    if x.is_nan() {
+        return x;
+    }
+    if x > 710.0 {
+        return f64::INFINITY;
+    }
+    if x < -746.0 {
+        return 0.0;
+    }
+    let k = (x * std::f64::consts::LOG2_E).round();
+    let r = (x - k * LN2_HI) - k * LN2_LO;
+    let series = (1..=13).rev().fold(1.0, |acc, n| 1.0 + acc * r / n as f64);
+    let half = (k / 2.0).trunc();
+    series * power_of_two(half as i32) * power_of_two((k - half) as i32)
+}
+
+/// Natural logarithm of `x` computed with the basic IEEE 754 operations only, like
+/// [`portable_exp`].
+#[cfg(any(feature = "deterministic", test))]
+pub(crate) fn portable_ln(x: f64) -> f64 {
+    const LN2_HI: f64 = 6.931_471_803_691_238e-1;
+    const LN2_LO: f64 = 1.908_214_929_270_587_7e-10;
+    if x.is_nan() || x < 0.0 {
+        return f64::NAN;
+    }
+    if x == 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if x == f64::INFINITY {
+        return x;
+    }
+    // `x = m 2^e` with `m` in `[sqrt(1/2), sqrt(2))`, subnormals being scaled up first.
+    let (x, mut e) = match x < f64::MIN_POSITIVE {
+        true => (x * power_of_two(54), -54),
+        false => (x, 0),
+    };
+    let bits = x.to_bits();
+    e += ((bits >> 52) & 0x7ff) as i32 - 1023;
+    let mut m = f64::from_bits((bits & ((1 << 52) - 1)) | (1023 << 52));
+    if m > std::f64::consts::SQRT_2 {
+        m /= 2.0;
+        e += 1;
+    }
+    // `ln(m) = 2 atanh(s)` with `s = (m - 1) / (m + 1)`, `|s| < 0.172`.
+    let s = (m - 1.0) / (m + 1.0);
+    let s2 = s * s;
+    let series = (0..=13)
+        .rev()
+        .fold(0.0, |acc, k| 1.0 / (2 * k + 1) as f64 + acc * s2);
+    let e = e as f64;
+    e * LN2_HI + (e * LN2_LO + 2.0 * s * series)
+}
+
+/// `ln(1 + x)`, accurate for small `x`, computed like [`portable_ln`].
+#[cfg(any(feature = "deterministic", test))]
+pub(crate) fn portable_ln_1p(x: f64) -> f64 {
+    let u = 1.0 + x;
+    if u == 1.0 || u == f64::INFINITY {
+        return if u == 1.0 { x } else { u };
+    }
+    // The rounding error of `1 + x` is compensated by the ratio of `x` to `u - 1`.
+    portable_ln(u) * (x / (u - 1.0))
+}
+
+/// `x^y` computed as `e^(y ln x)` like [`portable_exp`], for a base `x` not less than zero.
+#[cfg(any(feature = "deterministic", test))]
+pub(crate) fn portable_powf(x: f64, y: f64) -> f64 {
+    match (x, y) {
+        (_, 0.0) => 1.0,
+        (_, 1.0) => x,
+        (0.0, _) if y > 0.0 => 0.0,
+        (0.0, _) => f64::INFINITY,
+        _ => portable_exp(y * portable_ln(x)),
+    }
+}
+
+/// Arccosine of `x` in `[0, pi]` computed with the basic IEEE 754 operations only, like
+/// [`portable_exp`].
+#[cfg(any(feature = "deterministic", test))]
+pub(crate) fn portable_acos(x: f64) -> f64 {
+    use std::f64::consts::{FRAC_PI_2, PI};
+    // `asin(z) = atan(z / sqrt(1 - z^2))` for `|z| <= 1/2`, with the angle halved twice by
+    // `atan(t) = 2 atan(t / (1 + sqrt(1 + t^2)))` to `|t| < 0.15`.
+    let asin = |z: f64| {
+        let t = z / (1.0 - z * z).sqrt();
+        let t = (0..2).fold(t, |t, _| t / (1.0 + (1.0 + t * t).sqrt()));
+        let series = (0..=15).rev().fold(0.0, |acc, k| {
+            let sign = if k % 2 == 0 { 1.0 } else { -1.0 };
+            sign / (2 * k + 1) as f64 + acc * t * t
+        });
+        4.0 * t * series
+    };
+    match x {
+        _ if x.is_nan() || !(-1.0..=1.0).contains(&x) => f64::NAN,
+        _ if x > 0.5 => 2.0 * asin(((1.0 - x) / 2.0).sqrt()),
+        _ if x < -0.5 => PI - 2.0 * asin(((1.0 + x) / 2.0).sqrt()),
+        _ => FRAC_PI_2 - asin(x),
+    }
+}
+
+/// `2^k` for `k` in the range of the normal exponents.
+#[cfg(any(feature = "deterministic", test))]
+fn power_of_two(k: i32) -> f64 {
+    f64::from_bits(((k + 1023) as u64) << 52)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        portable_acos, portable_exp, portable_ln, portable_ln_1p, portable_powf, Similarity,
+    };
+
+    #[test]
+    fn similarity_bounds() {
+        for kernel in [Similarity::Reciprocal, Similarity::Exponential(2.0)] {
+            assert_eq!(kernel.score(0.0), 1.0);
+            assert!(kernel.score(1.0) < 1.0);
+            assert!(kernel.score(1.0) > kernel.score(10.0));
+            assert!(kernel.score(f64::INFINITY) == 0.0);
+        }
+        assert_eq!(Similarity::Reciprocal.score(1.0), 0.5);
+        assert_eq!(Similarity::Exponential(2.0).score(2.0), f64::exp(-1.0));
+    }
+
+    #[test]
+    fn portable_exp_matches_exp() {
+        for x in [-700.0, -20.5, -1.0, -1e-3, 0.0, 0.5, 1.0, 3.7, 300.0, 709.0] {
+            let (portable, std) = (portable_exp(x), f64::exp(x));
+            assert!(f64::abs(portable - std) <= 4.0 * f64::EPSILON * std, "{x}");
+        }
+        assert_eq!(portable_exp(-800.0), 0.0);
+        assert_eq!(portable_exp(800.0), f64::INFINITY);
+    }
+
+    #[test]
+    fn portable_functions_match_std() {
+        let close =
+            |portable: f64, std: f64| f64::abs(portable - std) <= 4.0 * f64::EPSILON * std.abs();
+        for x in [
+            1e-310,
+            1e-5,
+            0.3,
+            0.75,
+            1.0,
+            1.5,
+            2.0,
+            10.0,
+            1e100,
+            f64::MAX,
+        ] {
+            assert!(close(portable_ln(x), x.ln()), "{x}");
+        }
+        assert_eq!(portable_ln(0.0), f64::NEG_INFINITY);
+        assert!(portable_ln(-1.0).is_nan());
+        for x in [-0.5, -1e-9, 1e-20, 1e-9, 1e-3, 0.5, 3.0, 1e10] {
+            assert!(close(portable_ln_1p(x), x.ln_1p()), "{x}");
+        }
+        for (x, y) in [(2.0, 0.5), (0.3, 2.5), (7.0, -1.5), (1e-3, 3.0), (0.0, 2.0)] {
+            assert!(close(portable_powf(x, y), x.powf(y)) || x.powf(y) == portable_powf(x, y));
+        }
+        assert_eq!(portable_powf(0.0, -1.0), f64::INFINITY);
+        for x in [-1.0, -0.9, -0.5, -1e-3, 0.0, 0.2, 0.5, 0.99, 1.0] {
+            assert!(
+                f64::abs(portable_acos(x) - x.acos()) <= 4.0 * f64::EPSILON,
+                "{x}"
+            );
+        }
+        assert!(portable_acos(1.5).is_nan());
+    }
+}
@@ -0,0 +1,179 @@
+#[cfg(feature = "deterministic")]
+use super::similarity::{portable_exp as exp, portable_ln as ln};
+use super::utils::Matrix;
+
+/// Soft dynamic time warping (Cuturi and Blondel, "Soft-DTW: a Differentiable Loss Function for
+/// Time-Series"), where the minimum of the recursion is replaced by the soft minimum
+/// `-gamma * ln(sum(exp(-x / gamma)))`, with the squared differences as local costs.
+///
+/// The distance is differentiable with respect to the series, e.g. to be used as a loss, and
+/// tends to the distance of the dynamic time warping as `gamma` tends to zero.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SoftDtw {
+    gamma: f64,
+    a: Vec<f64>,
+    b: Vec<f64>,
+    accumulated: Matrix<f64>,
+}
+
+impl SoftDtw {
+    /// Soft dynamic time warping between `a` and `b` with the smoothing `gamma`, greater than
+    /// zero.
+    pub fn new(a: &[f64], b: &[f64], gamma: f64) -> Self {
+        assert!(gamma > 0.0, "Gamma should be greater than zero");
+        assert!(
+            !a.is_empty() && !b.is_empty(),
+            "Sequences should not be empty"
+        );
+        let (n, m) = (a.len(), b.len());
+        // Padded with a border of infinite costs around the origin, and a row and a column past
+        // the ends used by the backward recursion.
+        let mut accumulated = Matrix::fill(f64::INFINITY, n + 2, m + 2);
+        accumulated[(0, 0)] = 0.0;
+        for i in 1..=n {
+            for j in 1..=m {
+                let soft_min = soft_min(
+                    [
+                        accumulated[(i - 1, j - 1)],
+                        accumulated[(i - 1, j)],
+                        accumulated[(i, j - 1)],
+                    ],
+                    gamma,
+                );
+                accumulated[(i, j)] = (a[i - 1] - b[j - 1]).powi(2) + soft_min;
+            }
+        }
+        Self {
+            gamma,
+            a: a.to_vec(),
+            b: b.to_vec(),
+            accumulated,
+        }
+    }
+
+    /// Soft warped distance between `a` and `b`.
+    pub fn distance(&self) -> f64 {
+        self.accumulated[(self.a.len(), self.b.len())]
+    }
+
+    /// Expected alignment between `a` and `b`, i.e. the gradient of the distance with respect to
+    /// the local costs, whose cell `(i, j)` weighs the match of `a[i]` with `b[j]`.
+    pub fn alignment(&self) -> Matrix<f64> {
+        let (n, m) = (self.a.len(), self.b.len());
+        let mut r = self.accumulated.clone();
+        for i in 1..=n {
+            r[(i, m + 1)] = f64::NEG_INFINITY;
+        }
+        for j in 1..=m {
+            r[(n + 1, j)] = f64::NEG_INFINITY;
+        }
+        r[(n + 1, m + 1)] = r[(n, m)];
+        let cost = |i: usize, j: usize| {
+            if i <= n && j <= m {
+                (self.a[i - 1] - self.b[j - 1]).powi(2)
+            } else {
+                0.0
+            }
+        };
+        let mut e = Matrix::fill(0.0, n + 2, m + 2);
+        e[(n + 1, m + 1)] = 1.0;
+        for i in (1..=n).rev() {
+            for j in (1..=m).rev() {
+                let weight = |(k, l): (usize, usize)| {
+                    e[(k, l)] * exp((r[(k, l)] - r[(i, j)] - cost(k, l)) / self.gamma)
+                };
+                e[(i, j)] = weight((i + 1, j)) + weight((i, j + 1)) + weight((i + 1, j + 1));
+            }
+        }
+        Matrix::from_iter(
+            (1..=n)
+                .flat_map(|i| (1..=m).map(move |j| (i, j)))
+                .map(|idx| e[idx]),
+            n,
+            m,
+        )
+    }
+
+    /// Gradient of the distance with respect to the elements of `a`.
+    pub fn gradient(&self) -> Vec<f64> {
+        let alignment = self.alignment();
+        self.a
+            .iter()
+            .zip(alignment.rows())
+            .map(|(x, weights)| {
+                weights
+                    .iter()
+                    .zip(&self.b)
+                    .map(|(w, y)| 2.0 * w * (x - y))
+                    .sum()
+            })
+            .collect()
+    }
+}
+
+/// `-gamma * ln(sum(exp(-x / gamma)))` of `values`, shifted by their minimum to avoid overflows.
+fn soft_min(values: [f64; 3], gamma: f64) -> f64 {
+    let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+    if min == f64::INFINITY {
+        return min;
+    }
+    let sum = values.iter().map(|v| exp(-(v - min) / gamma)).sum::<f64>();
+    min - gamma * ln(sum)
+}
+
+#[cfg(not(feature = "deterministic"))]
+fn exp(x: f64) -> f64 {
+    x.exp()
+}
+
+#[cfg(not(feature = "deterministic"))]
+fn ln(x: f64) -> f64 {
+    x.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SoftDtw;
+    use crate::{Algorithm, DynamicTimeWarping};
+
+    #[test]
+    fn soft_dtw_tends_to_dtw() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let hard = DynamicTimeWarping::with_closure(&a, &b, |x: &f64, y: &f64| (x - y).powi(2));
+        let soft = SoftDtw::new(&a, &b, 1e-3);
+        assert!(f64::abs(soft.distance() - hard.distance()) < 1e-2);
+        assert!(SoftDtw::new(&a, &b, 1.0).distance() < soft.distance());
+        let alignment = soft.alignment();
+        for (i, j) in hard.path() {
+            assert!(f64::abs(alignment[(i, j)] - 1.0) < 1e-6);
+        }
+    }
+
+    #[test]
+    fn soft_dtw_gradient() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let gradient = SoftDtw::new(&a, &b, 0.5).gradient();
+        let h = 1e-6;
+        for i in 0..a.len() {
+            let (mut plus, mut minus) = (a, a);
+            plus[i] += h;
+            minus[i] -= h;
+            let numeric = (SoftDtw::new(&plus, &b, 0.5).distance()
+                - SoftDtw::new(&minus, &b, 0.5).distance())
+                / (2.0 * h);
+            assert!(f64::abs(gradient[i] - numeric) < 1e-4, "{i}");
+        }
+    }
+
+    #[cfg(feature = "deterministic")]
+    #[test]
+    fn portable_soft_dtw() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let soft = SoftDtw::new(&a, &b, 0.5);
+        assert_eq!(soft.distance(), 16.936535931503663);
+        assert_eq!(soft.gradient()[0], 0.23840558894679628);
+    }
+}
@@ -0,0 +1,349 @@
+use std::ops::Add;
+
+use super::{dynamic_programming::Element, restriction::Restriction, utils::Matrix};
+use crate::Cost;
+
+/// Local continuity constraint of the dynamic programming recursion.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum StepPattern {
+    /// Diagonal, vertical and horizontal steps with equal weights.
+    #[default]
+    Symmetric,
+    /// Diagonal, vertical and horizontal steps with the diagonal step weighted twice, such that
+    /// every path has the total weight `n + m - 1` and the distance can be normalized by it.
+    Symmetric2,
+    /// Every index of `a` is matched exactly once, while the index of `b` advances by zero, one
+    /// or two. The resulting path is a function from the indices of `a` to the indices of `b`.
+    Asymmetric,
+    /// Local continuity constraint of Rabiner and Juang, with every visited cell weighted equally.
+    Rabiner(LocalContinuity),
+    /// Local continuity constraint of Rabiner and Juang with a slope weighting, optionally
+    /// smoothed, as defined by `rabinerJuangStepPattern` of the R dtw package.
+    RabinerJuang(LocalContinuity, SlopeWeighting, bool),
+}
+
+/// Local continuity constraint types of Rabiner and Juang, "Fundamentals of Speech Recognition",
+/// table 4.5, numbered like the types 1 to 7 of `rabinerJuangStepPattern` of the R dtw package.
+/// Each type is a set of moves, given as the successive steps `(di, dj)` of the path.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum LocalContinuity {
+    /// `(1, 0)`, `(1, 1)` and `(0, 1)`.
+    TypeI,
+    /// `(1, 1)(1, 0)`, `(1, 1)` and `(1, 1)(0, 1)`.
+    TypeII,
+    /// `(2, 1)`, `(1, 1)` and `(1, 2)`.
+    TypeIII,
+    /// `(1, 1)(1, 0)`, `(1, 2)(1, 0)`, `(1, 1)` and `(1, 2)`.
+    TypeIV,
+    /// `(1, 1)` followed by up to two `(1, 0)` or up to two `(0, 1)` steps.
+    TypeV,
+    /// `(1, 1)(1, 1)(1, 0)`, `(1, 1)` and `(1, 1)(1, 1)(0, 1)`.
+    TypeVI,
+    /// `(1, 1)`, `(1, 2)` and `(1, 3)`, each followed by up to two `(1, 0)` steps.
+    TypeVII,
+}
+
+/// Weight of each step `(di, dj)` of a move in the Rabiner-Juang step patterns.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SlopeWeighting {
+    /// `min(di, dj)`.
+    A,
+    /// `max(di, dj)`.
+    B,
+    /// `di`.
+    C,
+    /// `di + dj`.
+    D,
+}
+
+/// Predecessor chosen among the ones of equal accumulated cost when recovering the path of a
+/// single-step pattern.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum TieBreak {
+    /// Prefer the diagonal step, matching one element of `a` with one of `b`.
+    #[default]
+    Diagonal,
+    /// Prefer the vertical and horizontal steps, matching an element with several ones.
+    Expansion,
+}
+
+impl TieBreak {
+    /// Indices of `predecessors` in the order of preference.
+    pub(crate) fn order(&self, predecessors: &[(usize, usize)]) -> Vec<usize> {
+        let mut order = (0..predecessors.len()).collect::<Vec<usize>>();
+        let diagonal = |p: &usize| predecessors[*p].0 == predecessors[*p].1;
+        match self {
+            TieBreak::Diagonal => order.sort_by_key(|p| !diagonal(p)),
+            TieBreak::Expansion => order.sort_by_key(diagonal),
+        }
+        order
+    }
+}
+
+/// Move of a step pattern, relative to the cell `(i, j)` it reaches.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Move {
+    /// Offset of the cell the move starts from.
+    pub origin: (usize, usize),
+    /// Offsets and weights of the cells visited by the move, ending with `(0, 0)`.
+    pub cells: Vec<((usize, usize), f64)>,
+}
+
+impl StepPattern {
+    /// Offsets `(di, dj)` of the cells from which `(i, j)` can be reached in a single step, in
+    /// the order of preference when costs are tied.
+    pub(crate) fn predecessors(&self) -> &'static [(usize, usize)] {
+        match self {
+            StepPattern::Symmetric => &[(1, 1), (1, 0), (0, 1)],
+            StepPattern::Asymmetric => &[(1, 1), (1, 0), (1, 2)],
+            StepPattern::Symmetric2 | StepPattern::Rabiner(_) | StepPattern::RabinerJuang(..) => {
+                &[]
+            }
+        }
+    }
+
+    /// Whether every move of the pattern is a single step with a unit weight, such that the path
+    /// can be recovered from the accumulated costs alone.
+    pub(crate) fn is_single_step(&self) -> bool {
+        !self.predecessors().is_empty()
+    }
+
+    /// Moves of the pattern.
+    pub(crate) fn moves(&self) -> Vec<Move> {
+        let (local, weighting, smoothed) = match self {
+            StepPattern::Rabiner(local) => (Some(*local), None, false),
+            StepPattern::RabinerJuang(local, weighting, smoothed) => {
+                (Some(*local), Some(*weighting), *smoothed)
+            }
+            StepPattern::Symmetric2 => (None, Some(SlopeWeighting::D), false),
+            _ => (None, None, false),
+        };
+        let single_steps = match self {
+            StepPattern::Symmetric2 => StepPattern::Symmetric.predecessors(),
+            _ => self.predecessors(),
+        };
+        let steps = match local {
+            Some(local) => local.steps(),
+            None => single_steps.iter().map(|step| vec![*step]).collect(),
+        };
+        steps
+            .into_iter()
+            .map(|steps| {
+                let weights = steps
+                    .iter()
+                    .map(|(di, dj)| match weighting {
+                        None => 1.0,
+                        Some(SlopeWeighting::A) => (*di).min(*dj) as f64,
+                        Some(SlopeWeighting::B) => (*di).max(*dj) as f64,
+                        Some(SlopeWeighting::C) => *di as f64,
+                        Some(SlopeWeighting::D) => (di + dj) as f64,
+                    })
+                    .collect::<Vec<f64>>();
+                let mean = weights.iter().sum::<f64>() / weights.len() as f64;
+                let mut cell = steps
+                    .iter()
+                    .fold((0, 0), |(i, j), (di, dj)| (i + di, j + dj));
+                let origin = cell;
+                let cells = steps
+                    .iter()
+                    .zip(weights)
+                    .map(|((di, dj), weight)| {
+                        cell = (cell.0 - di, cell.1 - dj);
+                        (cell, if smoothed { mean } else { weight })
+                    })
+                    .collect();
+                Move { origin, cells }
+            })
+            .collect()
+    }
+}
+
+impl LocalContinuity {
+    /// Successive steps `(di, dj)` of each move.
+    fn steps(&self) -> Vec<Vec<(usize, usize)>> {
+        match self {
+            LocalContinuity::TypeI => vec![vec![(1, 0)], vec![(1, 1)], vec![(0, 1)]],
+            LocalContinuity::TypeII => {
+                vec![vec![(1, 1), (1, 0)], vec![(1, 1)], vec![(1, 1), (0, 1)]]
+            }
+            LocalContinuity::TypeIII => vec![vec![(2, 1)], vec![(1, 1)], vec![(1, 2)]],
+            LocalContinuity::TypeIV => vec![
+                vec![(1, 1), (1, 0)],
+                vec![(1, 2), (1, 0)],
+                vec![(1, 1)],
+                vec![(1, 2)],
+            ],
+            LocalContinuity::TypeV => vec![
+                vec![(1, 1), (1, 0), (1, 0)],
+                vec![(1, 1), (1, 0)],
+                vec![(1, 1)],
+                vec![(1, 1), (0, 1)],
+                vec![(1, 1), (0, 1), (0, 1)],
+            ],
+            LocalContinuity::TypeVI => vec![
+                vec![(1, 1), (1, 1), (1, 0)],
+                vec![(1, 1)],
+                vec![(1, 1), (1, 1), (0, 1)],
+            ],
+            LocalContinuity::TypeVII => (0..3)
+                .flat_map(|vertical| {
+                    (1..=3).map(move |dj| {
+                        std::iter::once((1, dj))
+                            .chain(std::iter::repeat_n((1, 0), vertical))
+                            .collect()
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Accumulate the costs using the moves of `step_pattern`, recording the move chosen at each
+/// cell in `trace`.
+pub(crate) fn optimize_moves<D: Clone + PartialOrd + Add<D, Output = D> + Cost>(
+    matrix: &mut Matrix<Element<D>>,
+    trace: &mut Matrix<Option<u8>>,
+    restriction: &Restriction,
+    step_pattern: StepPattern,
+    distance: impl Fn(usize, usize) -> D,
+) {
+    let moves = step_pattern.moves();
+    restriction.iter(matrix.shape()).for_each(|(i, j)| {
+        if (i, j) == (0, 0) {
+            matrix[(0, 0)] = Element::Value(distance(0, 0));
+            return;
+        }
+        let best = moves
+            .iter()
+            .enumerate()
+            .filter(|(_, mv)| mv.origin.0 <= i && mv.origin.1 <= j)
+            .map(|(m, mv)| {
+                let origin = matrix.cell((i - mv.origin.0, j - mv.origin.1)).clone();
+                let cost = mv.cells.iter().fold(origin, |acc, ((di, dj), weight)| {
+                    acc + Element::Value(distance(i - di, j - dj).weighted(*weight))
+                });
+                (m, cost)
+            })
+            .reduce(|best, candidate| {
+                if candidate.1 < best.1 {
+                    candidate
+                } else {
+                    best
+                }
+            });
+        if let Some((m, cost)) = best.filter(|(_, cost)| *cost != Element::Inf) {
+            *matrix.cell_mut((i, j)) = cost;
+            *trace.cell_mut((i, j)) = Some(m as u8);
+        }
+    });
+}
+
+/// Path ending at `(i, j)` following the moves recorded in `trace`.
+pub(crate) fn compute_move_path(
+    trace: &Matrix<Option<u8>>,
+    i: usize,
+    j: usize,
+    step_pattern: StepPattern,
+) -> Vec<(usize, usize)> {
+    let moves = step_pattern.moves();
+    let (mut i, mut j) = (i, j);
+    let mut v = vec![(i, j)];
+    while let Some(m) = trace[(i, j)] {
+        let mv = &moves[m as usize];
+        v.extend(
+            mv.cells[..mv.cells.len() - 1]
+                .iter()
+                .rev()
+                .map(|((di, dj), _)| (i - di, j - dj)),
+        );
+        v.push((i - mv.origin.0, j - mv.origin.1));
+        (i, j) = (i - mv.origin.0, j - mv.origin.1);
+    }
+    v.reverse();
+    v
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compute_move_path, optimize_moves, LocalContinuity, SlopeWeighting, StepPattern};
+    use crate::{
+        algorithms::{dynamic_programming::Element, utils::Matrix},
+        Restriction,
+    };
+
+    #[test]
+    fn rabiner_moves() {
+        let moves = StepPattern::Rabiner(LocalContinuity::TypeII).moves();
+        assert_eq!(moves.len(), 3);
+        assert_eq!(moves[0].origin, (2, 1));
+        assert_eq!(moves[0].cells, [((1, 0), 1.0), ((0, 0), 1.0)]);
+        assert_eq!(
+            StepPattern::Rabiner(LocalContinuity::TypeVII).moves().len(),
+            9
+        );
+        let moves = StepPattern::Symmetric.moves();
+        assert_eq!(moves[2].origin, (0, 1));
+        assert_eq!(moves[2].cells, [((0, 0), 1.0)]);
+    }
+
+    #[test]
+    fn rabiner_juang_weights() {
+        let type_ii = LocalContinuity::TypeII;
+        let moves = StepPattern::RabinerJuang(type_ii, SlopeWeighting::D, false).moves();
+        assert_eq!(moves[0].cells, [((1, 0), 2.0), ((0, 0), 1.0)]);
+        assert_eq!(moves[1].cells, [((0, 0), 2.0)]);
+        let moves = StepPattern::RabinerJuang(type_ii, SlopeWeighting::D, true).moves();
+        assert_eq!(moves[0].cells, [((1, 0), 1.5), ((0, 0), 1.5)]);
+        let moves = StepPattern::RabinerJuang(type_ii, SlopeWeighting::A, false).moves();
+        assert_eq!(moves[2].cells, [((0, 1), 1.0), ((0, 0), 0.0)]);
+        let type_iv = LocalContinuity::TypeIV;
+        let moves = StepPattern::RabinerJuang(type_iv, SlopeWeighting::C, false).moves();
+        assert_eq!(moves[1].cells, [((1, 0), 1.0), ((0, 0), 1.0)]);
+    }
+
+    #[test]
+    fn symmetric2_weights() {
+        let moves = StepPattern::Symmetric2.moves();
+        assert_eq!(moves[0].origin, (1, 1));
+        assert_eq!(moves[0].cells, [((0, 0), 2.0)]);
+        assert_eq!(moves[1].cells, [((0, 0), 1.0)]);
+        let a = [0.0, 1.0, 1.0, 2.0];
+        let b = [1.0, 2.0, 2.0];
+        let mut matrix = Matrix::fill(Element::Inf, a.len(), b.len());
+        let mut trace = Matrix::fill(None, a.len(), b.len());
+        optimize_moves(
+            &mut matrix,
+            &mut trace,
+            &Restriction::None,
+            StepPattern::Symmetric2,
+            |i, j| f64::abs(a[i] - b[j]),
+        );
+        assert_eq!(matrix[(3, 2)], Element::Value(1.0));
+        assert_eq!(
+            compute_move_path(&trace, 3, 2, StepPattern::Symmetric2),
+            [(0, 0), (1, 0), (2, 0), (3, 1), (3, 2)]
+        );
+    }
+
+    #[test]
+    fn rabiner_type_i_path() {
+        let a = [0.0, 1.0, 1.0, 2.0];
+        let b = [0.0, 1.0, 2.0];
+        let step_pattern = StepPattern::Rabiner(LocalContinuity::TypeII);
+        let mut matrix = Matrix::fill(Element::Inf, a.len(), b.len());
+        let mut trace = Matrix::fill(None, a.len(), b.len());
+        optimize_moves(
+            &mut matrix,
+            &mut trace,
+            &Restriction::None,
+            step_pattern,
+            |i, j| f64::abs(a[i] - b[j]),
+        );
+        assert_eq!(matrix[(3, 2)], Element::Value(0.0));
+        assert_eq!(matrix[(0, 1)], Element::Inf);
+        assert_eq!(
+            compute_move_path(&trace, 3, 2, step_pattern),
+            [(0, 0), (1, 1), (2, 1), (3, 2)]
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use std::ops::Add;
+
+use super::{dynamic_programming::Element, step_pattern::StepPattern, utils::Backpointers};
+use crate::{Algorithm, ParameterizedAlgorithm};
+
+/// Free endpoints of the alignment of a query within a reference.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Open {
+    /// The query starts anywhere in the reference and ends at its end.
+    Begin,
+    /// The query starts at the start of the reference and ends anywhere in it.
+    End,
+    /// The query starts and ends anywhere in the reference.
+    #[default]
+    Both,
+}
+
+/// Alignment of the whole query `a` with a part of the reference `b`, whose start and/or end are
+/// chosen to minimize the warped distance.
+///
+/// Only two rows of accumulated costs are kept along with the predecessor chosen at each cell.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Subsequence<D> {
+    distance: D,
+    path: Vec<(usize, usize)>,
+}
+
+impl<D: PartialOrd + Clone + Add<D, Output = D>> Subsequence<D> {
+    /// Index of the element of `b` matched with the first element of `a`.
+    pub fn start(&self) -> usize {
+        self.path[0].1
+    }
+
+    /// Index of the element of `b` matched with the last element of `a`.
+    pub fn end(&self) -> usize {
+        self.path[self.path.len() - 1].1
+    }
+}
+
+impl<D: PartialOrd + Clone + Add<D, Output = D>> Algorithm<D> for Subsequence<D> {
+    fn with_closure<T>(a: &[T], b: &[T], distance: impl Fn(&T, &T) -> D) -> Self {
+        Self::with_closure_and_param(a, b, distance, Open::Both)
+    }
+
+    fn distance(&self) -> D {
+        self.distance.clone()
+    }
+
+    fn path(&self) -> Vec<(usize, usize)> {
+        self.path.clone()
+    }
+}
+
+impl<D: PartialOrd + Clone + Add<D, Output = D>> ParameterizedAlgorithm<D> for Subsequence<D> {
+    type Param = Open;
+
+    fn with_closure_and_param<T>(
+        a: &[T],
+        b: &[T],
+        distance: impl Fn(&T, &T) -> D,
+        open: Self::Param,
+    ) -> Self {
+        assert!(
+            !a.is_empty() && !b.is_empty(),
+            "Sequences should not be empty"
+        );
+        let shape = (a.len(), b.len());
+        let (last, _, backpointers) = accumulate(a, b, distance, open != Open::End);
+        let end = match open {
+            Open::Begin => shape.1 - 1,
+            Open::End | Open::Both => (0..shape.1)
+                .reduce(|best, j| if last[j] < last[best] { j } else { best })
+                .unwrap(),
+        };
+        let Element::Value(distance) = last[end].clone() else {
+            panic!("Infinit distance")
+        };
+        Self {
+            distance,
+            path: backpointers.path(shape.0 - 1, end, StepPattern::Symmetric.predecessors()),
+        }
+    }
+}
+
+/// Accumulated costs of the last row of the alignment of `a` within `b`, free to start anywhere
+/// in `b` if `open_begin`, with the index of `b` at which the path to each of its cells starts
+/// and the predecessor chosen at every cell.
+pub(crate) fn accumulate<T, D: PartialOrd + Clone + Add<D, Output = D>>(
+    a: &[T],
+    b: &[T],
+    distance: impl Fn(&T, &T) -> D,
+    open_begin: bool,
+) -> (Vec<Element<D>>, Vec<usize>, Backpointers) {
+    let shape = (a.len(), b.len());
+    let predecessors = StepPattern::Symmetric.predecessors();
+    let mut backpointers = Backpointers::new(shape);
+    let mut previous: Vec<(Element<D>, usize)> = vec![(Element::Inf, 0); shape.1];
+    let mut current: Vec<(Element<D>, usize)> = vec![(Element::Inf, 0); shape.1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            let cost = Element::Value(distance(x, y));
+            current[j] = if i == 0 && (open_begin || j == 0) {
+                (cost, j)
+            } else {
+                let best = predecessors
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (di, dj))| *di <= i && *dj <= j)
+                    .map(|(p, (di, dj))| match di {
+                        0 => (p, &current[j - dj]),
+                        _ => (p, &previous[j - dj]),
+                    })
+                    .reduce(|best, candidate| {
+                        if candidate.1 .0 < best.1 .0 {
+                            candidate
+                        } else {
+                            best
+                        }
+                    });
+                match best {
+                    Some((p, (best, start))) => {
+                        backpointers.set((i, j), Some(p));
+                        (best.clone() + cost, *start)
+                    }
+                    None => (Element::Inf, 0),
+                }
+            };
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    let (costs, starts) = previous.into_iter().unzip();
+    (costs, starts, backpointers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Open, Subsequence};
+    use crate::{Algorithm, DynamicTimeWarping, ParameterizedAlgorithm};
+
+    #[test]
+    fn open_endpoints() {
+        let query = [1.0, 5.0, 2.0];
+        let reference = [0.0, 0.0, 1.0, 4.0, 5.0, 2.0, 0.0, 0.0];
+        let both = Subsequence::between(&query, &reference);
+        assert_eq!(both.distance(), 1.0);
+        assert_eq!((both.start(), both.end()), (2, 5));
+        assert_eq!(both.path()[0], (0, 2));
+        let end = Subsequence::with_param(&query, &reference, Open::End);
+        assert_eq!(end.start(), 0);
+        assert_eq!(end.end(), 5);
+        let begin = Subsequence::with_param(&query, &reference, Open::Begin);
+        assert_eq!(begin.end(), 7);
+        assert_eq!(begin.start(), 2);
+        let full = DynamicTimeWarping::between(&query, &reference[2..]);
+        assert_eq!(begin.distance(), full.distance());
+    }
+}
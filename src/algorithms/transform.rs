@@ -0,0 +1,100 @@
+#[cfg(feature = "deterministic")]
+use super::similarity::{portable_exp, portable_ln_1p, portable_powf};
+
+/// Transformation applied to every local cost before it is accumulated.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Transform {
+    /// `c`, leaving the costs unchanged.
+    #[default]
+    Identity,
+    /// `c^2`.
+    Square,
+    /// `ln(1 + c)`, compressing the large costs.
+    Log1p,
+    /// `c^p`, e.g. for a Minkowski flavored distance of order `p`.
+    Power(f64),
+    /// Huber loss with threshold `delta`: `c^2 / 2` up to `delta` and `delta * (c - delta / 2)`
+    /// beyond, limiting the influence of outlier spikes on the alignment.
+    Huber(f64),
+    /// `1 - exp(-c^2 / (2 * sigma^2))` with the bandwidth `sigma`, bounded in `[0, 1]` such
+    /// that the distances of series with different amplitudes are comparable.
+    Gaussian(f64),
+}
+
+impl Transform {
+    /// Transformed cost of the cost `cost`.
+    pub fn apply(&self, cost: f64) -> f64 {
+        match self {
+            Transform::Identity => cost,
+            Transform::Square => cost * cost,
+            #[cfg(not(feature = "deterministic"))]
+            Transform::Log1p => cost.ln_1p(),
+            #[cfg(feature = "deterministic")]
+            Transform::Log1p => portable_ln_1p(cost),
+            #[cfg(not(feature = "deterministic"))]
+            Transform::Power(p) => cost.powf(*p),
+            #[cfg(feature = "deterministic")]
+            Transform::Power(p) => portable_powf(cost, *p),
+            Transform::Huber(delta) if cost.abs() <= *delta => cost * cost / 2.0,
+            Transform::Huber(delta) => delta * (cost.abs() - delta / 2.0),
+            #[cfg(not(feature = "deterministic"))]
+            Transform::Gaussian(sigma) => 1.0 - (-cost * cost / (2.0 * sigma * sigma)).exp(),
+            #[cfg(feature = "deterministic")]
+            Transform::Gaussian(sigma) => 1.0 - portable_exp(-cost * cost / (2.0 * sigma * sigma)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transform;
+    use crate::{Algorithm, DynamicTimeWarping, Parameters};
+
+    #[test]
+    fn transformed_costs() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let squared = DynamicTimeWarping::with_closure(&a, &b, |x: &f64, y: &f64| (x - y).powi(2));
+        let param = Parameters::new().transform(Transform::Square);
+        let transformed = DynamicTimeWarping::with_param(&a, &b, param);
+        assert_eq!(transformed.distance(), squared.distance());
+        assert_eq!(transformed.path(), squared.path());
+        let param = Parameters::new().transform(Transform::Power(2.0));
+        let power = DynamicTimeWarping::with_param(&a, &b, param);
+        assert_eq!(power.distance(), squared.distance());
+        assert_eq!(Transform::Log1p.apply(0.0), 0.0);
+    }
+
+    #[test]
+    fn huber_tolerates_spikes() {
+        let a = [0.0, 1.0, 2.0, 100.0, 3.0, 4.0];
+        let b = [0.0, 1.0, 2.0, 3.0, 4.0];
+        let huber = Transform::Huber(1.0);
+        assert_eq!((huber.apply(0.5), huber.apply(-3.0)), (0.125, 2.5));
+        let square = DynamicTimeWarping::with_param(&a, &b, Transform::Square);
+        let robust = DynamicTimeWarping::with_param(&a, &b, huber);
+        assert_eq!(square.distance(), 9218.0);
+        assert_eq!(robust.distance(), 96.5);
+    }
+
+    #[test]
+    fn gaussian_bounded() {
+        let gaussian = Transform::Gaussian(2.0);
+        assert_eq!(gaussian.apply(0.0), 0.0);
+        assert_eq!(gaussian.apply(2.0), 1.0 - f64::exp(-0.5));
+        assert_eq!(gaussian.apply(f64::INFINITY), 1.0);
+        let a = [0.0, 1000.0, 0.0];
+        let b = [0.0, -1000.0, 0.0];
+        let dtw = DynamicTimeWarping::with_param(&a, &b, gaussian);
+        assert_eq!(dtw.distance(), 1.0);
+    }
+
+    #[cfg(feature = "deterministic")]
+    #[test]
+    fn portable_transforms() {
+        assert_eq!(Transform::Log1p.apply(0.75), 0.5596157879354227);
+        assert_eq!(Transform::Log1p.apply(1e-20), 1e-20);
+        assert_eq!(Transform::Power(1.5).apply(3.0), 5.196152422706632);
+        assert_eq!(Transform::Power(2.0).apply(0.0), 0.0);
+    }
+}
@@ -0,0 +1,127 @@
+use std::ops::Range;
+
+use crate::Algorithm;
+
+/// Detector of the leading and trailing segments ignored by [`Trimmed`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Trim {
+    /// Elements with an absolute value less than or equal to the threshold.
+    Energy(f64),
+    /// Elements within the tolerance of the first (leading) or last (trailing) element.
+    Flat(f64),
+}
+
+/// Alignment of two sequences after trimming their leading and trailing noise, with indices
+/// reported in the coordinates of the original sequences.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Trimmed<A> {
+    alignment: A,
+    ranges: (Range<usize>, Range<usize>),
+}
+
+impl Trim {
+    /// Range of `series` kept after trimming. The whole range is kept when every element is
+    /// detected as noise.
+    pub fn range<T: Copy + Into<f64>>(&self, series: &[T]) -> Range<usize> {
+        let values = series.iter().map(|v| (*v).into()).collect::<Vec<f64>>();
+        let (first, last) = match (values.first(), values.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => return 0..0,
+        };
+        let begin = values
+            .iter()
+            .position(|v| !self.is_noise(*v, first))
+            .unwrap_or(0);
+        let end = values
+            .iter()
+            .rposition(|v| !self.is_noise(*v, last))
+            .map(|i| i + 1)
+            .unwrap_or(values.len());
+        if begin < end {
+            begin..end
+        } else {
+            0..values.len()
+        }
+    }
+
+    fn is_noise(&self, value: f64, reference: f64) -> bool {
+        match self {
+            Trim::Energy(threshold) => value.abs() <= *threshold,
+            Trim::Flat(tolerance) => (value - reference).abs() <= *tolerance,
+        }
+    }
+}
+
+impl<A> Trimmed<A> {
+    /// Trim `a` and `b` using `trim` and align the remainders using `align`.
+    pub fn new<T: Copy + Into<f64>>(
+        a: &[T],
+        b: &[T],
+        trim: Trim,
+        align: impl FnOnce(&[T], &[T]) -> A,
+    ) -> Self {
+        let ranges = (trim.range(a), trim.range(b));
+        Self {
+            alignment: align(&a[ranges.0.clone()], &b[ranges.1.clone()]),
+            ranges,
+        }
+    }
+
+    /// Alignment of the trimmed sequences.
+    pub fn alignment(&self) -> &A {
+        &self.alignment
+    }
+
+    /// Ranges of `a` and `b` kept after trimming.
+    pub fn ranges(&self) -> (Range<usize>, Range<usize>) {
+        self.ranges.clone()
+    }
+
+    /// Warped distance between the trimmed sequences.
+    pub fn distance<D>(&self) -> D
+    where
+        A: Algorithm<D>,
+    {
+        self.alignment.distance()
+    }
+
+    /// Warped path between the trimmed sequences in the coordinates of `a` and `b`.
+    pub fn path<D>(&self) -> Vec<(usize, usize)>
+    where
+        A: Algorithm<D>,
+    {
+        let offset = (self.ranges.0.start, self.ranges.1.start);
+        self.alignment
+            .path()
+            .into_iter()
+            .map(|(i, j)| (i + offset.0, j + offset.1))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Trim, Trimmed};
+    use crate::{Algorithm, DynamicTimeWarping};
+
+    #[test]
+    fn trim_range() {
+        let series = [0.0, 0.01, 1.0, 2.0, 0.0, 3.0, -0.02, 0.0];
+        assert_eq!(Trim::Energy(0.05).range(&series), 2..6);
+        assert_eq!(Trim::Flat(0.05).range(&series), 2..6);
+        assert_eq!(Trim::Energy(0.05).range(&[0.0, 0.0]), 0..2);
+        assert_eq!(Trim::Flat(0.0).range(&[5.0, 5.0, 1.0, 7.0, 7.0]), 2..3);
+    }
+
+    #[test]
+    fn trimmed_path_in_original_coordinates() {
+        let a = [0.0, 0.0, 1.0, 2.0, 3.0, 0.0];
+        let b = [1.0, 2.0, 3.0, 0.0, 0.0, 0.0];
+        let trimmed = Trimmed::new(&a, &b, Trim::Energy(0.0), |a, b| {
+            DynamicTimeWarping::between(a, b)
+        });
+        assert_eq!(trimmed.ranges(), (2..5, 0..3));
+        assert_eq!(trimmed.distance(), 0.0);
+        assert_eq!(trimmed.path(), [(2, 0), (3, 1), (4, 2)]);
+    }
+}
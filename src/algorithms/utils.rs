@@ -1,8 +1,9 @@
 use std::{
     fmt::Display,
-    ops::{Index, IndexMut},
+    ops::{Index, IndexMut, Range},
 };
 
+/// Dense matrix stored row by row.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Matrix<T> {
     data: Box<[T]>,
@@ -98,19 +99,210 @@ impl<T> Matrix<T> {
         }
     }
 
+    /// Cell `idx`, accessed without bounds checks in release builds with the `unsafe-perf`
+    /// feature. The callers guarantee that `idx` lies within the shape.
+    #[inline(always)]
+    pub(crate) fn cell(&self, idx: (usize, usize)) -> &T {
+        #[cfg(feature = "unsafe-perf")]
+        {
+            debug_assert!(idx.0 < self.shape.0 && idx.1 < self.shape.1);
+            // SAFETY: `idx` lies within the shape, such that the offset lies within the data.
+            unsafe { self.data.get_unchecked(self.shape.1 * idx.0 + idx.1) }
+        }
+        #[cfg(not(feature = "unsafe-perf"))]
+        &self[idx]
+    }
+
+    /// Mutable cell `idx`, accessed like [`Matrix::cell`].
+    #[inline(always)]
+    pub(crate) fn cell_mut(&mut self, idx: (usize, usize)) -> &mut T {
+        #[cfg(feature = "unsafe-perf")]
+        {
+            debug_assert!(idx.0 < self.shape.0 && idx.1 < self.shape.1);
+            // SAFETY: `idx` lies within the shape, such that the offset lies within the data.
+            unsafe { self.data.get_unchecked_mut(self.shape.1 * idx.0 + idx.1) }
+        }
+        #[cfg(not(feature = "unsafe-perf"))]
+        &mut self[idx]
+    }
+
+    /// Number of rows and columns.
     pub fn shape(&self) -> (usize, usize) {
         self.shape
     }
 
-    #[cfg(test)]
-    pub fn data(self) -> Box<[T]> {
+    /// Cells stored row by row.
+    pub fn data(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Mutable cells stored row by row.
+    pub fn data_mut(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+
+    /// Cells of row `i`.
+    pub fn row(&self, i: usize) -> &[T] {
+        assert!(
+            i < self.shape.0,
+            "Dimention 0 should be less than shape.0 = {}",
+            self.shape.0
+        );
+        &self.data[self.shape.1 * i..self.shape.1 * (i + 1)]
+    }
+
+    /// Rows from the first one.
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.shape.0).map(|i| self.row(i))
+    }
+
+    /// Cells of column `j` from the first row.
+    pub fn column(&self, j: usize) -> impl Iterator<Item = &T> {
+        assert!(
+            j < self.shape.1,
+            "Dimention 1 should be less than shape.1 = {}",
+            self.shape.1
+        );
+        self.data.iter().skip(j).step_by(self.shape.1)
+    }
+
+    /// Cells with their indices, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let columns = self.shape.1;
         self.data
+            .iter()
+            .enumerate()
+            .map(move |(k, cell)| ((k / columns, k % columns), cell))
     }
 }
 
+/// Cells of a matrix stored row by row, only a range of columns of each row, e.g. the cells of
+/// a band such that the storage is linear in the number of rows.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Layout {
+    shape: (usize, usize),
+    rows: Box<[(usize, Range<usize>)]>,
+}
+
+impl Layout {
+    /// Every cell of a matrix of shape `shape`.
+    pub(crate) fn dense(shape: (usize, usize)) -> Self {
+        Self::new(shape, |_| 0..shape.1)
+    }
+
+    /// Cells of the columns `columns(i)` of each row `i` of a matrix of shape `shape`.
+    pub(crate) fn new(shape: (usize, usize), columns: impl Fn(usize) -> Range<usize>) -> Self {
+        let mut offset = 0;
+        let rows = (0..shape.0)
+            .map(|i| {
+                let range = columns(i);
+                let row = (offset, range.clone());
+                offset += range.len();
+                row
+            })
+            .collect();
+        Self { shape, rows }
+    }
+
+    /// Number of rows and columns of the matrix.
+    pub(crate) fn shape(&self) -> (usize, usize) {
+        self.shape
+    }
+
+    /// Number of stored cells.
+    pub(crate) fn len(&self) -> usize {
+        self.rows
+            .last()
+            .map_or(0, |(offset, range)| offset + range.len())
+    }
+
+    /// Stored columns of row `i`.
+    pub(crate) fn columns(&self, i: usize) -> Range<usize> {
+        self.rows[i].1.clone()
+    }
+
+    /// Position of the cell `idx` in the storage, or `None` if it is not stored.
+    pub(crate) fn position(&self, idx: (usize, usize)) -> Option<usize> {
+        let (offset, range) = &self.rows[idx.0];
+        range.contains(&idx.1).then(|| offset + idx.1 - range.start)
+    }
+}
+
+/// Predecessor chosen at each cell, packed in two bits per cell: zero for none, otherwise one
+/// plus the index of the predecessor in the step pattern.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct Backpointers {
+    words: Box<[u64]>,
+    layout: Layout,
+}
+
+impl Backpointers {
+    /// Backpointers of shape `shape` without any predecessor.
+    pub(crate) fn new(shape: (usize, usize)) -> Self {
+        Self::with_layout(Layout::dense(shape))
+    }
+
+    /// Backpointers of the cells of `layout` without any predecessor. The cells outside of it
+    /// have no predecessor.
+    pub(crate) fn with_layout(layout: Layout) -> Self {
+        Self {
+            words: vec![0; layout.len().div_ceil(32)].into_boxed_slice(),
+            layout,
+        }
+    }
+
+    /// Index of the predecessor of the cell `idx`.
+    pub(crate) fn get(&self, idx: (usize, usize)) -> Option<usize> {
+        let k = self.layout.position(idx)?;
+        match self.words[k / 32] >> (2 * (k % 32)) & 0b11 {
+            0 => None,
+            p => Some(p as usize - 1),
+        }
+    }
+
+    /// Set the index of the predecessor of the cell `idx` of the layout, which is less than
+    /// three.
+    pub(crate) fn set(&mut self, idx: (usize, usize), predecessor: Option<usize>) {
+        let k = self
+            .layout
+            .position(idx)
+            .expect("Cell should be in the layout");
+        let bits = predecessor.map_or(0, |p| p as u64 + 1);
+        let shift = 2 * (k % 32);
+        self.words[k / 32] = self.words[k / 32] & !(0b11 << shift) | bits << shift;
+    }
+
+    /// Path ending at `(i, j)` following the predecessors `predecessors` of the step pattern.
+    pub(crate) fn path(
+        &self,
+        i: usize,
+        j: usize,
+        predecessors: &[(usize, usize)],
+    ) -> Vec<(usize, usize)> {
+        let (mut i, mut j) = (i, j);
+        let mut v = vec![(i, j)];
+        while let Some(p) = self.get((i, j)) {
+            let (di, dj) = predecessors[p];
+            (i, j) = (i - di, j - dj);
+            v.push((i, j));
+        }
+        v.reverse();
+        v
+    }
+}
+
+/// Pseudo-random series of `len` values in `[0, 10)` drawn from `seed`, shared by the tests
+/// comparing the alternative computations of the distance.
+#[cfg(test)]
+pub(crate) fn series(len: usize, seed: u64) -> Vec<f64> {
+    (0..len)
+        .map(|k| ((k as u64 * 2654435761 + seed) % 1000) as f64 / 100.0)
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::Matrix;
+    use super::{Backpointers, Matrix};
 
     #[test]
     fn matrix_new() {
@@ -193,6 +385,29 @@ mod tests {
         }
     }
 
+    #[test]
+    fn matrix_iterators() {
+        let mut matrix = Matrix::from((0..6).collect(), 2, 3);
+        assert_eq!(matrix.rows().collect::<Vec<_>>(), [[0, 1, 2], [3, 4, 5]]);
+        assert_eq!(matrix.column(1).copied().collect::<Vec<_>>(), [1, 4]);
+        assert!(matrix.iter().all(|((i, j), v)| *v == 3 * i + j));
+        matrix.data_mut()[4] = 9;
+        assert_eq!(matrix[(1, 1)], 9);
+        assert_eq!(matrix.data(), [0, 1, 2, 3, 9, 5]);
+    }
+
+    #[test]
+    fn packed_backpointers() {
+        let mut backpointers = Backpointers::new((3, 15));
+        for k in 0..45 {
+            backpointers.set((k / 15, k % 15), Some(k % 3));
+        }
+        backpointers.set((1, 2), None);
+        assert_eq!(backpointers.get((1, 2)), None);
+        assert_eq!(backpointers.get((2, 14)), Some(2));
+        assert_eq!(backpointers.get((1, 3)), Some(0));
+    }
+
     fn sized_send_sync_unpin_check<T: Sized + Send + Sync + Unpin>() {}
     #[test]
     fn check_auto_traits() {
@@ -0,0 +1,150 @@
+use std::ops::{Add, Range};
+
+use rayon::prelude::*;
+
+use super::dynamic_programming::{DynamicTimeWarping, Element};
+use crate::Cost;
+
+/// Bottom row and right column of accumulated costs of a computed tile.
+type Edges<D> = (Vec<Element<D>>, Vec<Element<D>>);
+
+impl<D> DynamicTimeWarping<D>
+where
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost + Send + Sync,
+{
+    /// Warped distance between `a` and `b` with the symmetric step pattern and no restriction,
+    /// computed on several threads, e.g. for a single pair of very long series.
+    ///
+    /// The matrix is split into square tiles of side `block`. A tile only depends on the tiles
+    /// above, on the left and on the upper left, such that the tiles of an anti-diagonal of
+    /// tiles are computed in parallel once the previous anti-diagonal is done. Only the last
+    /// row and column of the tiles of the two latest anti-diagonals are retained, such that the
+    /// memory is linear in the lengths of `a` and `b`.
+    pub fn par_distance_only<T: Sync>(
+        a: &[T],
+        b: &[T],
+        distance: impl Fn(&T, &T) -> D + Sync,
+        block: usize,
+    ) -> D {
+        assert!(
+            !a.is_empty() && !b.is_empty(),
+            "Sequences should not be empty"
+        );
+        assert!(block > 0, "Block should be positive");
+        let (rows, columns) = (a.len().div_ceil(block), b.len().div_ceil(block));
+        let span = |k: usize, len: usize| k * block..((k + 1) * block).min(len);
+        let mut edges: Vec<Option<Edges<D>>> = vec![None; rows * columns];
+        for wave in 0..rows + columns - 1 {
+            let tiles = (wave.saturating_sub(columns - 1)..=wave.min(rows - 1))
+                .map(|r| (r, wave - r))
+                .collect::<Vec<(usize, usize)>>();
+            let computed = tiles
+                .par_iter()
+                .map(|&(r, c)| {
+                    let top = r
+                        .checked_sub(1)
+                        .and_then(|r| edges[r * columns + c].as_ref());
+                    let left = c
+                        .checked_sub(1)
+                        .and_then(|c| edges[r * columns + c].as_ref());
+                    let corner = match (r.checked_sub(1), c.checked_sub(1)) {
+                        (Some(r), Some(c)) => edges[r * columns + c]
+                            .as_ref()
+                            .and_then(|(bottom, _)| bottom.last().cloned())
+                            .unwrap_or(Element::Inf),
+                        _ => Element::Inf,
+                    };
+                    tile(
+                        |i, j| distance(&a[i], &b[j]),
+                        span(r, a.len()),
+                        span(c, b.len()),
+                        top.map(|(bottom, _)| bottom.as_slice()),
+                        left.map(|(_, right)| right.as_slice()),
+                        corner,
+                    )
+                })
+                .collect::<Vec<Edges<D>>>();
+            if let Some(old) = wave.checked_sub(2) {
+                for r in old.saturating_sub(columns - 1)..=old.min(rows - 1) {
+                    edges[r * columns + old - r] = None;
+                }
+            }
+            for ((r, c), computed) in tiles.into_iter().zip(computed) {
+                edges[r * columns + c] = Some(computed);
+            }
+        }
+        match edges[rows * columns - 1]
+            .take()
+            .and_then(|(bottom, _)| bottom.last().cloned())
+        {
+            Some(Element::Value(distance)) => distance,
+            _ => panic!("Infinit distance"),
+        }
+    }
+}
+
+/// Accumulated costs of the tile of the rows `rows` and the columns `columns`, given the
+/// bottom row `top` of the tile above, the right column `left` of the tile on the left and the
+/// last cell `corner` of the tile on the upper left, returned as its bottom row and right column.
+fn tile<D>(
+    distance: impl Fn(usize, usize) -> D,
+    rows: Range<usize>,
+    columns: Range<usize>,
+    top: Option<&[Element<D>]>,
+    left: Option<&[Element<D>]>,
+    corner: Element<D>,
+) -> Edges<D>
+where
+    D: PartialOrd + Clone + Add<D, Output = D>,
+{
+    let mut row = top.map_or_else(|| vec![Element::Inf; columns.len()], <[_]>::to_vec);
+    let mut right = Vec::with_capacity(rows.len());
+    for (k, i) in rows.enumerate() {
+        let outside = |k: usize| left.map_or(Element::Inf, |left| left[k].clone());
+        let mut diagonal = match k {
+            0 => corner.clone(),
+            _ => outside(k - 1),
+        };
+        let mut west = outside(k);
+        for (cell, j) in row.iter_mut().zip(columns.clone()) {
+            let cost = Element::Value(distance(i, j));
+            let accumulated = match (i, j) {
+                (0, 0) => cost,
+                _ => {
+                    let best = [&*cell, &west]
+                        .into_iter()
+                        .fold(&diagonal, |best, candidate| match candidate < best {
+                            true => candidate,
+                            false => best,
+                        })
+                        .clone();
+                    best + cost
+                }
+            };
+            diagonal = std::mem::replace(cell, accumulated);
+            west = cell.clone();
+        }
+        right.push(west);
+    }
+    (row, right)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{algorithms::utils::series, DynamicTimeWarping, Parameters};
+
+    #[test]
+    fn wavefront_matches_two_rows() {
+        let cost = |x: &f64, y: &f64| f64::abs(x - y);
+        for (n, m) in [(1, 1), (1, 9), (9, 1), (13, 40), (40, 13), (33, 33)] {
+            let (a, b) = (series(n, 3), series(m, 11));
+            let expected = DynamicTimeWarping::distance_only(&a, &b, cost, Parameters::new());
+            for block in [1, 4, 7, 64] {
+                assert_eq!(
+                    DynamicTimeWarping::par_distance_only(&a, &b, cost, block),
+                    expected
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,94 @@
+//! Interoperability with `ndarray`: scalar series given as one-dimensional arrays, multivariate
+//! series as two-dimensional arrays with a row per time step and a column per dimension, and
+//! the accumulated costs returned as a two-dimensional array.
+
+use ndarray::{Array2, ArrayView1, ArrayView2};
+
+use crate::{DynamicTimeWarping, Parameters};
+
+/// Dynamic time warping between the scalar series `a` and `b` with the absolute difference as
+/// local cost and the hyper-parameters `hyper_parameters`.
+pub fn align(
+    a: ArrayView1<f64>,
+    b: ArrayView1<f64>,
+    hyper_parameters: impl Into<Parameters>,
+) -> DynamicTimeWarping<f64> {
+    DynamicTimeWarping::with_closure_and_param(
+        &a.iter().collect::<Vec<&f64>>(),
+        &b.iter().collect::<Vec<&f64>>(),
+        |x, y| f64::abs(*x - *y),
+        hyper_parameters,
+    )
+}
+
+/// Dynamic time warping between the multivariate series `a` and `b` of a common number of
+/// columns, with the Euclidean distance between the rows as local cost and the
+/// hyper-parameters `hyper_parameters`.
+pub fn align_rows(
+    a: ArrayView2<f64>,
+    b: ArrayView2<f64>,
+    hyper_parameters: impl Into<Parameters>,
+) -> DynamicTimeWarping<f64> {
+    assert_eq!(
+        a.ncols(),
+        b.ncols(),
+        "Series should have the same number of columns"
+    );
+    DynamicTimeWarping::with_closure_and_param(
+        &a.rows().into_iter().collect::<Vec<ArrayView1<f64>>>(),
+        &b.rows().into_iter().collect::<Vec<ArrayView1<f64>>>(),
+        |x, y| {
+            x.iter()
+                .zip(y)
+                .map(|(x, y)| (x - y) * (x - y))
+                .sum::<f64>()
+                .sqrt()
+        },
+        hyper_parameters,
+    )
+}
+
+/// Accumulated cost of every cell of `dtw`, infinite for the unreached cells.
+pub fn cost_array<D>(dtw: &DynamicTimeWarping<D>) -> Array2<f64>
+where
+    D: PartialOrd + Clone + Default + Into<f64>,
+{
+    let costs = dtw.cost_matrix();
+    Array2::from_shape_vec(
+        costs.shape(),
+        costs
+            .data()
+            .iter()
+            .map(|cost| cost.clone().map_or(f64::INFINITY, Into::into))
+            .collect(),
+    )
+    .expect("Shape should match the number of cells")
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::{array, Array1};
+
+    use super::{align, align_rows, cost_array};
+    use crate::{Algorithm, DynamicTimeWarping, Parameters, Restriction};
+
+    #[test]
+    fn arrays_as_series() {
+        let a = Array1::from(vec![1.0, 3.0, 9.0, 2.0, 1.0]);
+        let b = Array1::from(vec![2.0, 0.0, 0.0, 8.0, 7.0, 2.0]);
+        let dtw = align(a.view(), b.view(), Parameters::new());
+        let slices = DynamicTimeWarping::between(a.as_slice().unwrap(), b.as_slice().unwrap());
+        assert_eq!(dtw.distance(), slices.distance());
+        assert_eq!(dtw.path(), slices.path());
+        let costs = cost_array(&dtw);
+        assert_eq!(costs.dim(), (5, 6));
+        assert_eq!(costs[[4, 5]], dtw.distance());
+        let banded = align(a.view(), b.view(), Restriction::Band(0));
+        assert_eq!(cost_array(&banded)[[0, 3]], f64::INFINITY);
+        let rows = array![[0.0, 1.0], [1.0, 1.0], [2.0, 0.0]];
+        let columns = array![[0.0, 1.0, 2.0], [1.0, 1.0, 0.0]];
+        let multivariate = align_rows(rows.view(), columns.t(), Parameters::new());
+        assert_eq!(multivariate.distance(), 0.0);
+        assert_eq!(multivariate.path(), vec![(0, 0), (1, 1), (2, 2)]);
+    }
+}
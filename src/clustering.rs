@@ -0,0 +1,687 @@
+//! Averaging and clustering of series under dynamic time warping.
+//!
+//! The series are aligned with the squared differences as local costs, such that the mean of
+//! the elements matched with an element of the average minimizes the total cost.
+
+#[cfg(feature = "deterministic")]
+use crate::algorithms::portable_powf as powf;
+use crate::{Algorithm, DynamicTimeWarping, Matrix};
+
+/// Average of a set of series computed by [`dba_report`], with the final alignment of every
+/// member to it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Barycenter {
+    /// Average series.
+    pub average: Vec<f64>,
+    /// Alignment of every member to the average, in the order of the members.
+    pub members: Vec<MemberAlignment>,
+    /// Number of iterations run before convergence or the limit.
+    pub iterations: usize,
+}
+
+/// Alignment of a member of a set of series to their average.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MemberAlignment {
+    /// Warped path from the average to the member.
+    pub path: Vec<(usize, usize)>,
+    /// Warped distance between the average and the member.
+    pub distance: f64,
+}
+
+/// Average of `series` by DTW barycenter averaging (Petitjean et al., "A global averaging method
+/// for dynamic time warping"), starting from their medoid and running at most `iterations`
+/// iterations.
+pub fn dba(series: &[Vec<f64>], iterations: usize) -> Vec<f64> {
+    dba_report(series, iterations).average
+}
+
+/// [`dba`] along with the final alignment and distance of every member to the average, e.g. to
+/// assess the convergence or to detect outlying members.
+pub fn dba_report(series: &[Vec<f64>], iterations: usize) -> Barycenter {
+    assert!(
+        !series.is_empty() && series.iter().all(|s| !s.is_empty()),
+        "Series should not be empty"
+    );
+    let mut average = series[medoid(series)].clone();
+    let mut members = align(&average, series);
+    let mut iteration = 0;
+    while iteration < iterations {
+        let updated = update(&average, series, &members, None);
+        iteration += 1;
+        if updated == average {
+            break;
+        }
+        average = updated;
+        members = align(&average, series);
+    }
+    Barycenter {
+        average,
+        members,
+        iterations: iteration,
+    }
+}
+
+/// Fuzzy partition of a set of series computed by [`fuzzy_c_means`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct FuzzyClusters {
+    /// Centroid of every cluster.
+    pub centroids: Vec<Vec<f64>>,
+    /// Membership weights of every series in every cluster, summing to one for each series.
+    pub memberships: Vec<Vec<f64>>,
+    /// Number of iterations run before convergence or the limit.
+    pub iterations: usize,
+}
+
+/// Fuzzy c-means partition of `series` into `clusters` clusters with the fuzziness exponent
+/// `fuzziness`, greater than one, running at most `iterations` iterations.
+///
+/// The centroids start from series far apart from each other and are updated by a step of DTW
+/// barycenter averaging weighted by the memberships raised to the fuzziness exponent, such that
+/// the series straddling clusters are shared between them instead of being assigned to one.
+pub fn fuzzy_c_means(
+    series: &[Vec<f64>],
+    clusters: usize,
+    fuzziness: f64,
+    iterations: usize,
+) -> FuzzyClusters {
+    assert!(
+        0 < clusters && clusters <= series.len(),
+        "Clusters should be between one and the number of series"
+    );
+    assert!(fuzziness > 1.0, "Fuzziness should be greater than one");
+    let mut centroids = farthest_first(series, clusters);
+    let mut memberships = vec![vec![0.0; clusters]; series.len()];
+    let mut iteration = 0;
+    while iteration < iterations {
+        let alignments = centroids
+            .iter()
+            .map(|c| align(c, series))
+            .collect::<Vec<Vec<MemberAlignment>>>();
+        let updated = (0..series.len())
+            .map(|k| {
+                let distances = alignments
+                    .iter()
+                    .map(|a| a[k].distance)
+                    .collect::<Vec<f64>>();
+                memberships_of(&distances, fuzziness)
+            })
+            .collect::<Vec<Vec<f64>>>();
+        iteration += 1;
+        let change = updated
+            .iter()
+            .flatten()
+            .zip(memberships.iter().flatten())
+            .map(|(u, v)| f64::abs(u - v))
+            .fold(0.0, f64::max);
+        memberships = updated;
+        for (c, centroid) in centroids.iter_mut().enumerate() {
+            let weights = memberships
+                .iter()
+                .map(|u| powf(u[c], fuzziness))
+                .collect::<Vec<f64>>();
+            *centroid = update(centroid, series, &alignments[c], Some(&weights));
+        }
+        if change < 1e-9 {
+            break;
+        }
+    }
+    FuzzyClusters {
+        centroids,
+        memberships,
+        iterations: iteration,
+    }
+}
+
+/// Partition of a set of series computed by [`KMeans`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct Clusters {
+    /// Cluster of every series.
+    pub labels: Vec<usize>,
+    /// Centroid of every cluster.
+    pub centroids: Vec<Vec<f64>>,
+    /// Number of iterations run before convergence or the limit.
+    pub iterations: usize,
+}
+
+/// K-means partition of series under dynamic time warping, with DTW barycenter averaging of the
+/// members of every cluster as centroids.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct KMeans {
+    clusters: usize,
+    iterations: usize,
+}
+
+impl KMeans {
+    /// K-means into `clusters` clusters running at most `iterations` iterations.
+    pub fn new(clusters: usize, iterations: usize) -> Self {
+        assert!(clusters > 0, "Clusters should be positive");
+        Self {
+            clusters,
+            iterations,
+        }
+    }
+
+    /// Partition of `series`, whose centroids start from series far apart from each other. Every
+    /// iteration assigns every series to its nearest centroid, the lowest cluster on ties, and
+    /// updates every centroid by a step of DTW barycenter averaging of its members, until the
+    /// assignments no longer change. The centroids of the empty clusters are kept.
+    pub fn fit(&self, series: &[Vec<f64>]) -> Clusters {
+        assert!(
+            self.clusters <= series.len(),
+            "Clusters should not outnumber the series"
+        );
+        let mut centroids = farthest_first(series, self.clusters);
+        let mut labels = Vec::new();
+        let mut iteration = 0;
+        while iteration < self.iterations {
+            let alignments = centroids
+                .iter()
+                .map(|c| align(c, series))
+                .collect::<Vec<Vec<MemberAlignment>>>();
+            let updated = (0..series.len())
+                .map(|k| {
+                    (0..self.clusters)
+                        .reduce(|best, c| {
+                            if alignments[c][k].distance < alignments[best][k].distance {
+                                c
+                            } else {
+                                best
+                            }
+                        })
+                        .unwrap()
+                })
+                .collect::<Vec<usize>>();
+            iteration += 1;
+            if updated == labels {
+                break;
+            }
+            labels = updated;
+            for (c, centroid) in centroids.iter_mut().enumerate() {
+                let weights = labels
+                    .iter()
+                    .map(|l| if *l == c { 1.0 } else { 0.0 })
+                    .collect::<Vec<f64>>();
+                *centroid = update(centroid, series, &alignments[c], Some(&weights));
+            }
+        }
+        Clusters {
+            labels,
+            centroids,
+            iterations: iteration,
+        }
+    }
+}
+
+/// Partition of a set of series around some of them computed by [`KMedoids`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct Medoids {
+    /// Index of the series at the center of every cluster.
+    pub medoids: Vec<usize>,
+    /// Cluster of every series.
+    pub labels: Vec<usize>,
+    /// Total distance of the series to their medoids.
+    pub cost: f64,
+    /// Number of swaps run before convergence or the limit.
+    pub iterations: usize,
+}
+
+/// K-medoids partition of series by Partitioning Around Medoids (Kaufman and Rousseeuw), on the
+/// matrix of their warped distances, e.g. from [`crate::pairwise::distance_matrix`], such that
+/// no series is averaged.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct KMedoids {
+    clusters: usize,
+    iterations: usize,
+}
+
+impl KMedoids {
+    /// K-medoids into `clusters` clusters running at most `iterations` swaps.
+    pub fn new(clusters: usize, iterations: usize) -> Self {
+        assert!(clusters > 0, "Clusters should be positive");
+        Self {
+            clusters,
+            iterations,
+        }
+    }
+
+    /// Partition of the series whose cell `(i, j)` of `distances` is the distance from the
+    /// series `i` to the series `j`, i.e. the distance to the medoid `j`.
+    ///
+    /// The medoids are built greedily, each one reducing the total distance the most, then the
+    /// swap of a medoid with another series reducing the total distance the most is applied
+    /// until none does. Every series belongs to its nearest medoid, the first one on ties.
+    pub fn fit(&self, distances: &Matrix<f64>) -> Medoids {
+        let n = distances.shape().0;
+        assert_eq!(distances.shape(), (n, n), "Distances should be square");
+        assert!(
+            self.clusters <= n,
+            "Clusters should not outnumber the series"
+        );
+        let cost = |medoids: &[usize]| {
+            (0..n)
+                .map(|i| {
+                    medoids
+                        .iter()
+                        .map(|m| distances[(i, *m)])
+                        .fold(f64::INFINITY, f64::min)
+                })
+                .sum::<f64>()
+        };
+        let cheapest = |candidates: &mut dyn Iterator<Item = Vec<usize>>| {
+            candidates
+                .map(|medoids| (cost(&medoids), medoids))
+                .reduce(|best, candidate| {
+                    if candidate.0 < best.0 {
+                        candidate
+                    } else {
+                        best
+                    }
+                })
+        };
+        let mut medoids = Vec::new();
+        while medoids.len() < self.clusters {
+            let mut candidates = (0..n).filter(|c| !medoids.contains(c)).map(|c| {
+                let mut candidate = medoids.clone();
+                candidate.push(c);
+                candidate
+            });
+            (_, medoids) = cheapest(&mut candidates).unwrap();
+        }
+        let mut total = cost(&medoids);
+        let mut iteration = 0;
+        while iteration < self.iterations {
+            let mut swaps = (0..self.clusters).flat_map(|k| {
+                let medoids = &medoids;
+                (0..n).filter(|c| !medoids.contains(c)).map(move |c| {
+                    let mut candidate = medoids.clone();
+                    candidate[k] = c;
+                    candidate
+                })
+            });
+            match cheapest(&mut swaps) {
+                Some((swapped, candidate)) if swapped < total => {
+                    (total, medoids) = (swapped, candidate);
+                    iteration += 1;
+                }
+                _ => break,
+            }
+        }
+        let labels = (0..n)
+            .map(|i| {
+                (0..self.clusters)
+                    .reduce(|best, k| {
+                        if distances[(i, medoids[k])] < distances[(i, medoids[best])] {
+                            k
+                        } else {
+                            best
+                        }
+                    })
+                    .unwrap()
+            })
+            .collect();
+        Medoids {
+            medoids,
+            labels,
+            cost: total,
+            iterations: iteration,
+        }
+    }
+}
+
+/// Distance between two clusters of series from the distances between their members.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Linkage {
+    /// Distance between the closest members.
+    Single,
+    /// Distance between the farthest members.
+    Complete,
+    /// Mean distance between the members (UPGMA).
+    #[default]
+    Average,
+}
+
+/// Merge of two clusters of a [`Dendrogram`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Merge {
+    /// Merged clusters, the series being the clusters `0..n` and the cluster formed by the
+    /// `k`-th merge being the cluster `n + k`, the lowest first.
+    pub clusters: (usize, usize),
+    /// Distance between the merged clusters.
+    pub distance: f64,
+    /// Number of series of the formed cluster.
+    pub size: usize,
+}
+
+/// Hierarchy of clusters formed by agglomerative clustering, in the layout of the linkage
+/// matrices of SciPy.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Dendrogram {
+    merges: Vec<Merge>,
+}
+
+impl Dendrogram {
+    /// Merges from the closest clusters, `n - 1` for `n` series.
+    pub fn merges(&self) -> &[Merge] {
+        &self.merges
+    }
+
+    /// Cluster of every series when the hierarchy is cut into `clusters` clusters, numbered in
+    /// the order of their first series.
+    pub fn labels(&self, clusters: usize) -> Vec<usize> {
+        let n = self.merges.len() + 1;
+        assert!(
+            0 < clusters && clusters <= n,
+            "Clusters should be between one and the number of series"
+        );
+        let mut parents = (0..2 * n - 1).collect::<Vec<usize>>();
+        for (k, merge) in self.merges[..n - clusters].iter().enumerate() {
+            parents[merge.clusters.0] = n + k;
+            parents[merge.clusters.1] = n + k;
+        }
+        let root = |mut c: usize| {
+            while parents[c] != c {
+                c = parents[c];
+            }
+            c
+        };
+        let mut roots = Vec::new();
+        (0..n)
+            .map(|i| {
+                let r = root(i);
+                roots.iter().position(|s| *s == r).unwrap_or_else(|| {
+                    roots.push(r);
+                    roots.len() - 1
+                })
+            })
+            .collect()
+    }
+}
+
+/// Agglomerative clustering of the series whose cell `(i, j)` of `distances` with `i < j` is the
+/// distance between the series `i` and `j`, repeatedly merging the two closest clusters under
+/// `linkage`, the first pair on ties.
+pub fn hierarchical(distances: &Matrix<f64>, linkage: Linkage) -> Dendrogram {
+    let n = distances.shape().0;
+    assert_eq!(distances.shape(), (n, n), "Distances should be square");
+    let mut d = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| distances[(i.min(j), i.max(j))])
+                .collect::<Vec<f64>>()
+        })
+        .collect::<Vec<Vec<f64>>>();
+    let mut active = (0..n)
+        .map(|i| Some((i, 1)))
+        .collect::<Vec<Option<(usize, usize)>>>();
+    let mut merges = Vec::new();
+    for step in 0..n.saturating_sub(1) {
+        let slots = (0..n)
+            .filter(|i| active[*i].is_some())
+            .collect::<Vec<usize>>();
+        let (i, j) = slots
+            .iter()
+            .enumerate()
+            .flat_map(|(k, i)| slots[k + 1..].iter().map(move |j| (*i, *j)))
+            .reduce(|best, pair| {
+                if d[pair.0][pair.1] < d[best.0][best.1] {
+                    pair
+                } else {
+                    best
+                }
+            })
+            .unwrap();
+        let ((ci, si), (cj, sj)) = (active[i].unwrap(), active[j].unwrap());
+        merges.push(Merge {
+            clusters: (ci.min(cj), ci.max(cj)),
+            distance: d[i][j],
+            size: si + sj,
+        });
+        for k in slots.into_iter().filter(|k| *k != i && *k != j) {
+            let merged = match linkage {
+                Linkage::Single => d[i][k].min(d[j][k]),
+                Linkage::Complete => d[i][k].max(d[j][k]),
+                Linkage::Average => (si as f64 * d[i][k] + sj as f64 * d[j][k]) / (si + sj) as f64,
+            };
+            d[i][k] = merged;
+            d[k][i] = merged;
+        }
+        active[i] = Some((n + step, si + sj));
+        active[j] = None;
+    }
+    Dendrogram { merges }
+}
+
+/// `clusters` series of `series` far apart from each other, each one the farthest from the
+/// previous ones starting from the first series.
+fn farthest_first(series: &[Vec<f64>], clusters: usize) -> Vec<Vec<f64>> {
+    let mut centroids = vec![series[0].clone()];
+    while centroids.len() < clusters {
+        let farthest = (0..series.len())
+            .map(|k| {
+                let nearest = centroids
+                    .iter()
+                    .map(|c| squared(c, &series[k]).distance())
+                    .fold(f64::INFINITY, f64::min);
+                (k, nearest)
+            })
+            .reduce(|best, candidate| {
+                if candidate.1 > best.1 {
+                    candidate
+                } else {
+                    best
+                }
+            })
+            .unwrap();
+        centroids.push(series[farthest.0].clone());
+    }
+    centroids
+}
+
+/// Membership weights of a series at the distances `distances` to the centroids, shared equally
+/// by the centroids at a distance of zero if any.
+fn memberships_of(distances: &[f64], fuzziness: f64) -> Vec<f64> {
+    let zeros = distances.iter().filter(|d| **d == 0.0).count();
+    if zeros > 0 {
+        return distances
+            .iter()
+            .map(|d| if *d == 0.0 { 1.0 / zeros as f64 } else { 0.0 })
+            .collect();
+    }
+    // The distances are sums of squared differences, hence the exponent 1 / (m - 1).
+    let exponent = 1.0 / (fuzziness - 1.0);
+    distances
+        .iter()
+        .map(|d| 1.0 / distances.iter().map(|e| powf(d / e, exponent)).sum::<f64>())
+        .collect()
+}
+
+#[cfg(not(feature = "deterministic"))]
+fn powf(x: f64, y: f64) -> f64 {
+    x.powf(y)
+}
+
+/// Squared-difference dynamic time warping between `a` and `b`.
+pub(crate) fn squared(a: &[f64], b: &[f64]) -> DynamicTimeWarping<f64> {
+    DynamicTimeWarping::with_closure(a, b, |x: &f64, y: &f64| (x - y) * (x - y))
+}
+
+/// Alignment of every series of `series` to `average`.
+pub(crate) fn align(average: &[f64], series: &[Vec<f64>]) -> Vec<MemberAlignment> {
+    series
+        .iter()
+        .map(|s| {
+            let dtw = squared(average, s);
+            MemberAlignment {
+                path: dtw.path(),
+                distance: dtw.distance(),
+            }
+        })
+        .collect()
+}
+
+/// Average whose elements are the means of the elements of `series` matched with them by
+/// `alignments`, each series weighted by `weights` if any.
+pub(crate) fn update(
+    average: &[f64],
+    series: &[Vec<f64>],
+    alignments: &[MemberAlignment],
+    weights: Option<&[f64]>,
+) -> Vec<f64> {
+    let mut sums = vec![(0.0, 0.0); average.len()];
+    for (k, (s, alignment)) in series.iter().zip(alignments).enumerate() {
+        let weight = weights.map_or(1.0, |w| w[k]);
+        for (i, j) in &alignment.path {
+            sums[*i].0 += weight * s[*j];
+            sums[*i].1 += weight;
+        }
+    }
+    sums.iter()
+        .zip(average)
+        .map(|((sum, weight), a)| if *weight > 0.0 { sum / weight } else { *a })
+        .collect()
+}
+
+/// Index of the series of `series` with the least total distance to the others.
+fn medoid(series: &[Vec<f64>]) -> usize {
+    (0..series.len())
+        .map(|k| {
+            let total = series
+                .iter()
+                .map(|s| squared(&series[k], s).distance())
+                .sum::<f64>();
+            (k, total)
+        })
+        .reduce(|best, candidate| {
+            if candidate.1 < best.1 {
+                candidate
+            } else {
+                best
+            }
+        })
+        .map(|(k, _)| k)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dba, dba_report, fuzzy_c_means, hierarchical, KMeans, KMedoids, Linkage};
+    use crate::{pairwise::distance_matrix, Parameters};
+
+    #[test]
+    fn dba_of_shifted_bumps() {
+        let bump = |shift: usize| {
+            (0..20)
+                .map(|i| {
+                    if (shift..shift + 4).contains(&i) {
+                        1.0
+                    } else {
+                        0.0
+                    }
+                })
+                .collect::<Vec<f64>>()
+        };
+        let series = vec![bump(5), bump(7), bump(9)];
+        let report = dba_report(&series, 10);
+        assert!(report.iterations <= 10);
+        assert_eq!(report.members.len(), 3);
+        assert!(report.members.iter().all(|m| m.distance == 0.0));
+        assert_eq!(report.average, dba(&series, 10));
+        assert_eq!(report.average.iter().sum::<f64>(), 4.0);
+        let outlier = vec![bump(5), bump(7), vec![3.0; 20]];
+        let report = dba_report(&outlier, 10);
+        assert!(report.members[2].distance > report.members[0].distance);
+    }
+
+    #[test]
+    fn k_means_of_shifted_shapes() {
+        let shape = |shift: usize, height: f64| {
+            (0..12)
+                .map(|i| match i >= shift && i < shift + 3 {
+                    true => height,
+                    false => 0.0,
+                })
+                .collect::<Vec<f64>>()
+        };
+        let series = vec![
+            shape(2, 1.0),
+            shape(6, 5.0),
+            shape(4, 1.0),
+            shape(3, 5.0),
+            shape(7, 1.0),
+        ];
+        let clusters = KMeans::new(2, 10).fit(&series);
+        assert!(clusters.iterations <= 10);
+        assert_eq!(clusters.labels, [0, 1, 0, 1, 0]);
+        assert_eq!(clusters.centroids.len(), 2);
+        assert_eq!(
+            clusters.centroids[0].iter().cloned().fold(0.0, f64::max),
+            1.0
+        );
+        assert_eq!(
+            clusters.centroids[1].iter().cloned().fold(0.0, f64::max),
+            5.0
+        );
+    }
+
+    #[test]
+    fn medoids_and_dendrogram() {
+        let level = |x: f64| vec![x; 6];
+        let series = [level(0.0), level(9.0), level(0.5), level(10.0), level(1.0)];
+        let slices = series.iter().map(|s| &s[..]).collect::<Vec<&[f64]>>();
+        let distances = distance_matrix::<f64, f64>(&slices, Parameters::new());
+        let medoids = KMedoids::new(2, 10).fit(&distances);
+        assert_eq!(medoids.medoids, [2, 1]);
+        assert_eq!(medoids.labels, [0, 1, 0, 1, 0]);
+        assert_eq!(medoids.cost, 12.0);
+        let dendrogram = hierarchical(&distances, Linkage::Single);
+        let merges = dendrogram.merges();
+        assert_eq!(merges.len(), 4);
+        assert_eq!((merges[0].clusters, merges[0].distance), ((0, 2), 3.0));
+        assert_eq!((merges[1].clusters, merges[1].size), ((4, 5), 3));
+        assert_eq!(merges[3].distance, 48.0);
+        assert_eq!(dendrogram.labels(2), [0, 1, 0, 1, 0]);
+        assert_eq!(dendrogram.labels(5), [0, 1, 2, 3, 4]);
+        let complete = hierarchical(&distances, Linkage::Complete);
+        assert_eq!(complete.merges()[1].distance, 6.0);
+        assert_eq!(complete.merges()[3].distance, 60.0);
+    }
+
+    #[test]
+    fn fuzzy_memberships() {
+        let level = |x: f64| vec![x; 10];
+        let series = vec![level(0.0), level(0.2), level(10.0), level(10.2), level(5.1)];
+        let clusters = fuzzy_c_means(&series, 2, 2.0, 50);
+        for u in &clusters.memberships {
+            assert!(f64::abs(u.iter().sum::<f64>() - 1.0) < 1e-12);
+        }
+        let low = clusters.memberships[0]
+            .iter()
+            .enumerate()
+            .fold(0, |best, (c, u)| {
+                if *u > clusters.memberships[0][best] {
+                    c
+                } else {
+                    best
+                }
+            });
+        assert!(clusters.memberships[0][low] > 0.9 && clusters.memberships[1][low] > 0.9);
+        assert!(clusters.memberships[2][low] < 0.1 && clusters.memberships[3][low] < 0.1);
+        assert!(f64::abs(clusters.memberships[4][low] - 0.5) < 0.1);
+    }
+
+    #[cfg(feature = "deterministic")]
+    #[test]
+    fn portable_fuzzy_memberships() {
+        let level = |x: f64| vec![x; 10];
+        let series = vec![level(0.0), level(0.2), level(10.0), level(10.2), level(5.1)];
+        let clusters = fuzzy_c_means(&series, 2, 2.0, 50);
+        assert_eq!(
+            clusters.memberships[0],
+            [0.9952451833231382, 0.00475481667686175]
+        );
+        assert_eq!(
+            clusters.memberships[4],
+            [0.5000000000000002, 0.4999999999999998]
+        );
+    }
+}
@@ -0,0 +1,43 @@
+use std::fmt::Display;
+
+/// Error of the fallible constructors, e.g. [`DynamicTimeWarping::try_between`].
+///
+/// [`DynamicTimeWarping::try_between`]: crate::DynamicTimeWarping::try_between
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Error {
+    /// One of the sequences has no element.
+    EmptyInput,
+    /// No path connects the corners, e.g. because of the step pattern or the landmarks.
+    Unreachable,
+    /// The Sakoe-Chiba band is narrower than the given minimal width admitting the bottom-right
+    /// corner, see [`Restriction::minimal_band`].
+    ///
+    /// [`Restriction::minimal_band`]: crate::Restriction::minimal_band
+    BandTooNarrow(usize),
+    /// The slope of an Itakura parallelogram is not greater than one, see
+    /// [`Restriction::itakura`].
+    ///
+    /// [`Restriction::itakura`]: crate::Restriction::itakura
+    InvalidSlope,
+    /// A local cost is infinite or not a number.
+    NonFiniteCost,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::EmptyInput => write!(f, "Sequences should not be empty"),
+            Error::Unreachable => write!(f, "Infinit distance"),
+            Error::BandTooNarrow(minimal) => {
+                write!(
+                    f,
+                    "Band should be at least {minimal} wide to reach the corner"
+                )
+            }
+            Error::InvalidSlope => write!(f, "Itakura slope should be greater than one"),
+            Error::NonFiniteCost => write!(f, "Local costs should be finite"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -5,8 +5,8 @@ A Dynamic Time Warping (DTW) library for Rust
 Computation methods:
 - [x] Dynamic programming
 - [x] Dynamic programming with the Sakoe-Chuba Band
-- [ ] Dynamic programming with the Itakura Parallelogram (future plan)
-- [ ] FastDTW (future plan)
+- [x] Dynamic programming with the Itakura Parallelogram
+- [x] FastDTW
 
 ```
 use dtw_rs::{Algorithm, DynamicTimeWarping};
@@ -20,9 +20,39 @@ println!("Distance: {}, Path: {:?}", dtw.distance(), dtw.path());
 
 ```
 
+Determinism: costs are accumulated in a fixed order with ties broken in a fixed order of
+preference, using the basic IEEE 754 operations without fused multiply-add. The `deterministic`
+feature also replaces the platform's transcendental functions, making every result
+bit-identical across platforms.
+
 */
 
 mod algorithms;
+#[cfg(feature = "ndarray")]
+pub mod arrays;
+pub mod clustering;
+mod error;
+pub mod lower_bounds;
+pub mod metrics;
+pub mod multiscale;
+pub mod pairwise;
+pub mod path;
+#[cfg(feature = "plot")]
+pub mod plot;
+pub mod preprocess;
+pub mod search;
 mod traits;
-pub use algorithms::{DynamicTimeWarping, Restriction};
-pub use traits::{Algorithm, Distance, ParameterizedAlgorithm};
+#[cfg(feature = "verify")]
+pub mod verify;
+pub use algorithms::{
+    binary_distance, run_length_distance, run_length_encode, Anchored, Anytime, Bitmap, Budget,
+    Compact, Compensated, CostTable, Custom, Detection, DiffKind, DiffRun, DynamicTimeWarping,
+    EventAlignment, EventPair, FastDtw, FreeCorner, GestureRecognizer, Landmark, LocalContinuity,
+    Matrix, Missing, Multivariate, MultivariateAlignment, Normalization, OnlineDtw, Open,
+    Parameters, Restriction, Segmentation, Segmented, Similarity, SlopeWeighting, SoftDtw, Step,
+    StepPattern, Subsequence, TieBreak, Transform, Trim, Trimmed, Window,
+};
+#[cfg(feature = "simd")]
+pub use algorithms::{Lane, LaneCost};
+pub use error::Error;
+pub use traits::{Algorithm, Cost, Distance, ParameterizedAlgorithm};
@@ -0,0 +1,137 @@
+//! Lower bounds of the warped distance with the absolute differences as local costs, to prune
+//! the candidates of a search before aligning them.
+
+use std::collections::VecDeque;
+
+/// Upper and lower envelopes of a series, i.e. its maximum and minimum within a window around
+/// every index, computed once and reused across the candidates compared with the series.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Envelope {
+    upper: Vec<f64>,
+    lower: Vec<f64>,
+}
+
+impl Envelope {
+    /// Envelope of `series` within a window of `window` elements on each side of every index,
+    /// i.e. a Sakoe-Chiba band of width `window`, by the streaming algorithm of Lemire, "Faster
+    /// Retrieval with a Two-Pass Dynamic-Time-Warping Lower Bound".
+    pub fn new(series: &[f64], window: usize) -> Self {
+        Self {
+            upper: extremum(series, window, |x, y| x >= y),
+            lower: extremum(series, window, |x, y| x <= y),
+        }
+    }
+
+    /// Maximum of the series within the window around every index.
+    pub fn upper(&self) -> &[f64] {
+        &self.upper
+    }
+
+    /// Minimum of the series within the window around every index.
+    pub fn lower(&self) -> &[f64] {
+        &self.lower
+    }
+
+    /// Lower bound of Keogh on the distance between the series of the envelope and `candidate`
+    /// of the same length: the sum of the deviations of the candidate outside of the envelope.
+    pub fn lb_keogh(&self, candidate: &[f64]) -> f64 {
+        assert!(
+            candidate.len() == self.upper.len(),
+            "Candidate should have the length of the series"
+        );
+        candidate
+            .iter()
+            .zip(self.upper.iter().zip(&self.lower))
+            .map(|(c, (u, l))| (c - u).max(l - c).max(0.0))
+            .sum()
+    }
+}
+
+/// Lower bound of Keogh on the distance between `query` and `candidate` of the same length within
+/// a Sakoe-Chiba band of width `window`. See [`Envelope`] to reuse the envelope of the query.
+pub fn lb_keogh(query: &[f64], candidate: &[f64], window: usize) -> f64 {
+    Envelope::new(query, window).lb_keogh(candidate)
+}
+
+/// Lower bound of Kim on the distance between `a` and `b`, in constant time: the costs of the
+/// first and the last cells, which every path visits, and of the cheapest of the cells next to
+/// them when the sequences are long enough for these cells to be distinct.
+pub fn lb_kim(a: &[f64], b: &[f64]) -> f64 {
+    assert!(
+        !a.is_empty() && !b.is_empty(),
+        "Sequences should not be empty"
+    );
+    let (n, m) = (a.len(), b.len());
+    let d = |i: usize, j: usize| f64::abs(a[i] - b[j]);
+    if n == 1 || m == 1 {
+        // Every cell of the single row or column is visited.
+        return (0..n)
+            .flat_map(|i| (0..m).map(move |j| (i, j)))
+            .map(|(i, j)| d(i, j))
+            .sum();
+    }
+    let mut bound = d(0, 0) + d(n - 1, m - 1);
+    if n >= 4 && m >= 4 {
+        bound += d(0, 1).min(d(1, 0)).min(d(1, 1));
+        bound += d(n - 1, m - 2).min(d(n - 2, m - 1)).min(d(n - 2, m - 2));
+    }
+    bound
+}
+
+/// Extremum of `series` within `window` elements on each side of every index, where `dominates`
+/// tells whether an element supersedes another one.
+fn extremum(series: &[f64], window: usize, dominates: impl Fn(f64, f64) -> bool) -> Vec<f64> {
+    let n = series.len();
+    let mut candidates: VecDeque<usize> = VecDeque::new();
+    let mut extrema = Vec::with_capacity(n);
+    let mut next = 0;
+    for i in 0..n {
+        while next < n && next <= i + window {
+            while candidates
+                .back()
+                .is_some_and(|k| dominates(series[next], series[*k]))
+            {
+                candidates.pop_back();
+            }
+            candidates.push_back(next);
+            next += 1;
+        }
+        while candidates.front().is_some_and(|k| k + window < i) {
+            candidates.pop_front();
+        }
+        extrema.push(series[candidates[0]]);
+    }
+    extrema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{lb_keogh, lb_kim, Envelope};
+    use crate::{Algorithm, DynamicTimeWarping, Restriction};
+
+    #[test]
+    fn envelope_extrema() {
+        let series = [1.0, 3.0, 9.0, 2.0, 1.0, 4.0, 4.0, 0.0];
+        let envelope = Envelope::new(&series, 1);
+        assert_eq!(envelope.upper(), [3.0, 9.0, 9.0, 9.0, 4.0, 4.0, 4.0, 4.0]);
+        assert_eq!(envelope.lower(), [1.0, 1.0, 2.0, 1.0, 1.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn lower_bounds_below_distance() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0, 4.0, 4.0, 0.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0, 5.0, 1.0];
+        for window in 0..4 {
+            let dtw = DynamicTimeWarping::with_param(&a, &b, Restriction::Band(window));
+            assert!(lb_keogh(&a, &b, window) <= dtw.distance());
+            assert!(lb_kim(&a, &b) <= dtw.distance());
+        }
+        assert_eq!(lb_kim(&a, &b), 1.0 + 1.0 + 1.0 + 1.0);
+        assert_eq!(lb_keogh(&a, &a, 2), 0.0);
+        let c = [2.0, 5.0, 1.0];
+        assert_eq!(
+            lb_kim(&[1.0], &c),
+            DynamicTimeWarping::between(&[1.0], &c).distance()
+        );
+    }
+}
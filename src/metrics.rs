@@ -0,0 +1,166 @@
+//! Local distances between scalar or vector observations.
+//!
+//! Every distance is a function usable as a distance closure, e.g.
+//! `DynamicTimeWarping::with_closure(&a, &b, metrics::euclidean)`, and a marker type
+//! implementing [`Metric`] for the code generic over the distance, e.g. `Euclidean::distance`.
+
+/// Observation made of one or more values, e.g. a scalar or a vector.
+pub trait Observation {
+    /// Values of the observation.
+    fn values(&self) -> &[f64];
+}
+
+impl Observation for f64 {
+    fn values(&self) -> &[f64] {
+        std::slice::from_ref(self)
+    }
+}
+
+impl Observation for [f64] {
+    fn values(&self) -> &[f64] {
+        self
+    }
+}
+
+impl<const N: usize> Observation for [f64; N] {
+    fn values(&self) -> &[f64] {
+        self
+    }
+}
+
+impl Observation for Vec<f64> {
+    fn values(&self) -> &[f64] {
+        self
+    }
+}
+
+/// Distance between two observations of a common dimension.
+pub trait Metric {
+    /// Distance between the observations `a` and `b`.
+    fn distance<T: Observation + ?Sized>(a: &T, b: &T) -> f64;
+}
+
+/// Pairs of values of the observations `a` and `b`.
+fn pairs<'a, T: Observation + ?Sized>(a: &'a T, b: &'a T) -> impl Iterator<Item = (f64, f64)> + 'a {
+    assert_eq!(
+        a.values().len(),
+        b.values().len(),
+        "Observations should have the same dimension"
+    );
+    a.values().iter().copied().zip(b.values().iter().copied())
+}
+
+/// Square root of the sum of the squared differences.
+pub fn euclidean<T: Observation + ?Sized>(a: &T, b: &T) -> f64 {
+    squared_euclidean(a, b).sqrt()
+}
+
+/// Sum of the squared differences.
+pub fn squared_euclidean<T: Observation + ?Sized>(a: &T, b: &T) -> f64 {
+    pairs(a, b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+/// Sum of the absolute differences.
+pub fn manhattan<T: Observation + ?Sized>(a: &T, b: &T) -> f64 {
+    pairs(a, b).map(|(x, y)| f64::abs(x - y)).sum()
+}
+
+/// Greatest absolute difference.
+pub fn chebyshev<T: Observation + ?Sized>(a: &T, b: &T) -> f64 {
+    pairs(a, b)
+        .map(|(x, y)| f64::abs(x - y))
+        .fold(0.0, f64::max)
+}
+
+/// One minus the cosine of the angle between the observations, in `[0, 2]`. A zero observation
+/// is at a distance of zero from another zero observation and of one from any other one.
+pub fn cosine<T: Observation + ?Sized>(a: &T, b: &T) -> f64 {
+    1.0 - cosine_similarity(a, b)
+}
+
+/// Angle between the observations divided by pi, in `[0, 1]`, which unlike [`cosine`] satisfies
+/// the triangle inequality. Zero observations are handled like by [`cosine`].
+pub fn angular<T: Observation + ?Sized>(a: &T, b: &T) -> f64 {
+    let cosine = cosine_similarity(a, b).clamp(-1.0, 1.0);
+    #[cfg(not(feature = "deterministic"))]
+    let angle = cosine.acos();
+    #[cfg(feature = "deterministic")]
+    let angle = crate::algorithms::portable_acos(cosine);
+    angle / std::f64::consts::PI
+}
+
+/// Cosine of the angle between the observations, one between zero observations and zero between
+/// a zero observation and any other one.
+fn cosine_similarity<T: Observation + ?Sized>(a: &T, b: &T) -> f64 {
+    let (dot, norm_a, norm_b) = pairs(a, b).fold((0.0, 0.0, 0.0), |(dot, na, nb), (x, y)| {
+        (dot + x * y, na + x * x, nb + y * y)
+    });
+    match (norm_a == 0.0, norm_b == 0.0) {
+        (true, true) => 1.0,
+        (true, false) | (false, true) => 0.0,
+        (false, false) => dot / (norm_a.sqrt() * norm_b.sqrt()),
+    }
+}
+
+macro_rules! impl_metric {
+    ($($t:ident => $f:ident),*) => {
+        $(
+            #[doc = concat!("Marker type of [`", stringify!($f), "`].")]
+            #[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+            pub struct $t;
+
+            impl Metric for $t {
+                fn distance<T: Observation + ?Sized>(a: &T, b: &T) -> f64 {
+                    $f(a, b)
+                }
+            }
+        )*
+    };
+}
+
+impl_metric!(
+    Euclidean => euclidean,
+    SquaredEuclidean => squared_euclidean,
+    Manhattan => manhattan,
+    Chebyshev => chebyshev,
+    Cosine => cosine,
+    Angular => angular
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{angular, chebyshev, cosine, euclidean, manhattan, squared_euclidean};
+    use super::{Euclidean, Metric};
+    use crate::{Algorithm, DynamicTimeWarping};
+
+    #[test]
+    fn vector_and_scalar_metrics() {
+        let (a, b) = ([0.0, 3.0], [4.0, 0.0]);
+        assert_eq!(euclidean(&a, &b), 5.0);
+        assert_eq!(squared_euclidean(&a, &b), 25.0);
+        assert_eq!(manhattan(&a, &b), 7.0);
+        assert_eq!(chebyshev(&a, &b), 4.0);
+        assert_eq!(cosine(&a, &b), 1.0);
+        assert_eq!(angular(&a, &b), 0.5);
+        assert_eq!(cosine(&[0.0, 0.0], &[0.0, 0.0]), 0.0);
+        assert_eq!(angular(&[3.0, 0.0], &[2.0, 0.0]), 0.0);
+        assert_eq!(euclidean(&-1.0, &2.0), 3.0);
+        assert_eq!(cosine(&-1.0, &2.0), 2.0);
+        let (a, b) = ([1.0, 3.0, 9.0, 2.0, 1.0], [2.0, 0.0, 0.0, 8.0, 7.0, 2.0]);
+        let scalars = DynamicTimeWarping::with_closure(&a, &b, euclidean);
+        assert_eq!(
+            scalars.distance(),
+            DynamicTimeWarping::between(&a, &b).distance()
+        );
+        let vectors = [vec![0.0, 1.0], vec![1.0, 1.0]];
+        let dtw = DynamicTimeWarping::with_closure(&vectors, &vectors, Euclidean::distance);
+        assert_eq!(dtw.distance(), 0.0);
+    }
+
+    #[cfg(feature = "deterministic")]
+    #[test]
+    fn portable_angular() {
+        assert_eq!(angular(&[1.0, 2.0], &[3.0, 1.0]), 0.25000000000000006);
+        assert_eq!(angular(&[1.0, 0.0], &[-1.0, 0.0]), 1.0);
+    }
+}
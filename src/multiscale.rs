@@ -0,0 +1,497 @@
+//! Multi-resolution representations of sequences for coarse-to-fine alignments.
+
+use crate::{preprocess::paa, Algorithm, DynamicTimeWarping, Restriction, Window};
+
+/// Method used to halve the resolution of a sequence.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Downsampling {
+    /// Piecewise aggregate approximation: the mean of every pair of elements, i.e. [`paa`] of
+    /// the whole chunks.
+    #[default]
+    Paa,
+    /// Every other element, starting from the first one.
+    Decimation,
+}
+
+/// Run of elements summarized by their mean in a piecewise-constant approximation.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Segment {
+    /// Index of the first element of the segment.
+    pub start: usize,
+    /// Number of elements of the segment.
+    pub len: usize,
+    /// Mean of the elements of the segment.
+    pub mean: f64,
+}
+
+/// Approximate method and the parameters it was run with.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Method {
+    /// [`coarse_to_fine`].
+    CoarseToFine { factor: usize, radius: usize },
+    /// [`blocked`].
+    Blocked { tolerance: f64, radius: usize },
+    /// [`fast_dtw`].
+    FastDtw { radius: usize },
+}
+
+/// Alignment computed by an approximate method, carrying the method and a bound on its
+/// deviation from the exact dynamic time warping.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Approximate {
+    alignment: DynamicTimeWarping<f64>,
+    method: Method,
+    lower_bound: f64,
+}
+
+/// Successively halved versions of a sequence, from the original one at level `0` to the
+/// coarsest one. It can be built once and shared across many alignments against the same
+/// sequence, see [`fast_dtw_with_pyramid`] and [`coarse_to_fine_with_pyramid`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct Pyramid {
+    levels: Vec<Vec<f64>>,
+    downsampling: Downsampling,
+}
+
+impl Downsampling {
+    /// Halve the resolution of `series`. An odd trailing element is kept as is.
+    pub fn apply(&self, series: &[f64]) -> Vec<f64> {
+        self.apply_by(series, 2)
+    }
+
+    /// Divide the resolution of `series` by `factor`. A shorter trailing chunk is reduced as is.
+    pub fn apply_by(&self, series: &[f64], factor: usize) -> Vec<f64> {
+        assert!(factor > 0, "Factor should be greater than zero");
+        match self {
+            Downsampling::Paa => {
+                let whole = series.len() / factor * factor;
+                let mut reduced = match whole {
+                    0 => Vec::new(),
+                    _ => paa(&series[..whole], whole / factor),
+                };
+                if whole < series.len() {
+                    reduced.extend(paa(&series[whole..], 1));
+                }
+                reduced
+            }
+            Downsampling::Decimation => series.iter().step_by(factor).copied().collect(),
+        }
+    }
+}
+
+/// Align `a` and `b` downsampled by `factor`, project the coarse path onto the full resolution,
+/// and refine it exactly within a corridor of `radius` cells around the projection.
+pub fn coarse_to_fine(a: &[f64], b: &[f64], factor: usize, radius: usize) -> Approximate {
+    let coarse = DynamicTimeWarping::between(
+        &Downsampling::Paa.apply_by(a, factor),
+        &Downsampling::Paa.apply_by(b, factor),
+    );
+    refine(a, b, &coarse.path(), factor, radius)
+}
+
+/// [`coarse_to_fine`] against the prebuilt pyramid `reference`, e.g. shared by many queries: `a`
+/// is halved `level` times with the downsampling of the pyramid and aligned with its level
+/// `level`, whose path is refined within `radius` cells at the full resolution.
+pub fn coarse_to_fine_with_pyramid(
+    a: &[f64],
+    reference: &Pyramid,
+    level: usize,
+    radius: usize,
+) -> Approximate {
+    assert!(level < reference.len(), "Level should be in the pyramid");
+    let coarse = (0..level).fold(a.to_vec(), |series, _| {
+        reference.downsampling.apply(&series)
+    });
+    let coarse = DynamicTimeWarping::between(&coarse, reference.level(level));
+    let b = reference.level(0);
+    let window = Window::from_path(&coarse.path(), (a.len(), b.len()), 1 << level, radius);
+    Approximate::new(
+        a,
+        b,
+        DynamicTimeWarping::with_param(a, b, Restriction::Window(window)),
+        Method::CoarseToFine {
+            factor: 1 << level,
+            radius,
+        },
+    )
+}
+
+/// Project the path `coarse` between `a` and `b` downsampled by `factor`, e.g. with
+/// [`Downsampling::apply_by`], onto the full resolution, and refine it exactly within a corridor
+/// of `radius` cells around the projection. The coarse path may come from any aligner, and
+/// [`Window::from_path`] gives the corridor alone.
+pub fn refine(
+    a: &[f64],
+    b: &[f64],
+    coarse: &[(usize, usize)],
+    factor: usize,
+    radius: usize,
+) -> Approximate {
+    let window = Window::from_path(coarse, (a.len(), b.len()), factor, radius);
+    Approximate::new(
+        a,
+        b,
+        DynamicTimeWarping::with_param(a, b, Restriction::Window(window)),
+        Method::CoarseToFine { factor, radius },
+    )
+}
+
+/// FastDTW (Salvador and Chan, "Toward Accurate Dynamic Time Warping in Linear Time and Space"):
+/// `a` and `b` are halved by averaging pairs of elements until the shorter one has at most
+/// `radius + 2` elements, aligned exactly at the coarsest resolution, and the path is projected
+/// onto every finer resolution and refined within `radius` cells around the projection.
+/// [`FastDtw`](crate::FastDtw) aligns sequences of arbitrary elements instead.
+pub fn fast_dtw(a: &[f64], b: &[f64], radius: usize) -> Approximate {
+    fast_levels(
+        &Pyramid::new(a, Downsampling::Paa, radius + 2),
+        &Pyramid::new(b, Downsampling::Paa, radius + 2),
+        radius,
+    )
+}
+
+/// [`fast_dtw`] against the prebuilt pyramid `reference`, e.g. shared by many queries, `a` being
+/// halved with the downsampling of the pyramid. The coarsest alignment is that of the coarsest
+/// level shared by both pyramids.
+pub fn fast_dtw_with_pyramid(a: &[f64], reference: &Pyramid, radius: usize) -> Approximate {
+    fast_levels(
+        &Pyramid::new(a, reference.downsampling, radius + 2),
+        reference,
+        radius,
+    )
+}
+
+/// FastDTW over the levels shared by the pyramids `a` and `b`, from the coarsest one.
+fn fast_levels(a: &Pyramid, b: &Pyramid, radius: usize) -> Approximate {
+    let depth = a.len().min(b.len());
+    let mut alignment = DynamicTimeWarping::between(a.level(depth - 1), b.level(depth - 1));
+    for level in (0..depth - 1).rev() {
+        let (la, lb) = (a.level(level), b.level(level));
+        let window = Window::from_path(&alignment.path(), (la.len(), lb.len()), 2, radius);
+        alignment = DynamicTimeWarping::with_param(la, lb, Restriction::Window(window));
+    }
+    Approximate::new(
+        a.level(0),
+        b.level(0),
+        alignment,
+        Method::FastDtw { radius },
+    )
+}
+
+/// Piecewise-constant approximation of `series`, starting a new segment whenever an element
+/// deviates from the mean of the current one by more than `tolerance`.
+pub fn segments(series: &[f64], tolerance: f64) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+    for (i, x) in series.iter().enumerate() {
+        match segments.last_mut() {
+            Some(s) if f64::abs(x - s.mean) <= tolerance => {
+                s.mean += (x - s.mean) / (s.len + 1) as f64;
+                s.len += 1;
+            }
+            _ => segments.push(Segment {
+                start: i,
+                len: 1,
+                mean: *x,
+            }),
+        }
+    }
+    segments
+}
+
+/// Align the segments of `a` and `b` by their means, weighted by the longer of their durations,
+/// project the path onto the full resolution, and refine it exactly within a corridor of
+/// `radius` cells around the projection. Suited to segment-structured signals such as setpoints
+/// or regimes.
+pub fn blocked(a: &[f64], b: &[f64], tolerance: f64, radius: usize) -> Approximate {
+    let (sa, sb) = (segments(a, tolerance), segments(b, tolerance));
+    let coarse = DynamicTimeWarping::with_closure(&sa, &sb, |x, y| {
+        f64::abs(x.mean - y.mean) * x.len.max(y.len) as f64
+    });
+    let bounds = |segments: &[Segment], len: usize| {
+        segments
+            .iter()
+            .map(|s| s.start)
+            .chain(std::iter::once(len))
+            .collect::<Vec<usize>>()
+    };
+    let window = Window::from_segments(
+        &coarse.path(),
+        &bounds(&sa, a.len()),
+        &bounds(&sb, b.len()),
+        radius,
+    );
+    Approximate::new(
+        a,
+        b,
+        DynamicTimeWarping::with_param(a, b, Restriction::Window(window)),
+        Method::Blocked { tolerance, radius },
+    )
+}
+
+impl Approximate {
+    fn new(a: &[f64], b: &[f64], alignment: DynamicTimeWarping<f64>, method: Method) -> Self {
+        #[cfg(feature = "verify")]
+        crate::verify::record(a, b, method, alignment.distance());
+        Self {
+            alignment,
+            method,
+            lower_bound: lower_bound(a, b).max(lower_bound(b, a)),
+        }
+    }
+
+    /// Approximate alignment.
+    pub fn alignment(&self) -> &DynamicTimeWarping<f64> {
+        &self.alignment
+    }
+
+    /// Method and parameters used to compute the alignment.
+    pub fn method(&self) -> Method {
+        self.method
+    }
+
+    /// Approximate warped distance, an upper bound of the exact one.
+    pub fn distance(&self) -> f64 {
+        self.alignment.distance()
+    }
+
+    /// Approximate warped path.
+    pub fn path(&self) -> Vec<(usize, usize)> {
+        self.alignment.path()
+    }
+
+    /// Lower bound of the exact warped distance.
+    pub fn lower_bound(&self) -> f64 {
+        self.lower_bound
+    }
+
+    /// Bound on the excess of the approximate distance over the exact one.
+    pub fn error_bound(&self) -> f64 {
+        (self.distance() - self.lower_bound).max(0.0)
+    }
+}
+
+/// Lower bound of Yi et al.: every element of `a` is matched at least once, at no less than its
+/// distance to the range of `b`.
+fn lower_bound(a: &[f64], b: &[f64]) -> f64 {
+    let (min, max) = b
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| {
+            (min.min(*x), max.max(*x))
+        });
+    a.iter().map(|x| (min - x).max(x - max).max(0.0)).sum()
+}
+
+impl Pyramid {
+    /// Pyramid of `series` halved using `downsampling` until its length is not greater than
+    /// `min_len`.
+    pub fn new(series: &[f64], downsampling: Downsampling, min_len: usize) -> Self {
+        let min_len = min_len.max(1);
+        let mut levels = vec![series.to_vec()];
+        while let Some(last) = levels.last().filter(|l| l.len() > min_len) {
+            let next = downsampling.apply(last);
+            levels.push(next);
+        }
+        Self {
+            levels,
+            downsampling,
+        }
+    }
+
+    /// Number of levels, including the original sequence.
+    pub fn len(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Whether the pyramid has no level. Always `false`, since the original sequence is a level.
+    pub fn is_empty(&self) -> bool {
+        self.levels.is_empty()
+    }
+
+    /// Sequence at level `level`, where level `0` is the original sequence.
+    pub fn level(&self, level: usize) -> &[f64] {
+        &self.levels[level]
+    }
+
+    /// Coarsest sequence of the pyramid.
+    pub fn coarsest(&self) -> &[f64] {
+        self.levels.last().unwrap()
+    }
+
+    /// Method used to build the pyramid.
+    pub fn downsampling(&self) -> Downsampling {
+        self.downsampling
+    }
+
+    /// Iterate over the levels, from the original sequence to the coarsest one.
+    pub fn iter(&self) -> impl Iterator<Item = &[f64]> {
+        self.levels.iter().map(Vec::as_slice)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        blocked, coarse_to_fine, coarse_to_fine_with_pyramid, fast_dtw, fast_dtw_with_pyramid,
+        refine, segments, Downsampling, Method, Pyramid, Segment,
+    };
+    use crate::{Algorithm, DynamicTimeWarping};
+
+    #[test]
+    fn downsampling() {
+        let series = [1.0, 3.0, 5.0, 7.0, 9.0];
+        assert_eq!(Downsampling::Paa.apply(&series), [2.0, 6.0, 9.0]);
+        assert_eq!(Downsampling::Decimation.apply(&series), [1.0, 5.0, 9.0]);
+        assert_eq!(Downsampling::Paa.apply_by(&series, 3), [3.0, 8.0]);
+    }
+
+    #[test]
+    fn coarse_to_fine_matches_exact() {
+        let a = (0..40)
+            .map(|i| f64::sin(i as f64 / 4.0))
+            .collect::<Vec<f64>>();
+        let b = (0..50)
+            .map(|i| f64::sin(i as f64 / 5.0))
+            .collect::<Vec<f64>>();
+        let exact = DynamicTimeWarping::between(&a, &b);
+        let approx = coarse_to_fine(&a, &b, 4, 2);
+        assert!(approx.distance() >= exact.distance());
+        assert!(approx.distance() - exact.distance() < 1e-9);
+        assert_eq!(*approx.path().first().unwrap(), (0, 0));
+        assert_eq!(*approx.path().last().unwrap(), (39, 49));
+        assert_eq!(
+            approx.method(),
+            Method::CoarseToFine {
+                factor: 4,
+                radius: 2
+            }
+        );
+        assert!(approx.lower_bound() <= exact.distance());
+        assert!(approx.distance() - approx.error_bound() <= exact.distance());
+    }
+
+    #[test]
+    fn refine_custom_coarse_path() {
+        let a = (0..40)
+            .map(|i| f64::sin(i as f64 / 4.0))
+            .collect::<Vec<f64>>();
+        let b = (0..50)
+            .map(|i| f64::sin(i as f64 / 5.0))
+            .collect::<Vec<f64>>();
+        let exact = DynamicTimeWarping::between(&a, &b);
+        let (ca, cb) = (
+            Downsampling::Decimation.apply_by(&a, 5),
+            Downsampling::Decimation.apply_by(&b, 5),
+        );
+        let coarse = DynamicTimeWarping::with_closure(&ca, &cb, |x, y| (x - y) * (x - y));
+        let refined = refine(&a, &b, &coarse.path(), 5, 3);
+        assert!(refined.distance() - exact.distance() < 1e-9);
+        assert_eq!(*refined.path().last().unwrap(), (39, 49));
+        let handmade = [
+            (0, 0),
+            (1, 1),
+            (2, 2),
+            (2, 3),
+            (3, 4),
+            (4, 5),
+            (5, 6),
+            (6, 7),
+            (7, 8),
+            (7, 9),
+        ];
+        let coarse = refine(&a, &b, &handmade, 5, 0);
+        assert!(coarse.distance() >= exact.distance());
+        assert_eq!(
+            coarse_to_fine(&a, &b, 4, 2),
+            refine(
+                &a,
+                &b,
+                &DynamicTimeWarping::between(
+                    &Downsampling::Paa.apply_by(&a, 4),
+                    &Downsampling::Paa.apply_by(&b, 4)
+                )
+                .path(),
+                4,
+                2
+            )
+        );
+    }
+
+    #[test]
+    fn fast_dtw_averages_pairs() {
+        let a = (0..300)
+            .map(|i| f64::sin(i as f64 / 20.0))
+            .collect::<Vec<f64>>();
+        let b = (0..250)
+            .map(|i| f64::sin(i as f64 / 15.0 + 0.5))
+            .collect::<Vec<f64>>();
+        let exact = DynamicTimeWarping::between(&a, &b);
+        let approx = fast_dtw(&a, &b, 4);
+        assert_eq!(approx.method(), Method::FastDtw { radius: 4 });
+        assert!(approx.distance() >= exact.distance() - 1e-9);
+        assert!(approx.distance() <= 1.05 * exact.distance());
+        assert!(approx.distance() - approx.error_bound() <= exact.distance());
+        assert_eq!(*approx.path().last().unwrap(), (299, 249));
+        let wide = fast_dtw(&a[..50], &b[..40], 100);
+        assert_eq!(
+            wide.path(),
+            DynamicTimeWarping::between(&a[..50], &b[..40]).path()
+        );
+    }
+
+    #[test]
+    fn pyramid_levels() {
+        let series = (0..10).map(|i| i as f64).collect::<Vec<f64>>();
+        let pyramid = Pyramid::new(&series, Downsampling::Paa, 2);
+        assert_eq!(pyramid.len(), 4);
+        assert_eq!(pyramid.level(0), series);
+        assert_eq!(pyramid.level(1), [0.5, 2.5, 4.5, 6.5, 8.5]);
+        assert_eq!(pyramid.level(2), [1.5, 5.5, 8.5]);
+        assert_eq!(pyramid.coarsest(), [3.5, 8.5]);
+        let a = (0..40)
+            .map(|i| f64::sin(i as f64 / 4.0))
+            .collect::<Vec<f64>>();
+        let b = (0..50)
+            .map(|i| f64::sin(i as f64 / 5.0))
+            .collect::<Vec<f64>>();
+        let exact = DynamicTimeWarping::between(&a, &b);
+        let reference = Pyramid::new(&b, Downsampling::Paa, 6);
+        for query in [&a[..], &a[5..35]] {
+            assert_eq!(
+                fast_dtw_with_pyramid(query, &reference, 4),
+                fast_dtw(query, &b, 4)
+            );
+        }
+        let approx = coarse_to_fine_with_pyramid(&a, &reference, 2, 2);
+        assert!(approx.distance() - exact.distance() < 1e-9);
+        assert_eq!(*approx.path().last().unwrap(), (39, 49));
+        assert_eq!(
+            approx.method(),
+            Method::CoarseToFine {
+                factor: 4,
+                radius: 2
+            }
+        );
+    }
+
+    #[test]
+    fn blocked_segments() {
+        let a = [0.0, 0.1, 0.0, 5.0, 5.1, 4.9, 5.0, 1.0, 1.0];
+        let b = [0.0, 0.0, 0.0, 0.1, 5.0, 5.0, 1.1, 1.0];
+        let sa = segments(&a, 0.2);
+        assert_eq!(sa.len(), 3);
+        assert_eq!(sa[1].start, 3);
+        assert_eq!(sa[1].len, 4);
+        assert!(f64::abs(sa[1].mean - 5.0) < 1e-9);
+        assert_eq!(
+            segments(&[1.0, 1.0, 2.0], 0.0)[1],
+            Segment {
+                start: 2,
+                len: 1,
+                mean: 2.0
+            }
+        );
+        let exact = DynamicTimeWarping::between(&a, &b);
+        let approx = blocked(&a, &b, 0.2, 1);
+        assert!(approx.distance() - exact.distance() < 1e-9);
+        assert_eq!(*approx.path().last().unwrap(), (8, 7));
+    }
+}
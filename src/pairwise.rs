@@ -0,0 +1,206 @@
+//! Distances between every pair of a set of series, e.g. for clustering.
+
+use std::ops::Add;
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{Algorithm, Cost, Distance, DynamicTimeWarping, Matrix, Parameters};
+
+/// Matrix whose cell `(i, j)` is the warped distance from `series[i]` to `series[j]` with the
+/// hyper-parameters `hyper_parameters`.
+///
+/// Every ordered pair is aligned, such that the matrix is exact for asymmetric step patterns or
+/// landmarks. See [`upper_triangle`] to align each unordered pair once.
+pub fn distance_matrix<T, D>(series: &[&[T]], hyper_parameters: impl Into<Parameters>) -> Matrix<D>
+where
+    T: Distance<D>,
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost,
+{
+    distance_matrix_with_closure(series, |a, b| a.distance(b), hyper_parameters)
+}
+
+/// [`distance_matrix`] using the distance closure `distance`.
+pub fn distance_matrix_with_closure<T, D>(
+    series: &[&[T]],
+    distance: impl Fn(&T, &T) -> D,
+    hyper_parameters: impl Into<Parameters>,
+) -> Matrix<D>
+where
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost,
+{
+    let hyper_parameters = hyper_parameters.into();
+    let n = series.len();
+    Matrix::from_iter(
+        (0..n * n).map(|k| {
+            let (a, b) = (series[k / n], series[k % n]);
+            DynamicTimeWarping::with_closure_and_param(a, b, &distance, hyper_parameters.clone())
+                .distance()
+        }),
+        n,
+        n,
+    )
+}
+
+/// Warped distances between the pairs `(i, j)` of `series` with `i < j`, in the order of `i`
+/// then `j`, i.e. the condensed upper triangle of [`distance_matrix`].
+///
+/// Each unordered pair is aligned once, which is exact for symmetric step patterns without
+/// landmarks.
+pub fn upper_triangle<T, D>(series: &[&[T]], hyper_parameters: impl Into<Parameters>) -> Vec<D>
+where
+    T: Distance<D>,
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost,
+{
+    let hyper_parameters = hyper_parameters.into();
+    pairs(series.len())
+        .map(|(i, j)| {
+            DynamicTimeWarping::with_param(series[i], series[j], hyper_parameters.clone())
+                .distance()
+        })
+        .collect()
+}
+
+/// Warped distances from `query` to every series of `series` with the hyper-parameters
+/// `hyper_parameters`, in the order of the series.
+pub fn distances_to<T, D>(
+    query: &[T],
+    series: &[&[T]],
+    hyper_parameters: impl Into<Parameters>,
+) -> Vec<D>
+where
+    T: Distance<D>,
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost,
+{
+    let hyper_parameters = hyper_parameters.into();
+    series
+        .iter()
+        .map(|s| DynamicTimeWarping::with_param(query, s, hyper_parameters.clone()).distance())
+        .collect()
+}
+
+/// [`distance_matrix`] computed in parallel, one row per task.
+#[cfg(feature = "rayon")]
+pub fn par_distance_matrix<T, D>(
+    series: &[&[T]],
+    hyper_parameters: impl Into<Parameters>,
+) -> Matrix<D>
+where
+    T: Distance<D> + Sync,
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost + Send,
+{
+    par_distance_matrix_with_closure(series, |a, b| a.distance(b), hyper_parameters)
+}
+
+/// [`distance_matrix_with_closure`] computed in parallel, one row per task.
+#[cfg(feature = "rayon")]
+pub fn par_distance_matrix_with_closure<T, D>(
+    series: &[&[T]],
+    distance: impl Fn(&T, &T) -> D + Sync,
+    hyper_parameters: impl Into<Parameters>,
+) -> Matrix<D>
+where
+    T: Sync,
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost + Send,
+{
+    let hyper_parameters = hyper_parameters.into();
+    let n = series.len();
+    let rows = series
+        .par_iter()
+        .map(|a| {
+            series
+                .iter()
+                .map(|b| {
+                    DynamicTimeWarping::with_closure_and_param(
+                        a,
+                        b,
+                        &distance,
+                        hyper_parameters.clone(),
+                    )
+                    .distance()
+                })
+                .collect::<Vec<D>>()
+        })
+        .collect::<Vec<Vec<D>>>();
+    Matrix::from_iter(rows.into_iter().flatten(), n, n)
+}
+
+/// [`upper_triangle`] computed in parallel, one pair per task.
+#[cfg(feature = "rayon")]
+pub fn par_upper_triangle<T, D>(series: &[&[T]], hyper_parameters: impl Into<Parameters>) -> Vec<D>
+where
+    T: Distance<D> + Sync,
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost + Send,
+{
+    let hyper_parameters = hyper_parameters.into();
+    pairs(series.len())
+        .collect::<Vec<(usize, usize)>>()
+        .into_par_iter()
+        .map(|(i, j)| {
+            DynamicTimeWarping::with_param(series[i], series[j], hyper_parameters.clone())
+                .distance()
+        })
+        .collect()
+}
+
+/// [`distances_to`] computed in parallel, one series per task.
+#[cfg(feature = "rayon")]
+pub fn par_distances_to<T, D>(
+    query: &[T],
+    series: &[&[T]],
+    hyper_parameters: impl Into<Parameters>,
+) -> Vec<D>
+where
+    T: Distance<D> + Sync,
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost + Send,
+{
+    let hyper_parameters = hyper_parameters.into();
+    series
+        .par_iter()
+        .map(|s| DynamicTimeWarping::with_param(query, s, hyper_parameters.clone()).distance())
+        .collect()
+}
+
+/// Pairs `(i, j)` with `i < j < n`, in the order of `i` then `j`.
+fn pairs(n: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..n).flat_map(move |i| (i + 1..n).map(move |j| (i, j)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{distance_matrix, distances_to, upper_triangle};
+    use crate::{Algorithm, DynamicTimeWarping, Parameters, Restriction};
+
+    #[test]
+    fn pairwise_distances() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let c = [0.0, 4.0, 4.0, 1.0];
+        let series: [&[f64]; 3] = [&a, &b, &c];
+        let matrix = distance_matrix(&series, Parameters::new());
+        assert_eq!(matrix.shape(), (3, 3));
+        assert_eq!(
+            matrix[(0, 1)],
+            DynamicTimeWarping::between(&a, &b).distance()
+        );
+        assert_eq!(matrix[(2, 0)], matrix[(0, 2)]);
+        assert!((0..3).all(|i| matrix[(i, i)] == 0.0));
+        let triangle = upper_triangle(&series, Restriction::Band(2));
+        let banded = distance_matrix(&series, Restriction::Band(2));
+        assert_eq!(triangle, [banded[(0, 1)], banded[(0, 2)], banded[(1, 2)]]);
+        assert_eq!(distances_to(&b, &series, Parameters::new()), matrix.row(1));
+        #[cfg(feature = "rayon")]
+        {
+            assert_eq!(
+                super::par_distance_matrix(&series, Parameters::new()),
+                matrix
+            );
+            assert_eq!(
+                super::par_upper_triangle(&series, Restriction::Band(2)),
+                triangle
+            );
+            let row = super::par_distances_to(&b, &series, Parameters::new());
+            assert_eq!(row, matrix.row(1));
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Post-processing of warping paths, given as the cells `(i, j)` matching `a[i]` with `b[j]`
+//! from `(0, 0)` to the last cell.
+
+use std::ops::Range;
+
+/// Map the labeled intervals of indices of `a` through `path` onto intervals of indices of `b`.
+///
+/// Every boundary of `a` is mapped to the first index of `b` matched with the element of `a`
+/// starting at it, so that consecutive intervals of `a` map to consecutive intervals of `b`.
+/// Intervals of `a` collapsed onto a single element of `b` map to empty intervals, keeping their
+/// labels and their order.
+pub fn map_intervals<L: Clone>(
+    path: &[(usize, usize)],
+    intervals: &[(Range<usize>, L)],
+) -> Vec<(Range<usize>, L)> {
+    let (n, m) = path.last().map_or((0, 0), |(i, j)| (i + 1, j + 1));
+    let mut boundaries = vec![m; n + 1];
+    for (i, j) in path.iter().rev() {
+        boundaries[*i] = *j;
+    }
+    let boundary = |i: usize| boundaries[i.min(n)];
+    intervals
+        .iter()
+        .map(|(range, label)| (boundary(range.start)..boundary(range.end), label.clone()))
+        .collect()
+}
+
+/// Smooth monotone function from the indices of `a` to the indices of `b` fitted to a warping
+/// path, evaluable at arbitrary times.
+#[derive(Debug, PartialEq, Clone)]
+pub struct WarpingFunction {
+    values: Vec<f64>,
+    slopes: Vec<f64>,
+}
+
+impl WarpingFunction {
+    /// Monotone cubic interpolation (Fritsch-Carlson) of the mean index of `b` matched with each
+    /// index of `a` along `path`.
+    pub fn fit(path: &[(usize, usize)]) -> Self {
+        let values = mean_columns(path);
+        let deltas = values.windows(2).map(|w| w[1] - w[0]).collect::<Vec<f64>>();
+        let slopes = (0..values.len())
+            .map(
+                |k| match (k.checked_sub(1).map(|k| deltas[k]), deltas.get(k).copied()) {
+                    (Some(d0), Some(d1)) if d0 > 0.0 && d1 > 0.0 => 2.0 / (1.0 / d0 + 1.0 / d1),
+                    (Some(d), None) | (None, Some(d)) => d,
+                    _ => 0.0,
+                },
+            )
+            .collect();
+        Self { values, slopes }
+    }
+
+    /// Index of `b` matched with the time `t` of `a`, clamped to the ends of the path.
+    pub fn eval(&self, t: f64) -> f64 {
+        let last = self.values.len() - 1;
+        if t <= 0.0 || last == 0 {
+            return self.values[0];
+        }
+        if t >= last as f64 {
+            return self.values[last];
+        }
+        let k = (t.floor() as usize).min(last - 1);
+        let s = t - k as f64;
+        let (s2, s3) = (s * s, s * s * s);
+        (2.0 * s3 - 3.0 * s2 + 1.0) * self.values[k]
+            + (s3 - 2.0 * s2 + s) * self.slopes[k]
+            + (-2.0 * s3 + 3.0 * s2) * self.values[k + 1]
+            + (s3 - s2) * self.slopes[k + 1]
+    }
+}
+
+/// Smooth `path` by averaging the mean index of `b` matched with each index of `a` over
+/// `radius` indices on both sides, narrowed near the ends, and rasterizing the result back into
+/// a monotone and continuous path with the same ends.
+pub fn smooth(path: &[(usize, usize)], radius: usize) -> Vec<(usize, usize)> {
+    let Some(&(last_i, last_j)) = path.last() else {
+        return Vec::new();
+    };
+    let means = mean_columns(path);
+    let mut smoothed = Vec::new();
+    let mut j = 0;
+    for i in 0..=last_i {
+        let radius = radius.min(i).min(last_i - i);
+        let window = &means[i - radius..=i + radius];
+        let target = match i {
+            _ if i == last_i => last_j,
+            _ => (window.iter().sum::<f64>() / window.len() as f64).round() as usize,
+        }
+        .clamp(j, last_j);
+        match i {
+            0 => smoothed.extend((0..=target).map(|j| (0, j))),
+            _ => {
+                smoothed.extend((j + 1..target).map(|j| (i - 1, j)));
+                smoothed.push((i, target));
+            }
+        }
+        j = target;
+    }
+    smoothed
+}
+
+/// Warping functions `(phi_a, phi_b)` of `path`, such that its `k`-th cell matches
+/// `a[phi_a[k]]` with `b[phi_b[k]]`.
+pub fn warping_functions(path: &[(usize, usize)]) -> (Vec<usize>, Vec<usize>) {
+    path.iter().copied().unzip()
+}
+
+/// Warp `b` onto the time axis of `a` along `path`, every index of `a` taking the mean of the
+/// elements of `b` matched with it.
+pub fn warp(path: &[(usize, usize)], b: &[f64]) -> Vec<f64> {
+    let n = path.last().map_or(0, |(i, _)| i + 1);
+    let mut sums = vec![(0.0, 0); n];
+    for (i, j) in path {
+        sums[*i] = (sums[*i].0 + b[*j], sums[*i].1 + 1);
+    }
+    sums.into_iter()
+        .map(|(sum, count)| sum / count as f64)
+        .collect()
+}
+
+/// Offset of the mean index of `b` matched with each index of `a` along `path` from that index,
+/// positive where `b` lags behind `a`.
+pub fn offsets(path: &[(usize, usize)]) -> Vec<f64> {
+    mean_columns(path)
+        .into_iter()
+        .enumerate()
+        .map(|(i, j)| j - i as f64)
+        .collect()
+}
+
+/// Mean index of `b` matched with each index of `a` along `path`.
+fn mean_columns(path: &[(usize, usize)]) -> Vec<f64> {
+    let m = path.last().map_or(0, |(_, j)| j + 1);
+    warp(path, &(0..m).map(|j| j as f64).collect::<Vec<f64>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{map_intervals, offsets, smooth, warp, warping_functions, WarpingFunction};
+
+    #[test]
+    fn map_labeled_intervals() {
+        let path = [(0, 0), (1, 0), (2, 1), (2, 2), (3, 3), (4, 3), (5, 4)];
+        let intervals = [(0..2, "sil"), (2..3, "a"), (3..5, "b"), (5..6, "sil")];
+        let mapped = map_intervals(&path, &intervals);
+        assert_eq!(
+            mapped,
+            [(0..1, "sil"), (1..3, "a"), (3..4, "b"), (4..5, "sil")]
+        );
+        let collapsed = map_intervals(&path, &[(0..1, "x"), (1..2, "y")]);
+        assert_eq!(collapsed, [(0..0, "x"), (0..1, "y")]);
+    }
+
+    #[test]
+    fn warp_along_path() {
+        let path = [(0, 0), (1, 1), (1, 2), (2, 3), (3, 3)];
+        let (phi_a, phi_b) = warping_functions(&path);
+        assert_eq!(phi_a, [0, 1, 1, 2, 3]);
+        assert_eq!(phi_b, [0, 1, 2, 3, 3]);
+        assert_eq!(warp(&path, &[1.0, 2.0, 4.0, 8.0]), [1.0, 3.0, 8.0, 8.0]);
+        assert_eq!(offsets(&path), [0.0, 0.5, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn warping_function_is_monotone() {
+        let path = [
+            (0, 0),
+            (0, 1),
+            (1, 2),
+            (2, 2),
+            (3, 3),
+            (3, 4),
+            (3, 5),
+            (4, 6),
+        ];
+        let warping = WarpingFunction::fit(&path);
+        assert_eq!(warping.eval(0.0), 0.5);
+        assert_eq!(warping.eval(2.0), 2.0);
+        assert_eq!(warping.eval(10.0), 6.0);
+        let values = (0..=40)
+            .map(|k| warping.eval(k as f64 / 10.0))
+            .collect::<Vec<f64>>();
+        assert!(values.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn smooth_jittery_path() {
+        let path = [
+            (0, 0),
+            (0, 1),
+            (1, 1),
+            (2, 2),
+            (2, 3),
+            (3, 3),
+            (4, 4),
+            (5, 5),
+        ];
+        let smoothed = smooth(&path, 1);
+        assert_eq!(smoothed.first(), Some(&(0, 0)));
+        assert_eq!(smoothed.last(), Some(&(5, 5)));
+        assert!(smoothed.windows(2).all(|w| {
+            let step = (w[1].0 - w[0].0, w[1].1 - w[0].1);
+            step == (1, 1) || step == (1, 0) || step == (0, 1)
+        }));
+        assert_eq!(
+            smooth(&[(0, 0), (1, 1), (2, 2)], 3),
+            [(0, 0), (1, 1), (2, 2)]
+        );
+        assert_eq!(
+            smooth(&[(0, 0), (0, 1), (0, 2)], 0),
+            [(0, 0), (0, 1), (0, 2)]
+        );
+    }
+}
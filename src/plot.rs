@@ -0,0 +1,147 @@
+//! Diagnostic views of alignments as SVG documents: a heatmap of the accumulated costs with the
+//! warping path overlaid, and the two series with lines connecting their matched elements.
+
+use std::{fmt::Write, io, ops::Add, path::Path};
+
+use crate::{Algorithm, Cost, DynamicTimeWarping};
+
+/// Side of a cell of the heatmap and step between the elements of the series, in pixels.
+const CELL: f64 = 10.0;
+
+/// Height of the band of each series in the alignment plot, in pixels.
+const BAND: f64 = 100.0;
+
+/// SVG heatmap of the accumulated costs of `dtw`, from white for the cheapest cells to black for
+/// the costliest ones, with its warping path overlaid in red. The rows are the elements of `a`
+/// from the top, the columns those of `b` from the left, and the unreached cells are left blank.
+pub fn heatmap<D>(dtw: &DynamicTimeWarping<D>) -> String
+where
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost + Into<f64>,
+{
+    let costs = dtw.cost_matrix();
+    let (n, m) = costs.shape();
+    let values = costs
+        .data()
+        .iter()
+        .map(|cost| cost.clone().map(Into::into))
+        .collect::<Vec<Option<f64>>>();
+    let (min, max) = values
+        .iter()
+        .flatten()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(*v), max.max(*v))
+        });
+    let mut svg = header(m as f64 * CELL, n as f64 * CELL);
+    for (k, value) in values.iter().enumerate() {
+        let Some(value) = value else {
+            continue;
+        };
+        let shade = match max > min {
+            true => 255.0 * (1.0 - (value - min) / (max - min)),
+            false => 255.0,
+        }
+        .round() as u8;
+        let _ = writeln!(
+            svg,
+            r#"<rect x="{}" y="{}" width="{CELL}" height="{CELL}" fill="rgb({shade},{shade},{shade})"/>"#,
+            (k % m) as f64 * CELL,
+            (k / m) as f64 * CELL,
+        );
+    }
+    let points = dtw
+        .path()
+        .iter()
+        .map(|(i, j)| format!("{},{}", (*j as f64 + 0.5) * CELL, (*i as f64 + 0.5) * CELL))
+        .collect::<Vec<String>>();
+    let _ = writeln!(
+        svg,
+        r#"<polyline points="{}" fill="none" stroke="red" stroke-width="2"/>"#,
+        points.join(" ")
+    );
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// SVG plot of the series `a` above `b`, each scaled to its band, with gray lines connecting the
+/// elements matched by `path`.
+pub fn alignment(a: &[f64], b: &[f64], path: &[(usize, usize)]) -> String {
+    let width = (a.len().max(b.len()) as f64) * CELL;
+    let (min, max) = a
+        .iter()
+        .chain(b)
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| {
+            (min.min(*v), max.max(*v))
+        });
+    let y = |v: f64, top: f64| match max > min {
+        true => top + BAND * (1.0 - (v - min) / (max - min)),
+        false => top + BAND / 2.0,
+    };
+    let x = |k: usize| (k as f64 + 0.5) * CELL;
+    let mut svg = header(width, 3.0 * BAND);
+    for (i, j) in path {
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{}" y1="{}" x2="{}" y2="{}" stroke="gray" stroke-width="0.5"/>"#,
+            x(*i),
+            y(a[*i], 0.0),
+            x(*j),
+            y(b[*j], 2.0 * BAND),
+        );
+    }
+    for (series, top, color) in [(a, 0.0, "blue"), (b, 2.0 * BAND, "orange")] {
+        let points = series
+            .iter()
+            .enumerate()
+            .map(|(k, v)| format!("{},{}", x(k), y(*v, top)))
+            .collect::<Vec<String>>();
+        let _ = writeln!(
+            svg,
+            r#"<polyline points="{}" fill="none" stroke="{color}" stroke-width="1.5"/>"#,
+            points.join(" ")
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Opening tag of an SVG document of size `width` by `height` with a white background.
+fn header(width: f64, height: f64) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">
+<rect width="{width}" height="{height}" fill="white"/>
+"#
+    )
+}
+
+impl<D> DynamicTimeWarping<D>
+where
+    D: PartialOrd + Clone + Default + Add<D, Output = D> + Cost + Into<f64>,
+{
+    /// Write the [`heatmap`] of the accumulated costs and the warping path to the SVG file
+    /// `path`.
+    pub fn plot(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        std::fs::write(path, heatmap(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{alignment, heatmap};
+    use crate::{Algorithm, DynamicTimeWarping, Restriction};
+
+    #[test]
+    fn svg_documents() {
+        let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+        let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+        let dtw = DynamicTimeWarping::between(&a, &b);
+        let svg = heatmap(&dtw);
+        assert!(svg.starts_with("<svg") && svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<rect").count(), 1 + a.len() * b.len());
+        assert!(svg.contains(r#"<polyline points="5,5 "#));
+        let banded = DynamicTimeWarping::with_param(&a, &b, Restriction::Band(0));
+        assert!(heatmap(&banded).matches("<rect").count() < 1 + a.len() * b.len());
+        let svg = alignment(&a, &b, &dtw.path());
+        assert_eq!(svg.matches("<line").count(), dtw.path().len());
+        assert_eq!(svg.matches("<polyline").count(), 2);
+    }
+}
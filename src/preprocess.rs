@@ -0,0 +1,652 @@
+//! Preprocessing of series before their alignment.
+//!
+//! Derivative dynamic time warping aligns the estimated derivatives of the series instead of
+//! their values, e.g. with [`Derivative::align`], matching their shapes regardless of their
+//! offsets. Aligning their second derivatives instead matches
+//! their curvatures, i.e. their peaks and inflections, and [`Features`] combines the three.
+//!
+//! The series are usually normalized first, e.g. with [`z_normalize`], and a [`Pipeline`] chains
+//! such preprocessing steps before aligning the series.
+
+use crate::{DynamicTimeWarping, Parameters};
+
+/// Estimator of the first derivative of a series.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum Derivative {
+    /// Estimator of Keogh and Pazzani, averaging the slope to the previous element and the slope
+    /// between the neighbors: `((x[i] - x[i-1]) + (x[i+1] - x[i-1]) / 2) / 2`.
+    #[default]
+    Keogh,
+    /// Central differences: `(x[i+1] - x[i-1]) / 2`.
+    Central,
+    /// Savitzky-Golay filter of half-width `k`, i.e. the slope of the least squares line fitted
+    /// to the `2k + 1` elements centered on `x[i]`, smoothing noisy series.
+    SavitzkyGolay(usize),
+}
+
+impl Derivative {
+    /// Estimated derivative of every element of `series`. The windows are narrowed near the
+    /// ends of the series, where the estimate is a one-sided difference.
+    pub fn apply(&self, series: &[f64]) -> Vec<f64> {
+        let n = series.len();
+        if n < 2 {
+            return vec![0.0; n];
+        }
+        let one_sided = |i: usize| match i {
+            0 => series[1] - series[0],
+            _ => series[i] - series[i - 1],
+        };
+        match self {
+            Derivative::Keogh if n == 2 => vec![one_sided(0); 2],
+            Derivative::Keogh => {
+                let keogh = |i: usize| {
+                    ((series[i] - series[i - 1]) + (series[i + 1] - series[i - 1]) / 2.0) / 2.0
+                };
+                (0..n).map(|i| keogh(i.clamp(1, n - 2))).collect()
+            }
+            Derivative::Central => Derivative::SavitzkyGolay(1).apply(series),
+            Derivative::SavitzkyGolay(k) => (0..n)
+                .map(|i| match (*k).min(i).min(n - 1 - i) {
+                    0 => one_sided(i),
+                    k => {
+                        let numerator = (1..=k)
+                            .map(|m| m as f64 * (series[i + m] - series[i - m]))
+                            .sum::<f64>();
+                        numerator / (k * (k + 1) * (2 * k + 1) / 3) as f64
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Estimated second derivative of every element of `series`, i.e. the estimated derivative
+    /// of its estimated derivative.
+    pub fn second(&self, series: &[f64]) -> Vec<f64> {
+        self.apply(&self.apply(series))
+    }
+
+    /// Derivative dynamic time warping (Keogh and Pazzani, "Derivative Dynamic Time Warping")
+    /// between `a` and `b` using the parameter `param`: the absolute differences between their
+    /// estimated derivatives are aligned, avoiding the singularities of the alignment of values
+    /// differing by their offsets or amplitudes. The path is in the indices of `a` and `b`.
+    pub fn align(
+        &self,
+        a: &[f64],
+        b: &[f64],
+        param: impl Into<Parameters>,
+    ) -> DynamicTimeWarping<f64> {
+        DynamicTimeWarping::with_closure_and_param(
+            &self.apply(a),
+            &self.apply(b),
+            |x: &f64, y: &f64| f64::abs(x - y),
+            param,
+        )
+    }
+}
+
+/// Weighted combination of the value, the slope and the curvature of every element, aligned with
+/// the Euclidean distance between the combinations.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Features {
+    value: f64,
+    slope: f64,
+    curvature: f64,
+    estimator: Derivative,
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Self {
+            value: 1.0,
+            slope: 0.0,
+            curvature: 0.0,
+            estimator: Derivative::default(),
+        }
+    }
+}
+
+impl Features {
+    /// Features of the values only, with the derivatives estimated by [`Derivative::Keogh`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the weight of the values.
+    pub fn value(mut self, weight: f64) -> Self {
+        self.value = weight;
+        self
+    }
+
+    /// Set the weight of the first derivatives.
+    pub fn slope(mut self, weight: f64) -> Self {
+        self.slope = weight;
+        self
+    }
+
+    /// Set the weight of the second derivatives.
+    pub fn curvature(mut self, weight: f64) -> Self {
+        self.curvature = weight;
+        self
+    }
+
+    /// Set the estimator of the derivatives.
+    pub fn estimator(mut self, estimator: Derivative) -> Self {
+        self.estimator = estimator;
+        self
+    }
+
+    /// Weighted value, slope and curvature of every element of `series`.
+    pub fn apply(&self, series: &[f64]) -> Vec<[f64; 3]> {
+        let slopes = self.estimator.apply(series);
+        let curvatures = self.estimator.apply(&slopes);
+        series
+            .iter()
+            .zip(slopes)
+            .zip(curvatures)
+            .map(|((x, s), c)| [self.value * x, self.slope * s, self.curvature * c])
+            .collect()
+    }
+
+    /// Dynamic time warping between the features of `a` and `b` using the parameter `param`.
+    pub fn align(
+        &self,
+        a: &[f64],
+        b: &[f64],
+        param: impl Into<Parameters>,
+    ) -> DynamicTimeWarping<f64> {
+        DynamicTimeWarping::with_closure_and_param(
+            &self.apply(a),
+            &self.apply(b),
+            |x, y| {
+                x.iter()
+                    .zip(y)
+                    .map(|(x, y)| (x - y) * (x - y))
+                    .sum::<f64>()
+                    .sqrt()
+            },
+            param,
+        )
+    }
+}
+
+/// `series` minus its least squares polynomial fit of degree `degree`, removing a baseline offset
+/// with a degree of zero, a linear drift with a degree of one, and slower curved drifts with
+/// higher degrees.
+pub fn detrend(series: &[f64], degree: usize) -> Vec<f64> {
+    let n = series.len();
+    let size = (degree + 1).min(n);
+    if size == 0 {
+        return Vec::new();
+    }
+    // The abscissas are scaled to [-1, 1] to keep the normal equations well conditioned.
+    let x = |i: usize| match n {
+        1 => 0.0,
+        _ => 2.0 * i as f64 / (n - 1) as f64 - 1.0,
+    };
+    let powers = |i: usize| {
+        (0..size)
+            .scan(1.0, |p, _| {
+                let current = *p;
+                *p *= x(i);
+                Some(current)
+            })
+            .collect::<Vec<f64>>()
+    };
+    let mut system = vec![vec![0.0; size + 1]; size];
+    for (i, y) in series.iter().enumerate() {
+        let p = powers(i);
+        for r in 0..size {
+            for c in 0..size {
+                system[r][c] += p[r] * p[c];
+            }
+            system[r][size] += p[r] * y;
+        }
+    }
+    for k in 0..size {
+        let pivot = (k..size)
+            .max_by(|r, s| system[*r][k].abs().total_cmp(&system[*s][k].abs()))
+            .unwrap();
+        system.swap(k, pivot);
+        for r in k + 1..size {
+            let (above, below) = system.split_at_mut(r);
+            let factor = below[0][k] / above[k][k];
+            for (x, y) in below[0][k..].iter_mut().zip(&above[k][k..]) {
+                *x -= factor * y;
+            }
+        }
+    }
+    let mut coefficients = vec![0.0; size];
+    for k in (0..size).rev() {
+        let known = (k + 1..size)
+            .map(|c| system[k][c] * coefficients[c])
+            .sum::<f64>();
+        coefficients[k] = (system[k][size] - known) / system[k][k];
+    }
+    series
+        .iter()
+        .enumerate()
+        .map(|(i, y)| {
+            let trend = powers(i)
+                .iter()
+                .zip(&coefficients)
+                .map(|(p, c)| p * c)
+                .sum::<f64>();
+            y - trend
+        })
+        .collect()
+}
+
+/// Additive decomposition of a series into its trend, its seasonal and its residual components.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Decomposition {
+    /// Centered moving average of the series over one period.
+    pub trend: Vec<f64>,
+    /// Mean deviation from the trend at every phase of the period, repeated along the series.
+    pub seasonal: Vec<f64>,
+    /// Remainder of the series besides its trend and its seasonal component.
+    pub residual: Vec<f64>,
+}
+
+/// Classical decomposition of `series` with a season of `period` elements, such that the
+/// seasonal and residual components can be aligned separately instead of mostly measuring the
+/// phase of the season.
+///
+/// The trend is the moving average over a period centered on every element, or over the first
+/// or the last period near the ends of the series.
+pub fn decompose(series: &[f64], period: usize) -> Decomposition {
+    let n = series.len();
+    assert!(
+        period > 0 && n >= period,
+        "Series should span at least one period"
+    );
+    let mut prefix = vec![0.0; n + 1];
+    for (i, x) in series.iter().enumerate() {
+        prefix[i + 1] = prefix[i] + x;
+    }
+    let window = |start: isize| {
+        let start = start.clamp(0, (n - period) as isize) as usize;
+        (prefix[start + period] - prefix[start]) / period as f64
+    };
+    let half = (period / 2) as isize;
+    let trend = (0..n as isize)
+        .map(|i| match period % 2 {
+            1 => window(i - half),
+            _ => (window(i - half) + window(i - half + 1)) / 2.0,
+        })
+        .collect::<Vec<f64>>();
+    let mut phases = vec![(0.0, 0); period];
+    for (i, (x, t)) in series.iter().zip(&trend).enumerate() {
+        phases[i % period].0 += x - t;
+        phases[i % period].1 += 1;
+    }
+    let means = phases
+        .iter()
+        .map(|(sum, count)| sum / *count as f64)
+        .collect::<Vec<f64>>();
+    let offset = means.iter().sum::<f64>() / period as f64;
+    let seasonal = (0..n)
+        .map(|i| means[i % period] - offset)
+        .collect::<Vec<f64>>();
+    let residual = series
+        .iter()
+        .zip(&trend)
+        .zip(&seasonal)
+        .map(|((x, t), s)| x - t - s)
+        .collect();
+    Decomposition {
+        trend,
+        seasonal,
+        residual,
+    }
+}
+
+/// Mean and standard deviation of every window of `window` consecutive elements of `series`,
+/// in the order of their first element.
+///
+/// The sums of the elements and of their squares are updated as the window slides, in `O(1)`
+/// per window. They are accumulated relative to the mean of the window they were last recomputed
+/// on, and recomputed every `window` windows as in the UCR suite, such that a large offset of
+/// the series does not cancel out the variance.
+pub fn rolling_stats(series: &[f64], window: usize) -> Vec<(f64, f64)> {
+    assert!(window > 0, "Window should not be empty");
+    if series.len() < window {
+        return Vec::new();
+    }
+    let w = window as f64;
+    let (mut shift, mut sum, mut squares) = (0.0, 0.0, 0.0);
+    let mut result = Vec::with_capacity(series.len() - window + 1);
+    for start in 0..=series.len() - window {
+        if start % window == 0 {
+            let values = &series[start..start + window];
+            shift = values.iter().sum::<f64>() / w;
+            (sum, squares) = values.iter().fold((0.0, 0.0), |(s, q), x| {
+                (s + (x - shift), q + (x - shift) * (x - shift))
+            });
+        } else {
+            let (old, new) = (
+                series[start - 1] - shift,
+                series[start + window - 1] - shift,
+            );
+            sum += new - old;
+            squares += new * new - old * old;
+        }
+        let mean = sum / w;
+        result.push((shift + mean, (squares / w - mean * mean).max(0.0).sqrt()));
+    }
+    result
+}
+
+/// `series` shifted to a mean of zero and scaled to a standard deviation of one, or only shifted
+/// if it is constant.
+pub fn z_normalize(series: &[f64]) -> Vec<f64> {
+    if series.is_empty() {
+        return Vec::new();
+    }
+    let len = series.len() as f64;
+    let mean = series.iter().sum::<f64>() / len;
+    let std = (series.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / len).sqrt();
+    series.iter().map(|x| z_score(*x, mean, std)).collect()
+}
+
+/// `series` scaled linearly to the range `[0, 1]`, or only shifted to zero if it is constant.
+pub fn min_max_scale(series: &[f64]) -> Vec<f64> {
+    let (min, max) = series
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), x| {
+            (min.min(*x), max.max(*x))
+        });
+    series
+        .iter()
+        .map(|x| match max - min > f64::EPSILON {
+            true => (x - min) / (max - min),
+            false => x - min,
+        })
+        .collect()
+}
+
+/// `series` shifted to a mean of zero.
+pub fn center(series: &[f64]) -> Vec<f64> {
+    let mean = series.iter().sum::<f64>() / series.len().max(1) as f64;
+    series.iter().map(|x| x - mean).collect()
+}
+
+/// Piecewise aggregate approximation of `series` by the means of `segments` frames of equal
+/// durations. An element straddling two frames contributes to both in proportion to its
+/// overlap with each, such that the length need not be a multiple of the number of frames.
+/// [`Downsampling::Paa`](crate::multiscale::Downsampling::Paa) is built on it to reduce chunks of
+/// a given length instead, keeping a shorter trailing chunk as is.
+pub fn paa(series: &[f64], segments: usize) -> Vec<f64> {
+    assert!(segments > 0, "Number of segments should be positive");
+    let (n, w) = (series.len(), segments);
+    if n == 0 {
+        return Vec::new();
+    }
+    // In units of `1 / w` elements, frame `k` spans `[k n, (k + 1) n)` and element `i` spans
+    // `[i w, (i + 1) w)`.
+    (0..w)
+        .map(|k| {
+            let (start, end) = (k * n, (k + 1) * n);
+            let sum = (start / w..end.div_ceil(w))
+                .map(|i| {
+                    let overlap = end.min((i + 1) * w) - start.max(i * w);
+                    series[i] * overlap as f64
+                })
+                .sum::<f64>();
+            sum / n as f64
+        })
+        .collect()
+}
+
+/// Preprocessing step of a [`Pipeline`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Preprocessing {
+    /// [`z_normalize`].
+    ZNormalize,
+    /// [`min_max_scale`].
+    MinMaxScale,
+    /// [`center`].
+    Center,
+    /// [`detrend`] with the given degree.
+    Detrend(usize),
+    /// [`paa`] with the given number of segments.
+    Paa(usize),
+}
+
+impl Preprocessing {
+    /// `series` preprocessed by the step.
+    pub fn apply(&self, series: &[f64]) -> Vec<f64> {
+        match self {
+            Preprocessing::ZNormalize => z_normalize(series),
+            Preprocessing::MinMaxScale => min_max_scale(series),
+            Preprocessing::Center => center(series),
+            Preprocessing::Detrend(degree) => detrend(series, *degree),
+            Preprocessing::Paa(segments) => paa(series, *segments),
+        }
+    }
+}
+
+/// Preprocessing steps applied in turn to both series before their alignment, e.g.
+/// `Pipeline::new().then(Preprocessing::Detrend(1)).then(Preprocessing::ZNormalize)`.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Pipeline {
+    steps: Vec<Preprocessing>,
+}
+
+impl Pipeline {
+    /// Pipeline without any step.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the step `step`.
+    pub fn then(mut self, step: Preprocessing) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Steps in the order they are applied.
+    pub fn steps(&self) -> &[Preprocessing] {
+        &self.steps
+    }
+
+    /// `series` preprocessed by every step in turn.
+    pub fn apply(&self, series: &[f64]) -> Vec<f64> {
+        self.steps
+            .iter()
+            .fold(series.to_vec(), |series, step| step.apply(&series))
+    }
+
+    /// Dynamic time warping between the preprocessed `a` and `b` with the absolute difference as
+    /// local cost, using the parameter `param`. The path is in the indices of the preprocessed
+    /// series, i.e. of the frames after a [`Preprocessing::Paa`] step.
+    pub fn align(
+        &self,
+        a: &[f64],
+        b: &[f64],
+        param: impl Into<Parameters>,
+    ) -> DynamicTimeWarping<f64> {
+        DynamicTimeWarping::with_closure_and_param(
+            &self.apply(a),
+            &self.apply(b),
+            |x: &f64, y: &f64| f64::abs(x - y),
+            param,
+        )
+    }
+}
+
+/// Score of `x` in a distribution of mean `mean` and standard deviation `std`.
+pub(crate) fn z_score(x: f64, mean: f64, std: f64) -> f64 {
+    if std > f64::EPSILON {
+        (x - mean) / std
+    } else {
+        x - mean
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        center, decompose, detrend, min_max_scale, paa, rolling_stats, z_normalize, Derivative,
+        Features, Pipeline, Preprocessing,
+    };
+    use crate::{Algorithm, DynamicTimeWarping, Parameters};
+
+    #[test]
+    fn derivative_estimators() {
+        let line = (0..10).map(|i| 3.0 * i as f64 + 1.0).collect::<Vec<f64>>();
+        for estimator in [
+            Derivative::Keogh,
+            Derivative::Central,
+            Derivative::SavitzkyGolay(3),
+        ] {
+            assert_eq!(estimator.apply(&line), vec![3.0; 10]);
+        }
+        let series = [0.0, 1.0, 4.0, 9.0, 16.0];
+        assert_eq!(
+            Derivative::Central.apply(&series),
+            [1.0, 2.0, 4.0, 6.0, 7.0]
+        );
+        assert_eq!(Derivative::Keogh.apply(&series), [1.5, 1.5, 3.5, 5.5, 5.5]);
+        assert_eq!(Derivative::Keogh.apply(&[2.0]), [0.0]);
+    }
+
+    #[test]
+    fn derivative_alignment() {
+        let a = [0.0, 1.0, 3.0, 6.0, 3.0, 1.0, 0.0];
+        let b = [5.0, 5.0, 6.0, 8.0, 11.0, 8.0, 6.0, 5.0];
+        let ddtw = Derivative::Keogh.align(&a, &b, Parameters::new());
+        let dtw = DynamicTimeWarping::between(&a, &b);
+        assert!(ddtw.distance() < dtw.distance());
+        let path = ddtw.path();
+        assert!(path.contains(&(3, 4)));
+        assert_eq!(path.last(), Some(&(6, 7)));
+    }
+
+    #[test]
+    fn second_derivative() {
+        let parabola = (0..12).map(|i| (i * i) as f64).collect::<Vec<f64>>();
+        for estimator in [Derivative::Keogh, Derivative::Central] {
+            let curvature = estimator.second(&parabola);
+            assert!(curvature[3..9].iter().all(|c| *c == 2.0));
+        }
+    }
+
+    #[test]
+    fn savitzky_golay_smooths_noise() {
+        let noisy = (0..40)
+            .map(|i| i as f64 + if i % 2 == 0 { 0.5 } else { -0.5 })
+            .collect::<Vec<f64>>();
+        let error = |d: Vec<f64>| d[5..35].iter().map(|x| (x - 1.0).abs()).sum::<f64>();
+        assert!(
+            error(Derivative::SavitzkyGolay(4).apply(&noisy))
+                < error(Derivative::Keogh.apply(&noisy))
+        );
+    }
+
+    #[test]
+    fn composite_features() {
+        let a = [0.0, 1.0, 3.0, 1.0, 0.0];
+        let b = [10.0, 11.0, 13.0, 11.0, 10.0];
+        let values = Features::new().align(&a, &b, Parameters::new());
+        assert_eq!(
+            values.distance(),
+            DynamicTimeWarping::between(&a, &b).distance()
+        );
+        let shapes = Features::new().value(0.0).slope(1.0).curvature(0.5);
+        assert_eq!(shapes.align(&a, &b, Parameters::new()).distance(), 0.0);
+        let curvature = 0.5 * Derivative::Keogh.second(&a)[2];
+        assert_eq!(shapes.apply(&a)[2], [0.0, 1.0, curvature]);
+    }
+
+    #[test]
+    fn detrend_polynomial_drift() {
+        let residual = (0..50)
+            .map(|i| f64::sin(i as f64 / 2.0))
+            .collect::<Vec<f64>>();
+        let drifted = residual
+            .iter()
+            .enumerate()
+            .map(|(i, r)| r + 0.3 * i as f64 - 2.0 + 1e-3 * (i * i) as f64)
+            .collect::<Vec<f64>>();
+        let close = |x: &[f64], y: &[f64]| x.iter().zip(y).all(|(x, y)| (x - y).abs() < 1e-9);
+        assert!(close(&detrend(&drifted, 2), &detrend(&residual, 2)));
+        assert!(!close(&detrend(&drifted, 1), &detrend(&residual, 1)));
+        assert_eq!(detrend(&[1.0, 2.0, 3.0], 0), [-1.0, 0.0, 1.0]);
+        assert_eq!(detrend(&[5.0], 3), [0.0]);
+    }
+
+    #[test]
+    fn decompose_seasons() {
+        let season = [1.0, -1.0, 2.0, -2.0];
+        let series = (0..24).map(|i| 5.0 + season[i % 4]).collect::<Vec<f64>>();
+        let decomposition = decompose(&series, 4);
+        assert_eq!(decomposition.trend, vec![5.0; 24]);
+        assert_eq!(decomposition.seasonal[..4], season);
+        assert_eq!(decomposition.residual, vec![0.0; 24]);
+        let drifting = (0..24)
+            .map(|i| i as f64 + season[i % 4])
+            .collect::<Vec<f64>>();
+        let trend = decompose(&drifting, 4).trend;
+        assert!((2..22).all(|i| trend[i] == i as f64));
+    }
+
+    #[test]
+    fn rolling_z_normalization() {
+        let series = [1.0, 3.0, 5.0, 5.0, 5.0, 2.0];
+        let stats = rolling_stats(&series, 3);
+        assert_eq!(stats.len(), 4);
+        assert_eq!(stats[0].0, 3.0);
+        assert!((stats[0].1 - f64::sqrt(8.0 / 3.0)).abs() < 1e-12);
+        assert_eq!(stats[2], (5.0, 0.0));
+        assert_eq!(z_normalize(&[2.0, 4.0]), [-1.0, 1.0]);
+        assert_eq!(z_normalize(&[7.0, 7.0]), [0.0, 0.0]);
+        let offset = (0..1000)
+            .map(|i| 1e8 + f64::sin(i as f64 / 3.0))
+            .collect::<Vec<f64>>();
+        for (start, (mean, std)) in rolling_stats(&offset, 100).into_iter().enumerate() {
+            let window = &offset[start..start + 100];
+            let exact = window.iter().map(|x| x - 1e8).sum::<f64>() / 100.0;
+            let spread = window
+                .iter()
+                .map(|x| (x - 1e8 - exact) * (x - 1e8 - exact))
+                .sum::<f64>()
+                / 100.0;
+            assert!((mean - 1e8 - exact).abs() < 1e-6);
+            assert!((std - spread.sqrt()).abs() < 1e-6);
+        }
+        let normalized = z_normalize(&offset);
+        assert!((normalized.iter().map(|x| x * x).sum::<f64>() / 1000.0 - 1.0).abs() < 1e-6);
+    }
+    #[test]
+    fn preprocessing_pipeline() {
+        assert_eq!(min_max_scale(&[2.0, 4.0, 3.0]), [0.0, 1.0, 0.5]);
+        assert_eq!(min_max_scale(&[3.0, 3.0]), [0.0, 0.0]);
+        assert_eq!(center(&[1.0, 2.0, 6.0]), [-2.0, -1.0, 3.0]);
+        assert_eq!(paa(&[1.0, 3.0, 5.0, 7.0], 2), [2.0, 6.0]);
+        assert_eq!(paa(&[3.0, 6.0, 9.0], 2), [4.0, 8.0]);
+        assert_eq!(paa(&[1.0, 2.0], 4), [1.0, 1.0, 2.0, 2.0]);
+        let offset = Pipeline::new().then(Preprocessing::ZNormalize).apply(&[
+            1e9 + 1.0,
+            1e9 + 3.0,
+            1e9 + 2.0,
+        ]);
+        assert!((offset[0] + f64::sqrt(1.5)).abs() < 1e-6);
+        let pipeline = Pipeline::new()
+            .then(Preprocessing::Detrend(1))
+            .then(Preprocessing::ZNormalize)
+            .then(Preprocessing::Paa(5));
+        assert_eq!(pipeline.steps().len(), 3);
+        let a = (0..40)
+            .map(|i| f64::sin(i as f64 / 3.0))
+            .collect::<Vec<f64>>();
+        let b = a
+            .iter()
+            .enumerate()
+            .map(|(i, x)| 10.0 + 4.0 * x + 0.5 * i as f64)
+            .collect::<Vec<f64>>();
+        let aligned = pipeline.align(&a, &b, Parameters::new());
+        assert!(aligned.distance() < 1e-9);
+        assert_eq!(aligned.path().last(), Some(&(4, 4)));
+        assert_eq!(Pipeline::new().apply(&a), a);
+    }
+}
@@ -0,0 +1,666 @@
+//! Search of a query among the subsequences of a long series.
+//!
+//! The query and every candidate subsequence of its length are z-normalized, the candidates
+//! using the rolling statistics of [`rolling_stats`], and aligned within a Sakoe-Chiba band.
+//! The alignment of a candidate is abandoned as soon as every cell of a row exceeds the best
+//! distance found so far, as in the UCR suite (Rakthanmanon et al., "Searching and Mining
+//! Trillions of Time Series Subsequences under Dynamic Time Warping").
+
+#[cfg(feature = "rayon")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{
+    algorithms::{accumulate_subsequence, Element},
+    lower_bounds::{lb_kim, Envelope},
+    multiscale::Downsampling,
+    preprocess::{rolling_stats, z_normalize, z_score, Derivative},
+    StepPattern,
+};
+
+/// Subsequence of the series matched by the query.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Match {
+    /// Index of the first element of the subsequence, whose length is the length of the query.
+    pub start: usize,
+    /// Warped distance between the z-normalized query and subsequence.
+    pub distance: f64,
+}
+
+/// Subsequence of the series matched by the query with free endpoints. See
+/// [`subsequence_search`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct SubsequenceMatch {
+    /// Index of the first element of the subsequence.
+    pub start: usize,
+    /// Index of the last element of the subsequence.
+    pub end: usize,
+    /// Warped distance between the query and the subsequence.
+    pub distance: f64,
+    /// Warped path from the query to the series, in the indices of the series.
+    pub path: Vec<(usize, usize)>,
+}
+
+/// Candidate closest to the query.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Neighbor {
+    /// Index of the candidate.
+    pub index: usize,
+    /// Warped distance between the query and the candidate.
+    pub distance: f64,
+}
+
+/// Subsequence of `series` closest to `query` within a band of width `band`, or `None` if the
+/// series is shorter than the query.
+pub fn best_match(query: &[f64], series: &[f64], band: usize) -> Option<Match> {
+    assert!(!query.is_empty(), "Query should not be empty");
+    let stats = rolling_stats(series, query.len());
+    scan(&z_normalize(query), series, &stats, 0, band, |best| best)
+}
+
+/// [`best_match`] computed in parallel over chunks of [`PARALLEL_CHUNK`] subsequences of
+/// `series`, the best distance found so far being shared by every thread. The statistics of the
+/// subsequences are computed once over the whole series and ties are broken by the lowest start,
+/// such that the match is the same as that of [`best_match`] whatever the number of threads.
+#[cfg(feature = "rayon")]
+pub fn par_best_match(query: &[f64], series: &[f64], band: usize) -> Option<Match> {
+    assert!(!query.is_empty(), "Query should not be empty");
+    let query = z_normalize(query);
+    let stats = rolling_stats(series, query.len());
+    let shared = AtomicU64::new(f64::INFINITY.to_bits());
+    stats
+        .par_chunks(PARALLEL_CHUNK)
+        .enumerate()
+        .filter_map(|(c, stats)| {
+            scan(&query, series, stats, c * PARALLEL_CHUNK, band, |best| {
+                let previous = shared.fetch_min(best.to_bits(), Ordering::Relaxed);
+                best.min(f64::from_bits(previous))
+            })
+        })
+        .reduce_with(|x, y| {
+            if (y.distance, y.start) < (x.distance, x.start) {
+                y
+            } else {
+                x
+            }
+        })
+}
+
+/// Number of subsequences scanned by a task of [`par_best_match`].
+#[cfg(feature = "rayon")]
+pub const PARALLEL_CHUNK: usize = 256;
+
+/// Subsequence of `series` closest to the z-normalized `query` among those starting from
+/// `offset` with the statistics `stats`, where the alignments are abandoned against `bound` of
+/// the best distance found so far.
+fn scan(
+    query: &[f64],
+    series: &[f64],
+    stats: &[(f64, f64)],
+    offset: usize,
+    band: usize,
+    bound: impl Fn(f64) -> f64,
+) -> Option<Match> {
+    let mut best: Option<Match> = None;
+    for (start, (mean, std)) in (offset..).zip(stats.iter().copied()) {
+        let local = best.map_or(f64::INFINITY, |m| m.distance);
+        let candidate = |j: usize| z_score(series[start + j], mean, std);
+        if let Some(distance) = banded_distance(query, candidate, band, bound(local)) {
+            if distance < local {
+                best = Some(Match { start, distance });
+            }
+        }
+    }
+    best
+}
+
+/// Iterator over the subsequences of a series within a distance threshold of a query, aligned
+/// lazily as the iterator advances. See [`matches()`].
+#[derive(Debug, Clone)]
+pub struct Matches<'a> {
+    query: Vec<f64>,
+    series: &'a [f64],
+    band: usize,
+    threshold: f64,
+    stats: std::iter::Enumerate<std::vec::IntoIter<(f64, f64)>>,
+}
+
+impl Iterator for Matches<'_> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (start, (mean, std)) in self.stats.by_ref() {
+            let candidate = |j: usize| z_score(self.series[start + j], mean, std);
+            match banded_distance(&self.query, candidate, self.band, self.threshold) {
+                Some(distance) if distance <= self.threshold => {
+                    return Some(Match { start, distance })
+                }
+                _ => (),
+            }
+        }
+        None
+    }
+}
+
+/// Every subsequence of `series` whose distance to `query` within a band of width `band` does not
+/// exceed `threshold`, in the order of their start, yielded as soon as it is found such that a
+/// pipeline can act on the early matches while the scan goes on.
+pub fn matches<'a>(query: &[f64], series: &'a [f64], band: usize, threshold: f64) -> Matches<'a> {
+    assert!(!query.is_empty(), "Query should not be empty");
+    Matches {
+        query: z_normalize(query),
+        series,
+        band,
+        threshold,
+        stats: rolling_stats(series, query.len()).into_iter().enumerate(),
+    }
+}
+
+/// Up to `k` subsequences of `series` closest to `query` within a band of width `band`, from the
+/// closest one, no two of them starting less than `exclusion` elements apart, e.g. the length of
+/// the query for non-overlapping matches.
+///
+/// The matches are selected greedily from the closest one, such that every subsequence has to be
+/// aligned in full: abandoning the alignments against the `k`-th best distance could discard a
+/// subsequence needed once its better neighbors are excluded.
+pub fn top_matches(
+    query: &[f64],
+    series: &[f64],
+    band: usize,
+    k: usize,
+    exclusion: usize,
+) -> Vec<Match> {
+    assert!(!query.is_empty(), "Query should not be empty");
+    let query = z_normalize(query);
+    let mut candidates = rolling_stats(series, query.len())
+        .into_iter()
+        .enumerate()
+        .filter_map(|(start, (mean, std))| {
+            let candidate = |j: usize| z_score(series[start + j], mean, std);
+            let distance = banded_distance(&query, candidate, band, f64::INFINITY)?;
+            Some(Match { start, distance })
+        })
+        .collect::<Vec<Match>>();
+    candidates.sort_by(|x, y| {
+        x.distance
+            .total_cmp(&y.distance)
+            .then(x.start.cmp(&y.start))
+    });
+    let mut matches: Vec<Match> = Vec::with_capacity(k);
+    for candidate in candidates {
+        if matches.len() == k {
+            break;
+        }
+        if matches
+            .iter()
+            .all(|m| m.start.abs_diff(candidate.start) >= exclusion)
+        {
+            matches.push(candidate);
+        }
+    }
+    matches
+}
+
+/// Up to `k` non-overlapping subsequences of `series` closest to `query`, from the closest one,
+/// each of them of any length and aligned without normalization or band.
+///
+/// The query is aligned once against the whole series with a free start, yielding the best
+/// subsequence ending at every element of the series, from which the matches are selected
+/// greedily.
+pub fn subsequence_search(query: &[f64], series: &[f64], k: usize) -> Vec<SubsequenceMatch> {
+    assert!(!query.is_empty(), "Query should not be empty");
+    if series.is_empty() {
+        return Vec::new();
+    }
+    let (last, starts, backpointers) =
+        accumulate_subsequence(query, series, |x: &f64, y: &f64| f64::abs(x - y), true);
+    let mut ends = last
+        .into_iter()
+        .enumerate()
+        .filter_map(|(end, cost)| match cost {
+            Element::Value(distance) => Some((end, distance)),
+            Element::Inf => None,
+        })
+        .collect::<Vec<(usize, f64)>>();
+    ends.sort_by(|x, y| x.1.total_cmp(&y.1).then(x.0.cmp(&y.0)));
+    let mut matches: Vec<SubsequenceMatch> = Vec::with_capacity(k);
+    for (end, distance) in ends {
+        if matches.len() == k {
+            break;
+        }
+        let start = starts[end];
+        if matches.iter().all(|m| end < m.start || m.end < start) {
+            matches.push(SubsequenceMatch {
+                start,
+                end,
+                distance,
+                path: backpointers.path(
+                    query.len() - 1,
+                    end,
+                    StepPattern::Symmetric.predecessors(),
+                ),
+            });
+        }
+    }
+    matches
+}
+
+/// Candidate of `candidates`, all of the length of `query`, closest to `query` within a band of
+/// width `band`, or `None` if there is no candidate. Ties are broken by the lowest index.
+pub fn nearest(query: &[f64], candidates: &[Vec<f64>], band: usize) -> Option<Neighbor> {
+    assert_lengths(query, candidates);
+    let mut best: Option<Neighbor> = None;
+    for (index, candidate) in candidates.iter().enumerate() {
+        let bound = best.map_or(f64::INFINITY, |n| n.distance);
+        if let Some(distance) = banded_distance(query, |j| candidate[j], band, bound) {
+            if distance < bound {
+                best = Some(Neighbor { index, distance });
+            }
+        }
+    }
+    best
+}
+
+/// [`nearest`] computed in parallel, the best distance found so far being shared by every
+/// thread such that each candidate is abandoned against the best one of any thread.
+#[cfg(feature = "rayon")]
+pub fn par_nearest(query: &[f64], candidates: &[Vec<f64>], band: usize) -> Option<Neighbor> {
+    assert_lengths(query, candidates);
+    // Non-negative floats are ordered like the integers of their bits.
+    let best = AtomicU64::new(f64::INFINITY.to_bits());
+    candidates
+        .par_iter()
+        .enumerate()
+        .filter_map(|(index, candidate)| {
+            let bound = f64::from_bits(best.load(Ordering::Relaxed));
+            let distance = banded_distance(query, |j| candidate[j], band, bound)?;
+            best.fetch_min(distance.to_bits(), Ordering::Relaxed);
+            Some(Neighbor { index, distance })
+        })
+        .reduce_with(|x, y| {
+            if (y.distance, y.index) < (x.distance, x.index) {
+                y
+            } else {
+                x
+            }
+        })
+}
+
+/// Artifacts of a query computed once and reused across its comparisons with many candidates of
+/// its length: its z-normalization, its envelope within the band, its derivative and its
+/// piecewise aggregate approximation.
+#[derive(Debug, PartialEq, Clone)]
+pub struct QueryContext {
+    normalized: Vec<f64>,
+    band: usize,
+    envelope: Envelope,
+    derivative: Vec<f64>,
+    paa: Vec<f64>,
+}
+
+impl QueryContext {
+    /// Context of `query` compared within a band of width `band`, approximated by the means of
+    /// every `factor` elements.
+    pub fn new(query: &[f64], band: usize, factor: usize) -> Self {
+        assert!(!query.is_empty(), "Query should not be empty");
+        let normalized = z_normalize(query);
+        Self {
+            envelope: Envelope::new(&normalized, band),
+            derivative: Derivative::Keogh.apply(&normalized),
+            paa: Downsampling::Paa.apply_by(&normalized, factor),
+            normalized,
+            band,
+        }
+    }
+
+    /// Z-normalized query.
+    pub fn normalized(&self) -> &[f64] {
+        &self.normalized
+    }
+
+    /// Upper and lower envelopes of the z-normalized query, i.e. its maximum and minimum within
+    /// the band around every index.
+    pub fn envelope(&self) -> (&[f64], &[f64]) {
+        (self.envelope.upper(), self.envelope.lower())
+    }
+
+    /// Derivative of the z-normalized query, estimated by [`Derivative::Keogh`].
+    pub fn derivative(&self) -> &[f64] {
+        &self.derivative
+    }
+
+    /// Piecewise aggregate approximation of the z-normalized query.
+    pub fn paa(&self) -> &[f64] {
+        &self.paa
+    }
+
+    /// Lower bound of Keogh on the distance between the query and the z-normalized `candidate`:
+    /// the sum of the deviations of the candidate outside of the envelope.
+    pub fn lower_bound(&self, candidate: &[f64]) -> f64 {
+        self.envelope.lb_keogh(candidate)
+    }
+
+    /// Warped distance between the query and `candidate` of the same length, both z-normalized,
+    /// or `None` if it exceeds `bound` according to the lower bound or the abandoned alignment.
+    pub fn distance(&self, candidate: &[f64], bound: f64) -> Option<f64> {
+        assert!(
+            candidate.len() == self.normalized.len(),
+            "Candidate should have the length of the query"
+        );
+        let candidate = z_normalize(candidate);
+        if self.lower_bound(&candidate) > bound {
+            return None;
+        }
+        banded_distance(&self.normalized, |j| candidate[j], self.band, bound)
+            .filter(|distance| *distance <= bound)
+    }
+}
+
+/// Nearest neighbor search of a query by the cascade of pruning of the UCR suite: every
+/// candidate is compared by [`lb_kim`], then by [`lb_keogh`] with the envelope of the query,
+/// then by [`lb_keogh`] with the envelope of the candidate, and finally aligned within the band
+/// with early abandoning, each step being skipped as soon as it exceeds the best distance found
+/// so far.
+///
+/// [`lb_keogh`]: crate::lower_bounds::lb_keogh
+#[derive(Debug, PartialEq, Clone)]
+pub struct NearestNeighbor {
+    band: usize,
+    raw: (Vec<f64>, Envelope),
+    normalized: (Vec<f64>, Envelope),
+}
+
+impl NearestNeighbor {
+    /// Search of `query` within a band of width `band`.
+    pub fn new(query: &[f64], band: usize) -> Self {
+        assert!(!query.is_empty(), "Query should not be empty");
+        let normalized = z_normalize(query);
+        Self {
+            band,
+            raw: (query.to_vec(), Envelope::new(query, band)),
+            normalized: (normalized.clone(), Envelope::new(&normalized, band)),
+        }
+    }
+
+    /// Candidate of `candidates`, all of the length of the query, closest to the query, or
+    /// `None` if there is no candidate. Ties are broken by the lowest index as in [`nearest`].
+    pub fn in_collection(&self, candidates: &[Vec<f64>]) -> Option<Neighbor> {
+        assert_lengths(&self.raw.0, candidates);
+        let mut best: Option<Neighbor> = None;
+        for (index, candidate) in candidates.iter().enumerate() {
+            let bound = best.map_or(f64::INFINITY, |n| n.distance);
+            if let Some(distance) = self.cascade(&self.raw, candidate, bound) {
+                best = Some(Neighbor { index, distance });
+            }
+        }
+        best
+    }
+
+    /// Subsequence of `series` closest to the query, both z-normalized, or `None` if the series
+    /// is shorter than the query. Ties are broken by the lowest start as in [`best_match`].
+    pub fn in_series(&self, series: &[f64]) -> Option<Match> {
+        let n = self.raw.0.len();
+        let mut best: Option<Match> = None;
+        for (start, (mean, std)) in rolling_stats(series, n).into_iter().enumerate() {
+            let bound = best.map_or(f64::INFINITY, |m| m.distance);
+            let candidate = series[start..start + n]
+                .iter()
+                .map(|x| z_score(*x, mean, std))
+                .collect::<Vec<f64>>();
+            if let Some(distance) = self.cascade(&self.normalized, &candidate, bound) {
+                best = Some(Match { start, distance });
+            }
+        }
+        best
+    }
+
+    /// Warped distance between `query` and `candidate`, or `None` if it is not less than
+    /// `bound` according to a lower bound or the abandoned alignment.
+    fn cascade(&self, query: &(Vec<f64>, Envelope), candidate: &[f64], bound: f64) -> Option<f64> {
+        let (query, envelope) = query;
+        if lb_kim(query, candidate) >= bound
+            || envelope.lb_keogh(candidate) >= bound
+            || Envelope::new(candidate, self.band).lb_keogh(query) >= bound
+        {
+            return None;
+        }
+        banded_distance(query, |j| candidate[j], self.band, bound)
+            .filter(|distance| *distance < bound)
+    }
+}
+
+fn assert_lengths(query: &[f64], candidates: &[Vec<f64>]) {
+    assert!(!query.is_empty(), "Query should not be empty");
+    assert!(
+        candidates.iter().all(|c| c.len() == query.len()),
+        "Candidates should have the length of the query"
+    );
+}
+
+/// Warped distance between `query` and the candidate of the same length whose elements are
+/// given by `candidate`, within a band of width `band`, or `None` if it is abandoned for
+/// exceeding `bound`.
+pub(crate) fn banded_distance(
+    query: &[f64],
+    candidate: impl Fn(usize) -> f64,
+    band: usize,
+    bound: f64,
+) -> Option<f64> {
+    let n = query.len();
+    let elements = (0..n).map(candidate).collect::<Vec<f64>>();
+    let mut previous = vec![f64::INFINITY; n];
+    let mut current = vec![f64::INFINITY; n];
+    for (i, q) in query.iter().enumerate() {
+        let (begin, end) = (i.saturating_sub(band), (i + band + 1).min(n));
+        current.fill(f64::INFINITY);
+        for j in begin..end {
+            let best = match (i, j) {
+                (0, 0) => 0.0,
+                (0, _) => current[j - 1],
+                (_, 0) => previous[j],
+                _ => previous[j - 1].min(previous[j]).min(current[j - 1]),
+            };
+            current[j] = best + f64::abs(q - elements[j]);
+        }
+        if current[begin..end].iter().all(|d| *d > bound) {
+            return None;
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    Some(previous[n - 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        best_match, matches, nearest, subsequence_search, top_matches, NearestNeighbor, Neighbor,
+        QueryContext,
+    };
+    use crate::{preprocess::z_normalize, Algorithm, DynamicTimeWarping, Restriction};
+
+    #[test]
+    fn best_match_in_series() {
+        let query = [0.0, 1.0, 3.0, 1.2, 0.0, -0.5];
+        let mut series = (0..60)
+            .map(|i| f64::sin(i as f64 / 4.0))
+            .collect::<Vec<f64>>();
+        for (k, q) in [0.0, 1.0, 3.0, 1.0, 0.0, -0.5].iter().enumerate() {
+            series[31 + k] = 10.0 + 2.0 * q;
+        }
+        let exact = (0..=series.len() - query.len())
+            .map(|s| {
+                DynamicTimeWarping::with_param(
+                    &z_normalize(&query),
+                    &z_normalize(&series[s..s + query.len()]),
+                    Restriction::Band(1),
+                )
+                .distance()
+            })
+            .fold(f64::INFINITY, f64::min);
+        let found = best_match(&query, &series, 1).unwrap();
+        assert_eq!(found.start, 31);
+        assert!(found.distance > 0.0 && f64::abs(found.distance - exact) < 1e-9);
+        assert_eq!(best_match(&query, &series[..3], 1), None);
+        let offset = series.iter().map(|x| 1e6 + x).collect::<Vec<f64>>();
+        let cut = best_match(&offset[17..29], &offset, 1).unwrap();
+        assert_eq!(cut.start, 17);
+        assert!(cut.distance < 1e-6);
+        #[cfg(feature = "rayon")]
+        {
+            assert_eq!(super::par_best_match(&query, &series, 1), Some(found));
+            assert_eq!(super::par_best_match(&query, &series[..3], 1), None);
+            let long = (0..3000)
+                .map(|i| 1e6 + f64::sin(i as f64 / 7.0) * (1.0 + (i % 13) as f64 / 10.0))
+                .collect::<Vec<f64>>();
+            let pattern = &long[1234..1274];
+            let sequential = best_match(pattern, &long, 4);
+            for threads in [1, 2, 3, 8] {
+                let pool = rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .unwrap();
+                let parallel = pool.install(|| super::par_best_match(pattern, &long, 4));
+                assert_eq!(parallel, sequential);
+            }
+        }
+    }
+
+    #[test]
+    fn nearest_candidate() {
+        let query = (0..30)
+            .map(|i| f64::sin(i as f64 / 3.0))
+            .collect::<Vec<f64>>();
+        let candidates = (0..200)
+            .map(|k| {
+                (0..30)
+                    .map(|i| f64::sin(i as f64 / 3.0 + k as f64 / 50.0) + (k % 7) as f64 / 10.0)
+                    .collect::<Vec<f64>>()
+            })
+            .collect::<Vec<Vec<f64>>>();
+        let exact = candidates
+            .iter()
+            .enumerate()
+            .map(|(index, c)| Neighbor {
+                index,
+                distance: DynamicTimeWarping::with_param(&query, c, Restriction::Band(3))
+                    .distance(),
+            })
+            .reduce(|x, y| if y.distance < x.distance { y } else { x });
+        assert_eq!(nearest(&query, &candidates, 3), exact);
+        #[cfg(feature = "rayon")]
+        assert_eq!(super::par_nearest(&query, &candidates, 3), exact);
+        assert_eq!(nearest(&query, &[], 3), None);
+    }
+
+    #[test]
+    fn top_non_overlapping_matches() {
+        let pattern = [0.0, 2.0, 5.0, 2.0, 0.0];
+        let mut series = (0..80)
+            .map(|i| f64::sin(i as f64 / 5.0) / 4.0)
+            .collect::<Vec<f64>>();
+        for (start, scale) in [(10, 1.0), (40, 3.0), (62, 0.5)] {
+            for (k, p) in pattern.iter().enumerate() {
+                series[start + k] = scale * p;
+            }
+        }
+        let matches = top_matches(&pattern, &series, 1, 3, pattern.len());
+        let mut starts = matches.iter().map(|m| m.start).collect::<Vec<usize>>();
+        starts.sort();
+        assert_eq!(starts, [10, 40, 62]);
+        assert!(matches.windows(2).all(|w| w[0].distance <= w[1].distance));
+        let best = best_match(&pattern, &series, 1).unwrap();
+        assert_eq!(matches[0].distance, best.distance);
+        assert_eq!(top_matches(&pattern, &series, 1, 100, 80).len(), 1);
+    }
+
+    #[test]
+    fn streaming_matches() {
+        let pattern = [0.0, 2.0, 5.0, 2.0, 0.0];
+        let series = (0..50)
+            .map(|i| pattern[i % 10 % 5] * if i % 10 < 5 { 1.0 } else { -1.0 })
+            .collect::<Vec<f64>>();
+        let mut found = matches(&pattern, &series, 1, 1e-9);
+        assert_eq!(found.next().map(|m| m.start), Some(0));
+        let starts = found.map(|m| m.start).collect::<Vec<usize>>();
+        assert_eq!(starts, [10, 20, 30, 40]);
+        let all = matches(&pattern, &series, 1, f64::INFINITY).count();
+        assert_eq!(all, series.len() - pattern.len() + 1);
+    }
+
+    #[test]
+    fn query_context_reuse() {
+        let query = (0..24)
+            .map(|i| f64::sin(i as f64 / 3.0))
+            .collect::<Vec<f64>>();
+        let context = QueryContext::new(&query, 2, 4);
+        assert_eq!(context.paa().len(), 6);
+        let (upper, lower) = context.envelope();
+        assert!((0..24)
+            .all(|i| lower[i] <= context.normalized()[i] && context.normalized()[i] <= upper[i]));
+        for shift in 1..6 {
+            let candidate = (0..24)
+                .map(|i| 3.0 * f64::sin((i + shift) as f64 / 3.0) + 1.0)
+                .collect::<Vec<f64>>();
+            let exact = DynamicTimeWarping::with_param(
+                context.normalized(),
+                &z_normalize(&candidate),
+                Restriction::Band(2),
+            )
+            .distance();
+            let distance = context.distance(&candidate, f64::INFINITY).unwrap();
+            assert!(f64::abs(distance - exact) < 1e-9);
+            assert!(context.lower_bound(&z_normalize(&candidate)) <= distance + 1e-9);
+            assert_eq!(context.distance(&candidate, distance / 2.0), None);
+        }
+    }
+
+    #[test]
+    fn subsequence_search_matches() {
+        let query = [1.0, 5.0, 2.0];
+        let series = [
+            0.0, 1.0, 5.0, 5.0, 2.0, 0.0, 0.0, 1.0, 4.0, 2.0, 0.0, 1.0, 5.0, 2.0, 0.0,
+        ];
+        let found = subsequence_search(&query, &series, 2);
+        assert_eq!(found.len(), 2);
+        assert_eq!(
+            (found[0].start, found[0].end, found[0].distance),
+            (1, 4, 0.0)
+        );
+        assert_eq!(
+            (found[1].start, found[1].end, found[1].distance),
+            (11, 13, 0.0)
+        );
+        assert_eq!(found[0].path, [(0, 1), (1, 2), (1, 3), (2, 4)]);
+        let all = subsequence_search(&query, &series, 10);
+        assert!(all.windows(2).all(|w| w[0].distance <= w[1].distance));
+        assert!(all
+            .iter()
+            .enumerate()
+            .all(|(k, m)| all[..k].iter().all(|o| m.end < o.start || o.end < m.start)));
+    }
+
+    #[test]
+    fn nearest_neighbor_cascade() {
+        let series = (0..200)
+            .map(|i| f64::sin(i as f64 / 7.0) + f64::sin(i as f64 / 3.1) / 2.0)
+            .collect::<Vec<f64>>();
+        let query = series[120..140]
+            .iter()
+            .enumerate()
+            .map(|(i, x)| x + 0.01 * (i % 3) as f64)
+            .collect::<Vec<f64>>();
+        let search = NearestNeighbor::new(&query, 3);
+        let found = search.in_series(&series).unwrap();
+        let expected = best_match(&query, &series, 3).unwrap();
+        assert_eq!(found.start, expected.start);
+        assert!(f64::abs(found.distance - expected.distance) < 1e-9);
+        let candidates = (0..9)
+            .map(|k| series[20 * k..20 * k + 20].to_vec())
+            .collect::<Vec<Vec<f64>>>();
+        let neighbor = search.in_collection(&candidates).unwrap();
+        assert_eq!(Some(neighbor), nearest(&query, &candidates, 3));
+        assert_eq!(neighbor.index, 6);
+    }
+}
@@ -1,4 +1,4 @@
-use std::ops::Sub;
+use std::{ops::Sub, time::Duration};
 
 /// Compute the dynamic time warping of two sequence.
 pub trait Algorithm<O> {
@@ -19,6 +19,28 @@ pub trait Algorithm<O> {
     {
         Self::with_closure(a, b, |a, b| a.distance(b))
     }
+
+    /// Dynamic time warping between sequences `a` and `b` using the distance closure `distance`
+    /// returning costs of a narrower type `C`, e.g. `u16`, converted to the accumulated type `O`,
+    /// e.g. `u64`, before being accumulated.
+    fn with_cost_closure<T, C>(a: &[T], b: &[T], distance: impl Fn(&T, &T) -> C) -> Self
+    where
+        C: Into<O>,
+        Self: Sized,
+    {
+        Self::with_closure(a, b, |a, b| distance(a, b).into())
+    }
+
+    /// Dynamic time warping between sequences `a` and `b` of a narrower type, e.g. `f32`,
+    /// widened to `O`, e.g. `f64`, before computing the distances and accumulating them.
+    fn between_widened<T>(a: &[T], b: &[T]) -> Self
+    where
+        T: Copy + Into<O>,
+        O: Distance<O>,
+        Self: Sized,
+    {
+        Self::with_closure(a, b, |a, b| (*a).into().distance(&(*b).into()))
+    }
 }
 
 /// Compute the dynamic time warping of two sequence with initial hyper-parameters.
@@ -34,6 +56,21 @@ pub trait ParameterizedAlgorithm<D> {
         param: Self::Param,
     ) -> Self;
 
+    /// Dynamic time warping between sequences `a` and `b` using the distance closure `distance`,
+    /// whose costs of type `C` are converted to the accumulated type `D`, and parameter `param`.
+    fn with_cost_closure_and_param<T, C>(
+        a: &[T],
+        b: &[T],
+        distance: impl Fn(&T, &T) -> C,
+        param: Self::Param,
+    ) -> Self
+    where
+        C: Into<D>,
+        Self: Sized,
+    {
+        Self::with_closure_and_param(a, b, |a, b| distance(a, b).into(), param)
+    }
+
     /// Dynamic time warping between sequences `a` and `b` using the parameter `param`
     fn with_param<T>(a: &[T], b: &[T], param: Self::Param) -> Self
     where
@@ -45,8 +82,7 @@ pub trait ParameterizedAlgorithm<D> {
 }
 
 /// An arbitrary distance between two objects.
-pub trait Distance<O>
-{
+pub trait Distance<O> {
     /// Distance between `self` and `other`.
     fn distance(&self, other: &Self) -> O;
 }
@@ -63,7 +99,97 @@ where
         } else {
             *other - *self
         }
-        // .into()
+    }
+}
+
+/// A numeric cost accumulated by the dynamic programming recursion.
+pub trait Cost {
+    /// `self` multiplied by the step weight `weight`.
+    fn weighted(&self, weight: f64) -> Self;
+
+    /// `self` mapped through the function `f` of its numeric value, e.g. to transform it.
+    fn mapped(&self, f: impl Fn(f64) -> f64) -> Self;
+
+    /// Whether `self` is neither infinite nor not a number.
+    fn is_finite(&self) -> bool {
+        true
     }
 
+    /// Cost greater than every other one and absorbing in sums, e.g. the infinity of the floats,
+    /// if any. It stands for the unreachable cells such that the accumulated costs are stored as
+    /// they are instead of being wrapped with an infinite variant, halving their memory.
+    fn infinity() -> Option<Self>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+macro_rules! impl_float_cost {
+    ($($t:ty),*) => {
+        $(impl Cost for $t {
+            fn weighted(&self, weight: f64) -> Self {
+                (*self as f64 * weight) as $t
+            }
+
+            fn mapped(&self, f: impl Fn(f64) -> f64) -> Self {
+                f(*self as f64) as $t
+            }
+
+            fn is_finite(&self) -> bool {
+                <$t>::is_finite(*self)
+            }
+
+            fn infinity() -> Option<Self> {
+                Some(<$t>::INFINITY)
+            }
+        })*
+    };
+}
+
+macro_rules! impl_integer_cost {
+    ($($t:ty),*) => {
+        $(impl Cost for $t {
+            fn weighted(&self, weight: f64) -> Self {
+                (*self as f64 * weight).round() as $t
+            }
+
+            fn mapped(&self, f: impl Fn(f64) -> f64) -> Self {
+                f(*self as f64).round() as $t
+            }
+        })*
+    };
+}
+
+impl_float_cost!(f32, f64);
+impl_integer_cost!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+/// Durations, e.g. the distances between `Instant`s or between `Duration`s of event times.
+impl Cost for Duration {
+    fn weighted(&self, weight: f64) -> Self {
+        self.mul_f64(weight)
+    }
+
+    /// The numeric value of a duration is its number of seconds.
+    fn mapped(&self, f: impl Fn(f64) -> f64) -> Self {
+        Self::from_secs_f64(f(self.as_secs_f64()).max(0.0))
+    }
+}
+
+/// Signed durations, e.g. the distances between chrono's `NaiveDateTime`s or `DateTime`s.
+#[cfg(feature = "chrono")]
+impl Cost for chrono::TimeDelta {
+    fn weighted(&self, weight: f64) -> Self {
+        match self.num_nanoseconds() {
+            Some(nanos) => Self::nanoseconds((nanos as f64 * weight).round() as i64),
+            None => Self::milliseconds((self.num_milliseconds() as f64 * weight).round() as i64),
+        }
+    }
+
+    /// The numeric value of a duration is its number of seconds.
+    fn mapped(&self, f: impl Fn(f64) -> f64) -> Self {
+        let seconds = self.num_milliseconds() as f64 / 1e3;
+        Self::milliseconds((f(seconds) * 1e3).round() as i64)
+    }
 }
@@ -0,0 +1,110 @@
+//! Cross-checking of approximate alignments against exact dynamic time warping.
+//!
+//! Every `n`-th approximate alignment (see [`set_every`]), one in [`DEFAULT_EVERY`] by default,
+//! is recomputed exactly and the pair of distances is collected, giving the distribution of the
+//! approximation error on real data. Only the latest [`CAPACITY`] observations are kept.
+
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use crate::{multiscale::Method, Algorithm, DynamicTimeWarping};
+
+/// Default sampling rate: one approximate alignment out of this many is verified.
+pub const DEFAULT_EVERY: usize = 100;
+
+/// Maximum number of observations kept, the oldest ones being dropped first.
+pub const CAPACITY: usize = 1024;
+
+static EVERY: AtomicUsize = AtomicUsize::new(DEFAULT_EVERY);
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+static OBSERVATIONS: Mutex<VecDeque<Observation>> = Mutex::new(VecDeque::new());
+
+/// Approximate and exact distances of a verified alignment.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Observation {
+    /// Approximate method and its parameters.
+    pub method: Method,
+    /// Approximate warped distance.
+    pub approximate: f64,
+    /// Exact warped distance.
+    pub exact: f64,
+}
+
+impl Observation {
+    /// Excess of the approximate distance over the exact one.
+    pub fn error(&self) -> f64 {
+        self.approximate - self.exact
+    }
+}
+
+/// Verify one approximate alignment out of every `n`, or none if `n` is zero.
+pub fn set_every(n: usize) {
+    EVERY.store(n, Ordering::Relaxed);
+}
+
+/// Latest observations collected so far, from the oldest one.
+pub fn observations() -> Vec<Observation> {
+    OBSERVATIONS.lock().unwrap().iter().copied().collect()
+}
+
+/// Clear the collected observations.
+pub fn reset() {
+    OBSERVATIONS.lock().unwrap().clear();
+    CALLS.store(0, Ordering::Relaxed);
+}
+
+/// Verify the approximate distance `approximate` between `a` and `b` if the call is sampled.
+pub(crate) fn record(a: &[f64], b: &[f64], method: Method, approximate: f64) {
+    let every = EVERY.load(Ordering::Relaxed);
+    let call = CALLS.fetch_add(1, Ordering::Relaxed);
+    if every == 0 || !call.is_multiple_of(every) {
+        return;
+    }
+    let exact = DynamicTimeWarping::between(a, b).distance();
+    let mut observations = OBSERVATIONS.lock().unwrap();
+    if observations.len() == CAPACITY {
+        observations.pop_front();
+    }
+    observations.push_back(Observation {
+        method,
+        approximate,
+        exact,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{observations, set_every, CAPACITY};
+    use crate::multiscale::{coarse_to_fine, Method};
+
+    #[test]
+    fn verify_sampled_calls() {
+        set_every(1);
+        let a = (0..30)
+            .map(|i| f64::sin(i as f64 / 3.0))
+            .collect::<Vec<f64>>();
+        let b = (0..20)
+            .map(|i| f64::cos(i as f64 / 2.0))
+            .collect::<Vec<f64>>();
+        for _ in 0..4 {
+            coarse_to_fine(&a, &b, 2, 0);
+        }
+        let method = Method::CoarseToFine {
+            factor: 2,
+            radius: 0,
+        };
+        let observations = observations();
+        assert!(observations.len() <= CAPACITY);
+        let observations = observations
+            .into_iter()
+            .filter(|o| o.method == method)
+            .collect::<Vec<_>>();
+        assert_eq!(observations.len(), 4);
+        assert!(observations.iter().all(|o| o.error() >= -1e-9));
+    }
+}
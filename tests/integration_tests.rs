@@ -1,6 +1,9 @@
-use std::str::FromStr;
+use std::{str::FromStr, time::Duration};
 
-use dtw_rs::{Algorithm, DynamicTimeWarping, ParameterizedAlgorithm, Restriction};
+use dtw_rs::{
+    Algorithm, CostTable, DynamicTimeWarping, LocalContinuity, Missing, Parameters, Restriction,
+    SlopeWeighting, StepPattern,
+};
 use float_cmp::assert_approx_eq;
 
 #[test]
@@ -64,6 +67,19 @@ fn dynamic_time_warping_with_corner_out_of_band() {
     assert_eq!(*dtw.path(), expected_path);
 }
 
+#[test]
+fn dynamic_time_warping_with_fitted_band() {
+    let a = [1.0, 3.0, 9.0, 2.0];
+    let b = [2.0, 0.0];
+    let expected_path = [(0, 0), (1, 0), (2, 0), (3, 1)];
+    let expected_distance = 11.0;
+
+    let dtw = DynamicTimeWarping::with_param(&a, &b, Restriction::FittedBand(1));
+
+    assert_eq!(dtw.distance(), expected_distance);
+    assert_eq!(*dtw.path(), expected_path);
+}
+
 #[test]
 fn dynamic_time_warping_with_band_restricted_and_distance_closure() {
     let a = [1.0, 3.0, 9.0, 2.0, 1.0];
@@ -83,6 +99,242 @@ fn dynamic_time_warping_with_band_restricted_and_distance_closure() {
     assert_eq!(*dtw.path(), expected_path);
 }
 
+#[test]
+fn dynamic_time_warping_with_itakura_parallelogram() {
+    let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+    let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+    let restriction = Restriction::Itakura(2.0);
+
+    let dtw = DynamicTimeWarping::with_param(&a, &b, restriction.clone());
+
+    assert!(dtw.distance() >= DynamicTimeWarping::between(&a, &b).distance());
+    assert!(dtw
+        .path()
+        .iter()
+        .all(|idx| restriction.contains(*idx, (5, 6))));
+}
+
+#[test]
+fn dynamic_time_warping_with_asymmetric_step_pattern() {
+    let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+    let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+    let expected_path = [(0, 0), (1, 2), (2, 3), (3, 5), (4, 5)];
+    let expected_distance = 6.0;
+
+    let dtw = DynamicTimeWarping::with_param(&a, &b, StepPattern::Asymmetric);
+
+    assert_eq!(dtw.distance(), expected_distance);
+    assert_eq!(*dtw.path(), expected_path);
+}
+
+#[test]
+fn dynamic_time_warping_with_missing_values() {
+    let a = [Some(1.0), Some(3.0), None, Some(9.0), Some(2.0), Some(1.0)];
+    let b = [
+        Some(2.0),
+        Some(0.0),
+        Some(0.0),
+        None,
+        Some(8.0),
+        Some(7.0),
+        Some(2.0),
+    ];
+    let expected_path = [
+        (0, 0),
+        (0, 1),
+        (1, 2),
+        (2, 3),
+        (3, 4),
+        (3, 5),
+        (4, 6),
+        (5, 6),
+    ];
+    let expected_distance = 9.0;
+
+    let dtw =
+        DynamicTimeWarping::with_missing(&a, &b, Missing::PairwiseComplete, |a, b| f64::abs(a - b));
+
+    assert_eq!(dtw.distance(), expected_distance);
+    assert_eq!(*dtw.path(), expected_path);
+}
+
+#[test]
+fn dynamic_time_warping_with_masks() {
+    let a = [1.0, 2.0, 100.0, 3.0, 4.0];
+    let b = [1.0, 2.0, 3.0, 4.0];
+    let mask_a = [false, false, true, false, false];
+    let mask_b = [false; 4];
+    let expected_path = [(0, 0), (1, 1), (2, 1), (3, 2), (4, 3)];
+    let expected_distance = 0.0;
+
+    let dtw = DynamicTimeWarping::with_masks(&a, &b, &mask_a, &mask_b, |a, b| f64::abs(a - b));
+
+    assert_eq!(dtw.distance(), expected_distance);
+    assert_eq!(*dtw.path(), expected_path);
+}
+
+#[test]
+fn dynamic_time_warping_with_max_run() {
+    let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+    let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+    let unconstrained = DynamicTimeWarping::with_param(&a, &b, Parameters::new().max_run(10));
+    assert_eq!(unconstrained.distance(), 9.0);
+    assert_eq!(
+        *unconstrained.path(),
+        [(0, 0), (0, 1), (1, 2), (2, 3), (2, 4), (3, 5), (4, 5)]
+    );
+
+    let a = [1.0, 1.0, 1.0, 5.0];
+    let b = [1.0, 5.0];
+    let dtw = DynamicTimeWarping::with_param(&a, &b, Parameters::new().max_run(1));
+    assert_eq!(dtw.distance(), 4.0);
+    assert_eq!(*dtw.path(), [(0, 0), (1, 0), (2, 1), (3, 1)]);
+}
+
+#[test]
+fn dynamic_time_warping_with_rabiner_local_continuity() {
+    let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+    let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+
+    let dtw = DynamicTimeWarping::with_param(&a, &b, StepPattern::Rabiner(LocalContinuity::TypeII));
+    assert_eq!(dtw.distance(), 11.0);
+    assert_eq!(
+        *dtw.path(),
+        [(0, 0), (1, 1), (1, 2), (2, 3), (2, 4), (3, 5), (4, 5)]
+    );
+
+    let dtw =
+        DynamicTimeWarping::with_param(&a, &b, StepPattern::Rabiner(LocalContinuity::TypeIII));
+    assert_eq!(dtw.distance(), 7.0);
+    assert_eq!(*dtw.path(), [(0, 0), (1, 2), (2, 4), (4, 5)]);
+}
+
+#[test]
+fn dynamic_time_warping_with_rabiner_juang_step_pattern() {
+    let a = [1.0, 3.0, 9.0, 2.0, 1.0];
+    let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0];
+    let step_pattern = StepPattern::RabinerJuang(LocalContinuity::TypeII, SlopeWeighting::D, false);
+
+    let dtw = DynamicTimeWarping::with_param(&a, &b, step_pattern);
+
+    assert_eq!(dtw.distance(), 15.0);
+    assert_eq!(
+        *dtw.path(),
+        [(0, 0), (1, 1), (1, 2), (2, 3), (2, 4), (3, 5), (4, 5)]
+    );
+}
+
+#[test]
+fn dynamic_time_warping_with_rabiner_juang_types() {
+    use LocalContinuity::*;
+    use SlopeWeighting::*;
+    let a = [1.0, 3.0, 9.0, 2.0, 1.0, 4.0, 4.0];
+    let b = [2.0, 0.0, 0.0, 8.0, 7.0, 2.0, 5.0, 3.0];
+    // Distances and paths under `rabinerJuangStepPattern(type, slope.weighting, smoothed)` of
+    // the R dtw package, recursing like its `dtw`, with zero-based indices.
+    type Case = (
+        LocalContinuity,
+        SlopeWeighting,
+        bool,
+        f64,
+        &'static [(usize, usize)],
+    );
+    #[rustfmt::skip]
+    let expected: [Case; 8] = [
+        (TypeI, D, false, 15.0, &[(0, 0), (0, 1), (0, 2), (1, 2), (2, 3), (2, 4), (3, 5), (4, 5), (5, 6), (5, 7), (6, 7)]),
+        (TypeII, D, false, 19.0, &[(0, 0), (1, 1), (1, 2), (2, 3), (2, 4), (3, 5), (4, 5), (5, 6), (6, 7)]),
+        (TypeIII, C, false, 8.0, &[(0, 0), (1, 2), (2, 3), (3, 5), (5, 6), (6, 7)]),
+        (TypeIV, C, true, 8.0, &[(0, 0), (1, 2), (2, 3), (3, 5), (4, 5), (5, 7), (6, 7)]),
+        (TypeV, A, false, 6.0, &[(0, 0), (1, 1), (1, 2), (2, 3), (2, 4), (3, 5), (4, 5), (5, 5), (6, 6), (6, 7)]),
+        (TypeV, D, true, 20.0, &[(0, 0), (1, 1), (1, 2), (2, 3), (2, 4), (3, 5), (4, 5), (5, 6), (6, 7)]),
+        (TypeVI, B, false, 22.0, &[(0, 0), (1, 1), (2, 2), (2, 3), (3, 4), (4, 5), (5, 6), (6, 7)]),
+        (TypeVII, C, false, 8.0, &[(0, 0), (1, 2), (2, 3), (3, 5), (4, 5), (5, 6), (6, 7)]),
+    ];
+    for (local, weighting, smoothed, distance, path) in expected {
+        let step_pattern = StepPattern::RabinerJuang(local, weighting, smoothed);
+        let dtw = DynamicTimeWarping::with_param(&a, &b, step_pattern);
+        assert_eq!(dtw.distance(), distance, "{step_pattern:?}");
+        assert_eq!(dtw.path(), path, "{step_pattern:?}");
+    }
+    let symmetric2 = DynamicTimeWarping::with_param(&a, &b, StepPattern::Symmetric2);
+    assert_eq!(symmetric2.distance(), 15.0);
+}
+
+#[test]
+fn dynamic_time_warping_with_cost_table() {
+    let a = [0, 1, 1, 2];
+    let b = [0, 2, 2];
+    let table = CostTable::new(
+        vec![vec![0, 4, 4], vec![4, 0, 1], vec![4, 1, 0]],
+        vec![2, 2, 0],
+        vec![2, 3, 3],
+    );
+    let expected_path = [(0, 0), (1, 0), (2, 1), (3, 2)];
+    let expected_distance = 4;
+
+    let dtw = DynamicTimeWarping::with_cost_table(&a, &b, &table);
+
+    assert_eq!(dtw.distance(), expected_distance);
+    assert_eq!(*dtw.path(), expected_path);
+}
+
+#[test]
+fn dynamic_time_warping_widened_to_f64() {
+    let a = vec![0.0_f32; 100_000];
+    let b = [0.1_f32];
+
+    let narrow = DynamicTimeWarping::<f32>::between(&a, &b);
+    let wide = DynamicTimeWarping::<f64>::between_widened(&a, &b);
+
+    let expected = 100_000.0 * 0.1_f32 as f64;
+    assert!(f64::abs(wide.distance() - expected) < 1e-6);
+    assert!(f64::abs(narrow.distance() as f64 - expected) > 1.0);
+}
+
+#[test]
+fn dynamic_time_warping_with_narrow_costs() {
+    let a = vec![0_u16; 3];
+    let b = [u16::MAX, u16::MAX];
+    let dtw: DynamicTimeWarping<u64> =
+        DynamicTimeWarping::with_cost_closure(&a, &b, |x: &u16, y: &u16| x.abs_diff(*y));
+    assert_eq!(dtw.distance(), 3 * u16::MAX as u64);
+    let banded: DynamicTimeWarping<u64> = DynamicTimeWarping::with_cost_closure_and_param(
+        &a,
+        &b,
+        |x: &u16, y: &u16| x.abs_diff(*y),
+        Restriction::Band(1),
+    );
+    assert_eq!(banded.distance(), dtw.distance());
+}
+
+#[test]
+fn dynamic_time_warping_between_durations() {
+    let a = [0, 10, 20, 35].map(Duration::from_millis);
+    let b = [0, 12, 20, 20, 34].map(Duration::from_millis);
+
+    let dtw = DynamicTimeWarping::between(&a, &b);
+
+    assert_eq!(dtw.distance(), Duration::from_millis(3));
+    assert_eq!(*dtw.path(), [(0, 0), (1, 1), (2, 2), (2, 3), (3, 4)]);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn dynamic_time_warping_between_date_times() {
+    use chrono::{NaiveDate, TimeDelta};
+    let day = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    let times = |hours: &[u32]| {
+        hours
+            .iter()
+            .map(|h| day.and_hms_opt(*h, 0, 0).unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let dtw = DynamicTimeWarping::between(&times(&[8, 12, 18]), &times(&[9, 12, 17]));
+
+    assert_eq!(dtw.distance(), TimeDelta::hours(2));
+}
+
 #[inline]
 fn into_float_vec<T: FromStr>(line: &str) -> Vec<T> {
     line.split(' ')